@@ -0,0 +1,188 @@
+use crate::{
+    binary::{ByteSource, Cursor, write_signed, write_varint},
+    id::{
+        DimensionRange::{self, AfterUnLimitRange, Any, BeforeUnLimitRange, LimitRange, Single},
+        SpaceTimeId,
+    },
+};
+
+/// 各次元の範囲 (f, x, y, t)
+type Ranges = (
+    DimensionRange<i32>,
+    DimensionRange<u32>,
+    DimensionRange<u32>,
+    DimensionRange<u32>,
+);
+
+/// ID の値以外の部分 (z, 各次元の種類, i)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Shape {
+    pub(crate) z: u8,
+    pub(crate) tags: [u8; 4],
+    pub(crate) i: u32,
+}
+
+impl Shape {
+    pub(crate) fn of(id: &SpaceTimeId) -> Self {
+        Self {
+            z: id.z(),
+            tags: [tag(id.f()), tag(id.x()), tag(id.y()), tag(id.t())],
+            i: id.i(),
+        }
+    }
+
+    pub(crate) fn write(&self, out: &mut Vec<u8>) {
+        out.push(self.z);
+        out.push(self.tags[0] | (self.tags[1] << 4));
+        out.push(self.tags[2] | (self.tags[3] << 4));
+        write_varint(out, self.i as u64);
+    }
+
+    pub(crate) fn read<S: ByteSource>(cursor: &mut S) -> Result<Self, String> {
+        let z = cursor.byte()?;
+        let fx = cursor.byte()?;
+        let yt = cursor.byte()?;
+        let tags = [fx & 0x0F, fx >> 4, yt & 0x0F, yt >> 4];
+        if let Some(tag) = tags.iter().find(|&&tag| tag > 4) {
+            return Err(format!("unknown dimension tag {}", tag));
+        }
+        let i = u32::try_from(cursor.varint()?).map_err(|_| "i is out of u32 range")?;
+        Ok(Self { z, tags, i })
+    }
+
+    /// この形の ID が持つ値の数
+    pub(crate) fn value_count(&self) -> usize {
+        self.tags
+            .iter()
+            .map(|tag| match tag {
+                1 => 2,
+                4 => 0,
+                _ => 1,
+            })
+            .sum()
+    }
+
+    /// 値の列から ID を組み立て、`SpaceTimeId::new` で検証する
+    pub(crate) fn build(&self, values: &[i64]) -> Result<SpaceTimeId, String> {
+        let (f, x, y, t) = self.ranges(values)?;
+        SpaceTimeId::new(self.z, f, x, y, self.i, t)
+    }
+
+    /// 値の列から ID を組み立てる。x の s > e は日付変更線をまたぐ範囲として 2 つに分ける
+    pub(crate) fn build_wrapping(&self, values: &[i64]) -> Result<Vec<SpaceTimeId>, String> {
+        let (f, x, y, t) = self.ranges(values)?;
+        SpaceTimeId::new_wrapping(self.z, f, x, y, self.i, t)
+    }
+
+    /// 値の列を各次元の範囲に戻す
+    fn ranges(&self, values: &[i64]) -> Result<Ranges, String> {
+        let mut rest = values;
+        let mut next = |tag: u8| -> Result<DimensionRange<i64>, String> {
+            let take = match tag {
+                1 => 2,
+                4 => 0,
+                _ => 1,
+            };
+            if rest.len() < take {
+                return Err("missing dimension values".into());
+            }
+            let (head, tail) = rest.split_at(take);
+            rest = tail;
+            Ok(match tag {
+                0 => Single(head[0]),
+                1 => LimitRange(
+                    head[0],
+                    head[0].checked_add(head[1]).ok_or("range end overflows")?,
+                ),
+                2 => BeforeUnLimitRange(head[0]),
+                3 => AfterUnLimitRange(head[0]),
+                _ => Any,
+            })
+        };
+
+        let f = narrow(next(self.tags[0])?, "F")?;
+        let x = narrow(next(self.tags[1])?, "X")?;
+        let y = narrow(next(self.tags[2])?, "Y")?;
+        let t = narrow(next(self.tags[3])?, "T")?;
+        Ok((f, x, y, t))
+    }
+}
+
+/// ID の値を Shape で決まる順に並べる
+pub(crate) fn values(id: &SpaceTimeId) -> Vec<i64> {
+    let mut out = Vec::with_capacity(8);
+    push_values(&mut out, id.f());
+    push_values(&mut out, id.x());
+    push_values(&mut out, id.y());
+    push_values(&mut out, id.t());
+    out
+}
+
+fn push_values<T: Into<i64> + Copy>(out: &mut Vec<i64>, range: DimensionRange<T>) {
+    match range {
+        Single(v) | BeforeUnLimitRange(v) | AfterUnLimitRange(v) => out.push(v.into()),
+        LimitRange(s, e) => {
+            out.push(s.into());
+            out.push(e.into() - s.into());
+        }
+        Any => {}
+    }
+}
+
+fn tag<T>(range: DimensionRange<T>) -> u8 {
+    match range {
+        Single(_) => 0,
+        LimitRange(_, _) => 1,
+        BeforeUnLimitRange(_) => 2,
+        AfterUnLimitRange(_) => 3,
+        Any => 4,
+    }
+}
+
+fn narrow<T: TryFrom<i64>>(
+    range: DimensionRange<i64>,
+    name: &str,
+) -> Result<DimensionRange<T>, String> {
+    let convert =
+        |v: i64| T::try_from(v).map_err(|_| format!("{} value {} is out of range", name, v));
+    Ok(match range {
+        Single(v) => Single(convert(v)?),
+        LimitRange(s, e) => LimitRange(convert(s)?, convert(e)?),
+        BeforeUnLimitRange(v) => BeforeUnLimitRange(convert(v)?),
+        AfterUnLimitRange(v) => AfterUnLimitRange(convert(v)?),
+        Any => Any,
+    })
+}
+
+impl SpaceTimeId {
+    /// Encodes the ID with the compact binary format described in [`crate::binary`].
+    ///
+    /// The encoding has no header; use [`SpaceTimeIdSet::to_bytes`](crate::set::SpaceTimeIdSet::to_bytes)
+    /// or [`IdWriter`](crate::binary::stream::IdWriter) to store many IDs.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(8);
+        Shape::of(self).write(&mut out);
+        for value in values(self) {
+            write_signed(&mut out, value);
+        }
+        out
+    }
+
+    /// Decodes an ID written by [`to_bytes`](Self::to_bytes).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is truncated, has trailing bytes, uses unknown
+    /// tags, or if the decoded values are rejected by [`SpaceTimeId::new`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<SpaceTimeId, String> {
+        let mut cursor = Cursor::new(bytes);
+        let shape = Shape::read(&mut cursor)?;
+        let values = (0..shape.value_count())
+            .map(|_| cursor.signed())
+            .collect::<Result<Vec<_>, _>>()?;
+        if !cursor.is_empty() {
+            return Err("trailing bytes after the ID".into());
+        }
+        shape.build(&values)
+    }
+}
@@ -0,0 +1,115 @@
+//! Compact, versioned binary encoding of space-time IDs.
+//!
+//! # Format (version 1)
+//!
+//! All integers are unsigned LEB128 varints; signed values are zigzag-encoded first.
+//!
+//! A single ID ([`SpaceTimeId::to_bytes`](crate::id::SpaceTimeId::to_bytes)) is its
+//! *shape* followed by its *values*:
+//!
+//! - shape: `z` (1 byte), the variant tags of F and X (1 byte, F in the low nibble),
+//!   the variant tags of Y and T (1 byte, Y in the low nibble), then `i` as a varint.
+//!   Tags are `0` Single, `1` LimitRange, `2` BeforeUnLimitRange, `3` AfterUnLimitRange
+//!   and `4` Any.
+//! - values: for each of F, X, Y and T in this order, `Single(v)` writes `v`,
+//!   `LimitRange(s, e)` writes `s` and `e - s`, the unlimited ranges write their bound
+//!   and `Any` writes nothing. Every value is a zigzag varint.
+//!
+//! A stream of IDs ([`IdWriter`](stream::IdWriter),
+//! [`SpaceTimeIdSet::to_bytes`](crate::set::SpaceTimeIdSet::to_bytes)) starts with the
+//! magic bytes `KSID` and the version byte, followed by records and an end marker:
+//!
+//! - `0x00`, shape, values: an ID with a new shape, values written as is.
+//! - `0x01`, values: an ID with the same shape as the previous record; each value is
+//!   written as the difference to the same value of the previous record.
+//! - `0xFF`: end of the stream.
+//!
+//! Sets are written sorted by shape and values, so that consecutive records share
+//! their shape and the differences stay small.
+
+pub mod id;
+pub mod set;
+pub mod stream;
+
+/// 先頭に置くマジックバイト
+pub const MAGIC: [u8; 4] = *b"KSID";
+
+/// 現在のフォーマットのバージョン
+pub const VERSION: u8 = 1;
+
+/// レコードの種類
+pub(crate) const RECORD_NEW_SHAPE: u8 = 0x00;
+pub(crate) const RECORD_SAME_SHAPE: u8 = 0x01;
+pub(crate) const RECORD_END: u8 = 0xFF;
+
+pub(crate) fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+pub(crate) fn write_signed(out: &mut Vec<u8>, value: i64) {
+    write_varint(out, zigzag(value));
+}
+
+pub(crate) fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+pub(crate) fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+/// 1 バイトずつ読み出せる入力
+pub(crate) trait ByteSource {
+    fn byte(&mut self) -> Result<u8, String>;
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            let bits = (byte & 0x7F) as u64;
+            if shift == 63 && bits > 1 {
+                return Err("varint overflows 64 bits".into());
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("varint is too long".into())
+    }
+
+    fn signed(&mut self) -> Result<i64, String> {
+        Ok(unzigzag(self.varint()?))
+    }
+}
+
+/// バイト列を先頭から読み進めるカーソル
+pub(crate) struct Cursor<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+}
+
+impl ByteSource for Cursor<'_> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self
+            .bytes
+            .get(self.position)
+            .ok_or("unexpected end of input")?;
+        self.position += 1;
+        Ok(byte)
+    }
+}
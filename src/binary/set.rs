@@ -0,0 +1,72 @@
+use crate::{
+    binary::{
+        id::{Shape, values},
+        stream::{IdReader, IdWriter},
+    },
+    id::SpaceTimeId,
+    set::{SpaceTimeIdSet, boxes::Grid},
+};
+
+impl SpaceTimeIdSet {
+    /// Encodes the set with the compact binary format described in [`crate::binary`].
+    ///
+    /// The IDs are sorted by shape and values before writing, so the output is
+    /// deterministic for a given set and the delta-encoded records stay short.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut ids: Vec<(Shape, Vec<i64>, &SpaceTimeId)> = self
+            .iter()
+            .map(|id| (Shape::of(id), values(id), id))
+            .collect();
+        ids.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+
+        let mut writer = IdWriter::new(Vec::new()).expect("writing to a Vec cannot fail");
+        for (_, _, id) in ids {
+            writer.write(id).expect("writing to a Vec cannot fail");
+        }
+        writer.finish().expect("writing to a Vec cannot fail")
+    }
+
+    /// Decodes a set written by [`to_bytes`](Self::to_bytes) or [`IdWriter`].
+    ///
+    /// A record whose X range has `start > end` is read as a range that crosses the
+    /// antimeridian and split with [`SpaceTimeIdSet::from_wrapping`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not a valid stream, if any ID is rejected by
+    /// [`SpaceTimeId::new_wrapping`], if there are bytes after the end marker, or if two
+    /// IDs overlap (a set must stay disjoint).
+    pub fn from_bytes(bytes: &[u8]) -> Result<SpaceTimeIdSet, String> {
+        let mut rest = bytes;
+        let mut reader = IdReader::new(&mut rest)?;
+        let mut ids = Vec::new();
+        while let Some(pieces) = reader.read_wrapping()? {
+            ids.extend(pieces);
+        }
+        if !rest.is_empty() {
+            return Err("trailing bytes after the end marker".into());
+        }
+        Self::check_disjoint(&ids)?;
+        Ok(SpaceTimeIdSet::from_disjoint(ids))
+    }
+
+    /// x の開始位置で並べて、重なり得る組み合わせだけを比べる
+    fn check_disjoint(ids: &[SpaceTimeId]) -> Result<(), String> {
+        let Some(grid) = Grid::of(ids) else {
+            return Ok(());
+        };
+        let boxes: Vec<_> = ids.iter().map(|id| grid.to_box(id)).collect();
+        let mut order: Vec<usize> = (0..boxes.len()).collect();
+        order.sort_by_key(|&k| boxes[k].x.0);
+
+        let mut active: Vec<usize> = Vec::new();
+        for k in order {
+            active.retain(|&a| boxes[a].x.1 >= boxes[k].x.0);
+            if let Some(&a) = active.iter().find(|&&a| boxes[a].intersects(&boxes[k])) {
+                return Err(format!("IDs {} and {} overlap", ids[a], ids[k]));
+            }
+            active.push(k);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use std::io::{self, Read, Write};
+
+use crate::{
+    binary::{
+        ByteSource, MAGIC, RECORD_END, RECORD_NEW_SHAPE, RECORD_SAME_SHAPE, VERSION,
+        id::{Shape, values},
+        write_signed,
+    },
+    id::SpaceTimeId,
+};
+
+/// Writes IDs one by one in the binary stream format of [`crate::binary`].
+///
+/// The header is written by [`new`](Self::new). Call [`finish`](Self::finish) after
+/// the last ID to write the end marker; a stream without it is reported as truncated
+/// by [`IdReader`].
+///
+/// IDs are written in the given order. Writing them sorted, as
+/// [`SpaceTimeIdSet::to_bytes`](crate::set::SpaceTimeIdSet::to_bytes) does, makes the
+/// output smaller.
+pub struct IdWriter<W: Write> {
+    inner: W,
+    previous: Option<(Shape, Vec<i64>)>,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> IdWriter<W> {
+    /// Writes the header to `inner` and returns the writer.
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        inner.write_all(&MAGIC)?;
+        inner.write_all(&[VERSION])?;
+        Ok(Self {
+            inner,
+            previous: None,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Appends one ID to the stream.
+    pub fn write(&mut self, id: &SpaceTimeId) -> io::Result<()> {
+        let shape = Shape::of(id);
+        let current = values(id);
+
+        self.buffer.clear();
+        match &self.previous {
+            Some((previous_shape, previous)) if *previous_shape == shape => {
+                self.buffer.push(RECORD_SAME_SHAPE);
+                for (value, before) in current.iter().zip(previous) {
+                    write_signed(&mut self.buffer, value - before);
+                }
+            }
+            _ => {
+                self.buffer.push(RECORD_NEW_SHAPE);
+                shape.write(&mut self.buffer);
+                for value in &current {
+                    write_signed(&mut self.buffer, *value);
+                }
+            }
+        }
+        self.inner.write_all(&self.buffer)?;
+        self.previous = Some((shape, current));
+        Ok(())
+    }
+
+    /// Writes the end marker, flushes and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.inner.write_all(&[RECORD_END])?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+/// Reads IDs written by [`IdWriter`] or [`SpaceTimeIdSet::to_bytes`](crate::set::SpaceTimeIdSet::to_bytes).
+///
+/// Iterates over the decoded IDs. Every ID is validated through [`SpaceTimeId::new`];
+/// [`SpaceTimeIdSet::from_bytes`](crate::set::SpaceTimeIdSet::from_bytes) also accepts X
+/// ranges that cross the antimeridian.
+/// After the first error the iterator ends. Reading is done byte by byte, so wrap
+/// unbuffered sources such as files in a [`std::io::BufReader`].
+pub struct IdReader<R: Read> {
+    inner: R,
+    previous: Option<(Shape, Vec<i64>)>,
+    done: bool,
+}
+
+impl<R: Read> IdReader<R> {
+    /// Reads and checks the header of the stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the magic bytes do not match or the version is not supported.
+    pub fn new(mut inner: R) -> Result<Self, String> {
+        let mut header = [0_u8; 5];
+        inner
+            .read_exact(&mut header)
+            .map_err(|e| format!("cannot read header: {}", e))?;
+        if header[..4] != MAGIC {
+            return Err("not a space-time ID stream".into());
+        }
+        if header[4] != VERSION {
+            return Err(format!("unsupported format version {}", header[4]));
+        }
+        Ok(Self {
+            inner,
+            previous: None,
+            done: false,
+        })
+    }
+
+    fn read_record(&mut self) -> Result<Option<SpaceTimeId>, String> {
+        match self.read_values()? {
+            Some((shape, current)) => shape.build(&current).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// 次のレコードを読む。x が日付変更線をまたぐ範囲なら 2 つの ID に分ける
+    pub(crate) fn read_wrapping(&mut self) -> Result<Option<Vec<SpaceTimeId>>, String> {
+        match self.read_values()? {
+            Some((shape, current)) => shape.build_wrapping(&current).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// 次のレコードの形と値。終端なら None
+    fn read_values(&mut self) -> Result<Option<(Shape, Vec<i64>)>, String> {
+        let (shape, current) = match self.byte()? {
+            RECORD_END => return Ok(None),
+            RECORD_NEW_SHAPE => {
+                let shape = Shape::read(self)?;
+                let current = (0..shape.value_count())
+                    .map(|_| self.signed())
+                    .collect::<Result<Vec<_>, _>>()?;
+                (shape, current)
+            }
+            RECORD_SAME_SHAPE => {
+                let (shape, previous) = self
+                    .previous
+                    .take()
+                    .ok_or("delta record without a previous record")?;
+                let mut current = Vec::with_capacity(previous.len());
+                for before in previous {
+                    let delta = self.signed()?;
+                    current.push(before.checked_add(delta).ok_or("value overflows")?);
+                }
+                (shape, current)
+            }
+            other => return Err(format!("unknown record type {}", other)),
+        };
+
+        self.previous = Some((shape, current.clone()));
+        Ok(Some((shape, current)))
+    }
+}
+
+impl<R: Read> ByteSource for IdReader<R> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let mut byte = [0_u8; 1];
+        self.inner
+            .read_exact(&mut byte)
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::UnexpectedEof => "unexpected end of input".to_string(),
+                _ => e.to_string(),
+            })?;
+        Ok(byte[0])
+    }
+}
+
+impl<R: Read> Iterator for IdReader<R> {
+    type Item = Result<SpaceTimeId, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.read_record() {
+            Ok(Some(id)) => Some(Ok(id)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{
+    function::{
+        shape::{
+            Chord, InclusionMode, Solid, add, axes_at, distance, dot, quadric, scale, slab, sub,
+            voxelize,
+        },
+        tools::{ECEF, point_to_ecef::point_to_ecef},
+    },
+    id::coordinates::Point,
+    set::SpaceTimeIdSet,
+};
+
+/// apex を頂点、base を底面の中心とする底面半径 base_radius_m [m] の円錐をボクセル化する
+///
+/// 軸は apex と base を結ぶ ECEF 上の線分で、進入経路のように傾いた円錐も表せる。
+/// `mode` の意味は [`sphere`](crate::function::sphere::sphere) と同じ。
+pub fn cone(
+    z: u8,
+    apex: Point,
+    base: Point,
+    base_radius_m: f64,
+    mode: InclusionMode,
+) -> SpaceTimeIdSet {
+    let a = point_to_ecef(apex);
+    let b = point_to_ecef(base);
+    let height = distance(a, b);
+    if !base_radius_m.is_finite() || base_radius_m <= 0.0 || height == 0.0 {
+        return SpaceTimeIdSet::new();
+    }
+
+    let cone = Cone {
+        apex: a,
+        base: b,
+        axis: scale(sub(b, a), 1.0 / height),
+        height,
+        radius: base_radius_m,
+    };
+    voxelize(z, &cone, mode)
+}
+
+struct Cone {
+    apex: ECEF,
+    base: ECEF,
+    /// apex から base への単位ベクトル
+    axis: ECEF,
+    height: f64,
+    radius: f64,
+}
+
+impl Solid for Cone {
+    fn bounds(&self) -> (ECEF, f64) {
+        let half = self.height / 2.0;
+        (
+            add(self.apex, scale(self.axis, half)),
+            (half * half + self.radius * self.radius).sqrt(),
+        )
+    }
+
+    fn key_points(&self) -> Vec<ECEF> {
+        let mut result = vec![self.apex, self.base];
+        // 底面の縁のうち東西南北・上下の各方向の端
+        for axis in axes_at(self.base) {
+            let v = sub(axis, scale(self.axis, dot(axis, self.axis)));
+            let length = dot(v, v).sqrt();
+            if length == 0.0 {
+                continue;
+            }
+            for sign in [1.0, -1.0] {
+                result.push(add(self.base, scale(v, sign * self.radius / length)));
+            }
+        }
+        result
+    }
+
+    fn chord(&self, origin: ECEF, direction: ECEF) -> Option<Chord> {
+        let k2 = (self.radius / self.height).powi(2);
+        let w = sub(origin, self.apex);
+        let (s, ds) = (dot(w, self.axis), dot(direction, self.axis));
+        // 軸に垂直な成分
+        let w_across = sub(w, scale(self.axis, s));
+        let d_across = sub(direction, scale(self.axis, ds));
+
+        // 軸からの距離が k * (軸方向の距離) 以下の二重円錐。頂点より手前の側は slab で落とす
+        let pieces = quadric(
+            dot(d_across, d_across) - k2 * ds * ds,
+            dot(w_across, d_across) - k2 * s * ds,
+            dot(w_across, w_across) - k2 * s * s,
+            |t| {
+                let across = add(w_across, scale(d_across, t));
+                sub(across, scale(self.axis, k2 * (s + ds * t)))
+            },
+        );
+        let slab = slab(origin, direction, self.apex, self.axis, self.height)?;
+        pieces
+            .into_iter()
+            .flatten()
+            .filter_map(|piece| piece.intersect(slab))
+            .reduce(Chord::hull)
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{
+    function::{
+        shape::{
+            Chord, InclusionMode, Solid, add, axes_at, distance, dot, quadric, scale, slab, sub,
+            voxelize,
+        },
+        tools::{ECEF, point_to_ecef::point_to_ecef},
+    },
+    id::coordinates::Point,
+    set::SpaceTimeIdSet,
+};
+
+/// center の鉛直線を軸とする半径 radius_m [m]、高度 alt_min から alt_max [m] の円柱をボクセル化する
+///
+/// 軸は center の緯度経度における楕円体法線で、`center.altitude` は使わない。
+/// 距離は ECEF 座標上で測り、`mode` の意味は [`sphere`](crate::function::sphere::sphere) と同じ。
+pub fn cylinder(
+    z: u8,
+    center: Point,
+    radius_m: f64,
+    alt_min: f64,
+    alt_max: f64,
+    mode: InclusionMode,
+) -> SpaceTimeIdSet {
+    let (alt_min, alt_max) = if alt_min > alt_max {
+        (alt_max, alt_min)
+    } else {
+        (alt_min, alt_max)
+    };
+    if !radius_m.is_finite() || radius_m <= 0.0 || alt_min == alt_max {
+        return SpaceTimeIdSet::new();
+    }
+
+    let bottom = point_to_ecef(Point {
+        altitude: alt_min,
+        ..center
+    });
+    let top = point_to_ecef(Point {
+        altitude: alt_max,
+        ..center
+    });
+    let length = distance(bottom, top);
+
+    let cylinder = Cylinder {
+        bottom,
+        top,
+        axis: scale(sub(top, bottom), 1.0 / length),
+        length,
+        radius: radius_m,
+    };
+    voxelize(z, &cylinder, mode)
+}
+
+struct Cylinder {
+    bottom: ECEF,
+    top: ECEF,
+    /// bottom から top への単位ベクトル
+    axis: ECEF,
+    length: f64,
+    radius: f64,
+}
+
+impl Solid for Cylinder {
+    fn bounds(&self) -> (ECEF, f64) {
+        let half = self.length / 2.0;
+        (
+            add(self.bottom, scale(self.axis, half)),
+            (half * half + self.radius * self.radius).sqrt(),
+        )
+    }
+
+    fn key_points(&self) -> Vec<ECEF> {
+        let [east, north, _] = axes_at(self.bottom);
+        let mut result = Vec::new();
+        for end in [self.bottom, self.top] {
+            result.push(end);
+            for axis in [east, north] {
+                for sign in [1.0, -1.0] {
+                    result.push(add(end, scale(axis, sign * self.radius)));
+                }
+            }
+        }
+        result
+    }
+
+    fn chord(&self, origin: ECEF, direction: ECEF) -> Option<Chord> {
+        // 軸に垂直な成分
+        let across = |v: ECEF| sub(v, scale(self.axis, dot(v, self.axis)));
+        let w = across(sub(origin, self.bottom));
+        let d = across(direction);
+
+        let [side, _] = quadric(
+            dot(d, d),
+            dot(w, d),
+            dot(w, w) - self.radius * self.radius,
+            |t| add(w, scale(d, t)),
+        );
+        side?.intersect(slab(
+            origin,
+            direction,
+            self.bottom,
+            self.axis,
+            self.length,
+        )?)
+    }
+}
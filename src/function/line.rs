@@ -29,10 +29,10 @@ pub fn line(z: u8, a: Point, b: Point) -> SpaceTimeIdSet {
         // ECEF → Point
         let p = ecef_to_point(e);
 
-        // Point → Voxel
-        let voxel = point_to_id(z, p);
-
-        voxels_set.insert(voxel);
+        // Point → Voxel (Web メルカトルで表せない点は飛ばす)
+        if let Ok(voxel) = point_to_id(z, p) {
+            voxels_set.insert(voxel);
+        }
     }
 
     voxels_set
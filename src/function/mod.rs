@@ -1,3 +1,8 @@
+pub mod cone;
+pub mod cylinder;
 pub mod line;
+pub mod shape;
+pub mod sphere;
 pub mod tools;
+pub mod traversal;
 pub mod triangle;
@@ -0,0 +1,666 @@
+use std::ops::RangeInclusive;
+
+#[cfg(feature = "full")]
+use rayon::prelude::*;
+
+use crate::{
+    function::tools::{
+        ECEF, ecef_to_point::ecef_to_point, point_to_ecef::point_to_ecef,
+        point_to_id::MAX_LATITUDE, tangent_axes,
+    },
+    id::{
+        SpaceTimeId,
+        coordinates::Point,
+        z_range::{F_MAX, F_MIN},
+    },
+    set::SpaceTimeIdSet,
+};
+
+/// ボクセルを立体に含めるかどうかの判定基準
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InclusionMode {
+    /// 立体の内部に完全に収まるボクセルだけを含める
+    Inner,
+    /// 立体と少しでも交わるボクセルをすべて含める
+    Outer,
+}
+
+/// 二分法・三分探索の反復回数
+const ITERATIONS: usize = 64;
+
+/// 地球の最小曲率半径 (赤道での子午線曲率半径) [m]
+const MIN_CURVATURE_RADIUS: f64 = 6_335_439.0;
+
+/// 直線と立体の表面との交点
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Hit {
+    /// 直線上の位置 [m]
+    pub(crate) t: f64,
+    /// 交点での立体の外向き法線 (長さは任意)
+    pub(crate) normal: ECEF,
+}
+
+/// 直線が立体の内部を通る区間
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Chord {
+    pub(crate) enter: Hit,
+    pub(crate) exit: Hit,
+}
+
+impl Chord {
+    fn new(enter: Hit, exit: Hit) -> Option<Self> {
+        (enter.t <= exit.t).then_some(Self { enter, exit })
+    }
+
+    /// 2 つの区間の共通部分
+    pub(crate) fn intersect(self, other: Chord) -> Option<Chord> {
+        let enter = if self.enter.t >= other.enter.t {
+            self.enter
+        } else {
+            other.enter
+        };
+        let exit = if self.exit.t <= other.exit.t {
+            self.exit
+        } else {
+            other.exit
+        };
+        Self::new(enter, exit)
+    }
+
+    /// 2 つの区間を覆う区間
+    pub(crate) fn hull(self, other: Chord) -> Chord {
+        Self {
+            enter: if self.enter.t <= other.enter.t {
+                self.enter
+            } else {
+                other.enter
+            },
+            exit: if self.exit.t >= other.exit.t {
+                self.exit
+            } else {
+                other.exit
+            },
+        }
+    }
+}
+
+/// ボクセル化できる凸な立体
+pub(crate) trait Solid: Sync {
+    /// 立体を包む球の中心と半径 [m]
+    fn bounds(&self) -> (ECEF, f64);
+
+    /// 立体上の代表点。頂点や、東西南北・上下の各方向の端点を含める
+    fn key_points(&self) -> Vec<ECEF>;
+
+    /// 直線 origin + t * direction (direction は単位ベクトル) が立体の内部を通る区間
+    fn chord(&self, origin: ECEF, direction: ECEF) -> Option<Chord>;
+}
+
+pub(crate) fn distance(a: ECEF, b: ECEF) -> f64 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+}
+
+pub(crate) fn add(a: ECEF, b: ECEF) -> ECEF {
+    ECEF {
+        x: a.x + b.x,
+        y: a.y + b.y,
+        z: a.z + b.z,
+    }
+}
+
+pub(crate) fn sub(a: ECEF, b: ECEF) -> ECEF {
+    ECEF {
+        x: a.x - b.x,
+        y: a.y - b.y,
+        z: a.z - b.z,
+    }
+}
+
+pub(crate) fn scale(a: ECEF, k: f64) -> ECEF {
+    ECEF {
+        x: a.x * k,
+        y: a.y * k,
+        z: a.z * k,
+    }
+}
+
+pub(crate) fn dot(a: ECEF, b: ECEF) -> f64 {
+    a.x * b.x + a.y * b.y + a.z * b.z
+}
+
+/// point の緯度経度における東・北・上の単位ベクトル
+pub(crate) fn axes_at(point: ECEF) -> [ECEF; 3] {
+    let p = ecef_to_point(point);
+    tangent_axes(p.latitude, p.longitude)
+}
+
+/// a t^2 + 2 b t + c <= 0 を満たす t の区間
+///
+/// a が負のときは 2 本の半直線になる。交点の法線は `normal(t)` で求める
+pub(crate) fn quadric<N>(a: f64, b: f64, c: f64, normal: N) -> [Option<Chord>; 2]
+where
+    N: Fn(f64) -> ECEF,
+{
+    let hit = |t: f64| Hit {
+        t,
+        normal: if t.is_finite() {
+            normal(t)
+        } else {
+            ECEF {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            }
+        },
+    };
+    let line = |from: f64, to: f64| Chord::new(hit(from), hit(to));
+    let all = line(f64::NEG_INFINITY, f64::INFINITY);
+
+    if a == 0.0 {
+        return match b.partial_cmp(&0.0) {
+            Some(std::cmp::Ordering::Greater) => [line(f64::NEG_INFINITY, -c / (2.0 * b)), None],
+            Some(std::cmp::Ordering::Less) => [line(-c / (2.0 * b), f64::INFINITY), None],
+            _ if c <= 0.0 => [all, None],
+            _ => [None, None],
+        };
+    }
+
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return if a < 0.0 { [all, None] } else { [None, None] };
+    }
+
+    // 桁落ちしない形で 2 根を求める
+    let q = -(b + b.signum() * discriminant.sqrt());
+    let (r1, r2) = if q == 0.0 { (0.0, 0.0) } else { (q / a, c / q) };
+    let (r1, r2) = (r1.min(r2), r1.max(r2));
+
+    if a > 0.0 {
+        [line(r1, r2), None]
+    } else {
+        [line(f64::NEG_INFINITY, r1), line(r2, f64::INFINITY)]
+    }
+}
+
+/// 直線 origin + t * direction のうち、point から axis (単位ベクトル) 方向の距離が 0 以上 length 以下の部分
+pub(crate) fn slab(
+    origin: ECEF,
+    direction: ECEF,
+    point: ECEF,
+    axis: ECEF,
+    length: f64,
+) -> Option<Chord> {
+    let s = dot(sub(origin, point), axis);
+    let ds = dot(direction, axis);
+    let back = scale(axis, -1.0);
+
+    if ds == 0.0 {
+        let infinite = |t: f64| Hit { t, normal: axis };
+        return (0.0..=length).contains(&s).then_some(Chord {
+            enter: infinite(f64::NEG_INFINITY),
+            exit: infinite(f64::INFINITY),
+        });
+    }
+
+    let near = Hit {
+        t: -s / ds,
+        normal: back,
+    };
+    let far = Hit {
+        t: (length - s) / ds,
+        normal: axis,
+    };
+    if ds > 0.0 {
+        Chord::new(near, far)
+    } else {
+        Chord::new(far, near)
+    }
+}
+
+/// 符号付き距離関数 (SDF) が凸な立体を、(x, y) の列ごとにボクセル化する
+///
+/// 各列について、列の中の鉛直線が立体を通る高度の範囲を閉じた形で求め、その最小・最大を
+/// 列の辺と立体の代表点で取る。`Inner` は列の全域で立体の内部にある高度、`Outer` は列の
+/// どこかで立体の内部にある高度に掛かる f を、1 本の `LimitRange` として出力する。
+pub(crate) fn voxelize<S: Solid>(z: u8, solid: &S, mode: InclusionMode) -> SpaceTimeIdSet {
+    let grid = Grid::new(z, solid, mode);
+
+    #[cfg(feature = "full")]
+    let columns: Vec<_> = (grid.y_start..=grid.y_end)
+        .into_par_iter()
+        .flat_map_iter(|y| grid.row(y))
+        .collect();
+    #[cfg(not(feature = "full"))]
+    let columns: Vec<_> = (grid.y_start..=grid.y_end)
+        .flat_map(|y| grid.row(y))
+        .collect();
+
+    SpaceTimeIdSet::from_columns(z, columns)
+}
+
+/// 探索する線。緯線は経度、経線は緯度を変数とする
+#[derive(Debug, Clone, Copy)]
+enum Line {
+    Latitude(f64),
+    Longitude(f64),
+}
+
+/// 1 本の鉛直線と立体との交わり
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    chord: Chord,
+    /// 東・北・上の単位ベクトル
+    axes: [ECEF; 3],
+}
+
+impl Sample {
+    /// line に沿って進んだとき、入口 (または出口) の高度が増える割合の符号を持つ値
+    fn slope(&self, hit: Hit, line: Line) -> f64 {
+        let along = match line {
+            Line::Latitude(_) => self.axes[0],
+            Line::Longitude(_) => self.axes[1],
+        };
+        -dot(hit.normal, along) / dot(hit.normal, self.axes[2])
+    }
+}
+
+/// 辺や列の中で、鉛直線が立体に入る高度と出る高度の範囲
+#[derive(Debug, Clone, Copy)]
+struct Extent {
+    /// 入る高度の (最小, 最大)
+    lower: (f64, f64),
+    /// 出る高度の (最小, 最大)
+    upper: (f64, f64),
+    /// 辺の全体で鉛直線が立体を通るか
+    full: bool,
+}
+
+impl Extent {
+    fn point(chord: &Chord) -> Self {
+        Self {
+            lower: (chord.enter.t, chord.enter.t),
+            upper: (chord.exit.t, chord.exit.t),
+            full: true,
+        }
+    }
+
+    fn merge(self, other: Extent) -> Self {
+        Self {
+            lower: (
+                self.lower.0.min(other.lower.0),
+                self.lower.1.max(other.lower.1),
+            ),
+            upper: (
+                self.upper.0.min(other.upper.0),
+                self.upper.1.max(other.upper.1),
+            ),
+            full: self.full && other.full,
+        }
+    }
+}
+
+/// 立体の外接球に掛かる (x, y) の範囲と、その格子線が立体の影を横切る範囲
+struct Grid<'a, S> {
+    solid: &'a S,
+    z: u8,
+    n: u32,
+    mode: InclusionMode,
+    /// 経度 ±180° を跨いでも連続するように、折り返す前の x で持つ
+    x_start: i64,
+    x_end: i64,
+    y_start: u32,
+    y_end: u32,
+    /// 代表点の (緯度, 経度)。経度は x の範囲に合わせて折り返さない
+    keys: Vec<(f64, f64)>,
+    /// 緯線 y_start..=y_end + 1 上で立体の影に入る経度の範囲
+    latitude_spans: Vec<Option<(f64, f64)>>,
+    /// 経線 x_start..=x_end + 1 上で立体の影に入る緯度の範囲
+    longitude_spans: Vec<Option<(f64, f64)>>,
+}
+
+impl<'a, S: Solid> Grid<'a, S> {
+    fn new(z: u8, solid: &'a S, mode: InclusionMode) -> Self {
+        let (center, radius) = solid.bounds();
+        let p = ecef_to_point(center);
+        let n = 2_u32.pow(z as u32);
+
+        let curvature = (MIN_CURVATURE_RADIUS + p.altitude.min(0.0) - radius).max(1_000.0);
+        let d_lat = (radius / curvature).to_degrees();
+        let lat_min = (p.latitude - d_lat).max(-MAX_LATITUDE);
+        let lat_max = (p.latitude + d_lat).min(MAX_LATITUDE);
+
+        // 中心の反対側で切れば、影が一周しない限り x の範囲の中で連続する
+        let extreme = lat_min.abs().max(lat_max.abs());
+        let parallel = curvature * extreme.to_radians().cos();
+        let whole = SpaceTimeId::x_position(p.longitude - 180.0, n).floor() as i64;
+        let (x_start, x_end) = if parallel <= radius || extreme >= 89.0 {
+            (whole, whole + n as i64 - 1)
+        } else {
+            let d_lon = (radius / parallel).to_degrees();
+            let start = SpaceTimeId::x_position(p.longitude - d_lon, n).floor() as i64;
+            let end = SpaceTimeId::x_position(p.longitude + d_lon, n).floor() as i64;
+            if end - start + 1 >= n as i64 {
+                (whole, whole + n as i64 - 1)
+            } else {
+                (start, end)
+            }
+        };
+
+        let y_of = |lat: f64| {
+            let y = SpaceTimeId::y_position(lat, n);
+            (y.floor() as i64).clamp(0, n as i64 - 1) as u32
+        };
+
+        let keys = solid
+            .key_points()
+            .into_iter()
+            .map(|key| {
+                let k = ecef_to_point(key);
+                let longitude =
+                    p.longitude + (k.longitude - p.longitude + 180.0).rem_euclid(360.0) - 180.0;
+                (k.latitude, longitude)
+            })
+            .collect();
+
+        let mut grid = Self {
+            solid,
+            z,
+            n,
+            mode,
+            x_start,
+            x_end,
+            y_start: y_of(lat_max),
+            y_end: y_of(lat_min),
+            keys,
+            latitude_spans: Vec::new(),
+            longitude_spans: Vec::new(),
+        };
+
+        let (west, east) = (grid.longitude(x_start), grid.longitude(x_end + 1));
+        let (south, north) = (grid.latitude(grid.y_end + 1), grid.latitude(grid.y_start));
+        grid.latitude_spans = (grid.y_start..=grid.y_end + 1)
+            .map(|y| grid.span(Line::Latitude(grid.latitude(y)), west, east))
+            .collect();
+        grid.longitude_spans = (x_start..=x_end + 1)
+            .map(|x| grid.span(Line::Longitude(grid.longitude(x)), south, north))
+            .collect();
+        grid
+    }
+
+    fn longitude(&self, x: i64) -> f64 {
+        360.0 * (x as f64 / self.n as f64) - 180.0
+    }
+
+    fn latitude(&self, y: u32) -> f64 {
+        SpaceTimeId::latitude(y, self.n)
+    }
+
+    /// 緯度・経度の鉛直線と立体との交わり
+    fn vertical(&self, latitude: f64, longitude: f64) -> Option<Sample> {
+        let axes = tangent_axes(latitude, longitude);
+        let origin = point_to_ecef(Point {
+            latitude,
+            longitude,
+            altitude: 0.0,
+        });
+        // 鉛直線上では t がそのまま楕円体高になる
+        let chord = self.solid.chord(origin, axes[2])?;
+        Some(Sample { chord, axes })
+    }
+
+    fn sample(&self, line: Line, s: f64) -> Option<Sample> {
+        match line {
+            Line::Latitude(latitude) => self.vertical(latitude, s),
+            Line::Longitude(longitude) => self.vertical(s, longitude),
+        }
+    }
+
+    /// 影に入っているはずの線上の点。代表点を結ぶ線分と線との交点、代表点そのものの順に試す
+    fn anchors(&self, line: Line) -> Vec<f64> {
+        // (線と垂直な座標, 線に沿った座標)
+        let keys: Vec<(f64, f64)> = self
+            .keys
+            .iter()
+            .map(|&(lat, lon)| match line {
+                Line::Latitude(_) => (lat, lon),
+                Line::Longitude(_) => (lon, lat),
+            })
+            .collect();
+        let fixed = match line {
+            Line::Latitude(latitude) => latitude,
+            Line::Longitude(longitude) => longitude,
+        };
+
+        let mut result = Vec::new();
+        for (i, a) in keys.iter().enumerate() {
+            for b in &keys[i + 1..] {
+                if a.0 != b.0 && (a.0 - fixed) * (b.0 - fixed) <= 0.0 {
+                    result.push(a.1 + (fixed - a.0) / (b.0 - a.0) * (b.1 - a.1));
+                }
+            }
+        }
+        result.extend(keys.iter().map(|key| key.1));
+        result
+    }
+
+    /// 線上の [from, to] のうち鉛直線が立体を通る範囲。立体が凸なので影と線の交わりは 1 区間になる
+    fn span(&self, line: Line, from: f64, to: f64) -> Option<(f64, f64)> {
+        let inside = |s: f64| self.sample(line, s).is_some();
+        let anchor = [from, to]
+            .into_iter()
+            .chain(self.anchors(line))
+            .filter(|s| (from..=to).contains(s))
+            .find(|&s| inside(s))?;
+
+        let boundary = |mut inner: f64, mut outer: f64| {
+            if inside(outer) {
+                return outer;
+            }
+            for _ in 0..ITERATIONS {
+                let mid = (inner + outer) / 2.0;
+                if inside(mid) {
+                    inner = mid;
+                } else {
+                    outer = mid;
+                }
+            }
+            inner
+        };
+        Some((boundary(anchor, from), boundary(anchor, to)))
+    }
+
+    /// 凸な value の [p, q] での最小値を三分探索で求める。影の外は無限大とみなす
+    fn minimize<V>(&self, line: Line, mut p: f64, mut q: f64, value: V) -> f64
+    where
+        V: Fn(&Chord) -> f64,
+    {
+        let at = |s: f64| {
+            self.sample(line, s)
+                .map_or(f64::INFINITY, |sample| value(&sample.chord))
+        };
+        let mut best = f64::INFINITY;
+        for _ in 0..ITERATIONS {
+            let m1 = p + (q - p) / 3.0;
+            let m2 = q - (q - p) / 3.0;
+            let (v1, v2) = (at(m1), at(m2));
+            best = best.min(v1).min(v2);
+            if v1 < v2 {
+                q = m2;
+            } else {
+                p = m1;
+            }
+        }
+        best
+    }
+
+    /// 線上の辺 [from, to] で鉛直線が立体に入る・出る高度の範囲。corners は from と to の点
+    fn edge(
+        &self,
+        line: Line,
+        (from, to): (f64, f64),
+        span: Option<(f64, f64)>,
+        corners: [Option<Sample>; 2],
+    ) -> Option<Extent> {
+        let (p, q) = span?;
+        let (p, q) = (p.max(from), q.min(to));
+        if p > q {
+            return None;
+        }
+        let start = if p == from {
+            corners[0]
+        } else {
+            self.sample(line, p)
+        }?;
+        let end = if q == to {
+            corners[1]
+        } else {
+            self.sample(line, q)
+        }?;
+
+        // 影の縁で切れた端は傾きが分からないので、内部に極値があるものとして探す
+        let rim = (p > from, q < to);
+        let lower = (
+            start.slope(start.chord.enter, line),
+            end.slope(end.chord.enter, line),
+        );
+        let upper = (
+            start.slope(start.chord.exit, line),
+            end.slope(end.chord.exit, line),
+        );
+        let falls = |(a, b): (f64, f64)| (rim.0 || a < 0.0) && (rim.1 || b > 0.0);
+        let rises = |(a, b): (f64, f64)| (rim.0 || a > 0.0) && (rim.1 || b < 0.0);
+
+        let mut extent = Extent::point(&start.chord).merge(Extent::point(&end.chord));
+        extent.full = !rim.0 && !rim.1;
+
+        if falls(lower) {
+            let min = self.minimize(line, p, q, |c| c.enter.t);
+            extent.lower.0 = extent.lower.0.min(min);
+        }
+        if rises(upper) {
+            let max = -self.minimize(line, p, q, |c| -c.exit.t);
+            extent.upper.1 = extent.upper.1.max(max);
+        }
+        if extent.full && rises(lower) {
+            let max = -self.minimize(line, p, q, |c| -c.enter.t);
+            extent.lower.1 = extent.lower.1.max(max);
+        }
+        if extent.full && falls(upper) {
+            let min = self.minimize(line, p, q, |c| c.exit.t);
+            extent.upper.0 = extent.upper.0.min(min);
+        }
+        Some(extent)
+    }
+
+    /// 行 y の列ごとに、含める f の範囲を求める
+    fn row(&self, y: u32) -> Vec<((u32, u32), RangeInclusive<i32>)> {
+        let row = (y - self.y_start) as usize;
+        let (north, south) = (self.latitude(y), self.latitude(y + 1));
+        let north_span = self.latitude_spans[row];
+        let south_span = self.latitude_spans[row + 1];
+
+        // 行の中で影に掛かりうる経度の範囲
+        let mut reach: Option<(f64, f64)> = None;
+        let mut widen = |lon: f64| {
+            reach = Some(reach.map_or((lon, lon), |(a, b)| (a.min(lon), b.max(lon))));
+        };
+        for (a, b) in [north_span, south_span].into_iter().flatten() {
+            widen(a);
+            widen(b);
+        }
+        for &(lat, lon) in &self.keys {
+            if (south..=north).contains(&lat) {
+                widen(lon);
+            }
+        }
+        let Some((reach_west, reach_east)) = reach else {
+            return Vec::new();
+        };
+        let x_position = |lon: f64| SpaceTimeId::x_position(lon, self.n).floor() as i64;
+        let first = x_position(reach_west).max(self.x_start);
+        let last = x_position(reach_east).min(self.x_end);
+
+        let factor = 2_f64.powi(self.z as i32 - 25);
+        let (f_min, f_max) = (F_MIN[self.z as usize] as f64, F_MAX[self.z as usize] as f64);
+        let to_f = |f: f64| f.clamp(f_min, f_max) as i32;
+
+        // 列の角の鉛直線は隣り合う辺で使い回す
+        let corners: Vec<[Option<Sample>; 2]> = (first..=last + 1)
+            .map(|x| {
+                let lon = self.longitude(x);
+                [self.vertical(south, lon), self.vertical(north, lon)]
+            })
+            .collect();
+
+        let mut result = Vec::new();
+        for x in first..=last {
+            let (west, east) = (self.longitude(x), self.longitude(x + 1));
+            let k = (x - first) as usize;
+            let [south_west, north_west] = corners[k];
+            let [south_east, north_east] = corners[k + 1];
+
+            let top = self.edge(
+                Line::Latitude(north),
+                (west, east),
+                north_span,
+                [north_west, north_east],
+            );
+            let bottom = self.edge(
+                Line::Latitude(south),
+                (west, east),
+                south_span,
+                [south_west, south_east],
+            );
+            let side = |x: i64, corners: [Option<Sample>; 2]| {
+                let span = self.longitude_spans[(x - self.x_start) as usize];
+                self.edge(
+                    Line::Longitude(self.longitude(x)),
+                    (south, north),
+                    span,
+                    corners,
+                )
+            };
+            let sides = [
+                side(x, [south_west, north_west]),
+                side(x + 1, [south_east, north_east]),
+            ];
+            let inner = matches!((top, bottom), (Some(a), Some(b)) if a.full && b.full);
+
+            // 列の内部にある代表点は、立体の頂点や最も低い・高い点になりうる
+            let keys = self.keys.iter().filter_map(|&(lat, lon)| {
+                let inside = (south..=north).contains(&lat) && (west..=east).contains(&lon);
+                inside
+                    .then(|| self.vertical(lat, lon))
+                    .flatten()
+                    .map(|sample| Extent::point(&sample.chord))
+            });
+
+            let Some(extent) = [top, bottom, sides[0], sides[1]]
+                .into_iter()
+                .flatten()
+                .chain(keys)
+                .reduce(Extent::merge)
+            else {
+                continue;
+            };
+
+            let range = match self.mode {
+                InclusionMode::Outer => {
+                    to_f((factor * extent.lower.0).floor())
+                        ..=to_f((factor * extent.upper.1).floor())
+                }
+                // ボクセルの下面が最も高い入口以上、上面が最も低い出口以下になる f
+                InclusionMode::Inner if inner => {
+                    to_f((factor * extent.lower.1).ceil())
+                        ..=to_f((factor * extent.upper.0).floor() - 1.0)
+                }
+                InclusionMode::Inner => continue,
+            };
+            if !range.is_empty() {
+                let x = x.rem_euclid(self.n as i64) as u32;
+                result.push(((x, y), range));
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{
+    function::{
+        shape::{Chord, InclusionMode, Solid, add, axes_at, dot, quadric, scale, sub, voxelize},
+        tools::{ECEF, point_to_ecef::point_to_ecef},
+    },
+    id::coordinates::Point,
+    set::SpaceTimeIdSet,
+};
+
+/// center を中心とする半径 radius_m [m] の球をボクセル化する
+///
+/// 距離は `point_to_ecef` による ECEF 座標上のユークリッド距離で測る。
+/// `mode` が `Inner` なら球に完全に含まれるボクセル、`Outer` なら球と交わるボクセルを返す。
+pub fn sphere(z: u8, center: Point, radius_m: f64, mode: InclusionMode) -> SpaceTimeIdSet {
+    if !radius_m.is_finite() || radius_m <= 0.0 {
+        return SpaceTimeIdSet::new();
+    }
+
+    let sphere = Sphere {
+        center: point_to_ecef(center),
+        radius: radius_m,
+    };
+    voxelize(z, &sphere, mode)
+}
+
+struct Sphere {
+    center: ECEF,
+    radius: f64,
+}
+
+impl Solid for Sphere {
+    fn bounds(&self) -> (ECEF, f64) {
+        (self.center, self.radius)
+    }
+
+    fn key_points(&self) -> Vec<ECEF> {
+        let mut result = vec![self.center];
+        for axis in axes_at(self.center) {
+            for sign in [1.0, -1.0] {
+                result.push(add(self.center, scale(axis, sign * self.radius)));
+            }
+        }
+        result
+    }
+
+    fn chord(&self, origin: ECEF, direction: ECEF) -> Option<Chord> {
+        let d = sub(origin, self.center);
+        let [chord, _] = quadric(
+            dot(direction, direction),
+            dot(d, direction),
+            dot(d, d) - self.radius * self.radius,
+            |t| add(d, scale(direction, t)),
+        );
+        chord
+    }
+}
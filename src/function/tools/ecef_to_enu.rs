@@ -0,0 +1,18 @@
+use crate::{
+    function::tools::{ECEF, ENU, point_to_ecef::point_to_ecef, tangent_axes},
+    id::coordinates::Point,
+};
+
+/// ECEF を origin を基準とする ENU に変換
+pub fn ecef_to_enu(origin: Point, ecef: ECEF) -> ENU {
+    let o = point_to_ecef(origin);
+    let d = [ecef.x - o.x, ecef.y - o.y, ecef.z - o.z];
+    let dot = |a: ECEF| a.x * d[0] + a.y * d[1] + a.z * d[2];
+
+    let [east, north, up] = tangent_axes(origin.latitude, origin.longitude);
+    ENU {
+        east: dot(east),
+        north: dot(north),
+        up: dot(up),
+    }
+}
@@ -3,6 +3,10 @@ use crate::{
     id::SpaceTimeId,
 };
 
-pub fn ecef_to_id(z: u8, ecef: ECEF) -> SpaceTimeId {
+/// ECEF 座標の点を含む SpaceTimeId を返す
+///
+/// # Errors
+/// [`point_to_id`] と同じ
+pub fn ecef_to_id(z: u8, ecef: ECEF) -> Result<SpaceTimeId, String> {
     point_to_id(z, ecef_to_point(ecef))
 }
@@ -0,0 +1,16 @@
+use crate::{
+    function::tools::{ECEF, ENU, point_to_ecef::point_to_ecef, tangent_axes},
+    id::coordinates::Point,
+};
+
+/// origin を基準とする ENU を ECEF に変換
+pub fn enu_to_ecef(origin: Point, enu: ENU) -> ECEF {
+    let o = point_to_ecef(origin);
+    let [east, north, up] = tangent_axes(origin.latitude, origin.longitude);
+
+    ECEF {
+        x: o.x + enu.east * east.x + enu.north * north.x + enu.up * up.x,
+        y: o.y + enu.east * east.y + enu.north * north.y + enu.up * up.y,
+        z: o.z + enu.east * east.z + enu.north * north.z + enu.up * up.z,
+    }
+}
@@ -0,0 +1,12 @@
+use crate::{
+    function::tools::{ENU, enu_to_point::enu_to_point, point_to_id::point_to_id},
+    id::{SpaceTimeId, coordinates::Point},
+};
+
+/// origin を基準とする ENU の点を含む SpaceTimeId を返す
+///
+/// # Errors
+/// [`point_to_id`] と同じ
+pub fn enu_to_id(z: u8, origin: Point, enu: ENU) -> Result<SpaceTimeId, String> {
+    point_to_id(z, enu_to_point(origin, enu))
+}
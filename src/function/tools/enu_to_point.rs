@@ -0,0 +1,9 @@
+use crate::{
+    function::tools::{ENU, ecef_to_point::ecef_to_point, enu_to_ecef::enu_to_ecef},
+    id::coordinates::Point,
+};
+
+/// origin を基準とする ENU を Point (lat, lon, alt) に変換
+pub fn enu_to_point(origin: Point, enu: ENU) -> Point {
+    ecef_to_point(enu_to_ecef(origin, enu))
+}
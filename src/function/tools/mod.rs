@@ -1,7 +1,14 @@
+pub mod ecef_to_enu;
 pub mod ecef_to_id;
 pub mod ecef_to_point;
+pub mod enu_to_ecef;
+pub mod enu_to_id;
+pub mod enu_to_point;
 pub mod point_to_ecef;
+pub mod point_to_enu;
 pub mod point_to_id;
+pub mod points_to_ids;
+pub mod projection;
 
 #[derive(Debug, Clone, Copy)]
 pub struct ECEF {
@@ -9,3 +16,34 @@ pub struct ECEF {
     pub y: f64,
     pub z: f64,
 }
+
+/// 基準点の接平面上の局所座標 (東・北・上, メートル)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ENU {
+    pub east: f64,
+    pub north: f64,
+    pub up: f64,
+}
+
+/// 緯度・経度 (度) における東・北・上の単位ベクトル (ECEF)
+pub(crate) fn tangent_axes(latitude: f64, longitude: f64) -> [ECEF; 3] {
+    let (sin_lat, cos_lat) = latitude.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = longitude.to_radians().sin_cos();
+    [
+        ECEF {
+            x: -sin_lon,
+            y: cos_lon,
+            z: 0.0,
+        },
+        ECEF {
+            x: -sin_lat * cos_lon,
+            y: -sin_lat * sin_lon,
+            z: cos_lat,
+        },
+        ECEF {
+            x: cos_lat * cos_lon,
+            y: cos_lat * sin_lon,
+            z: sin_lat,
+        },
+    ]
+}
@@ -0,0 +1,9 @@
+use crate::{
+    function::tools::{ENU, ecef_to_enu::ecef_to_enu, point_to_ecef::point_to_ecef},
+    id::coordinates::Point,
+};
+
+/// Point (lat, lon, alt) を origin を基準とする ENU に変換
+pub fn point_to_enu(origin: Point, point: Point) -> ENU {
+    ecef_to_enu(origin, point_to_ecef(point))
+}
@@ -1,31 +1,82 @@
+use crate::geoid::AltitudeReference;
 use crate::id::{DimensionRange, SpaceTimeId, coordinates::Point};
 
+/// Web メルカトルで表せる緯度の上限 (度)
+pub const MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+
 /// Point (lat, lon, alt) を SpaceTimeId に変換
-pub fn point_to_id(z: u8, point: Point) -> SpaceTimeId {
-    let lat = point.latitude;
-    let lon = point.longitude;
-    let alt = point.altitude;
+///
+/// 高度は楕円体高として読む。標高なら [`point_to_id_with_reference`] を使う
+///
+/// 経度は 360° ごとに折り返すので、180° と -180° は同じ x になる
+///
+/// # Errors
+/// - z が 31 を超える
+/// - 緯度・経度・高度が有限でない
+/// - 緯度の絶対値が [`MAX_LATITUDE`] を超える (極域)
+/// - 高度が z における f の範囲外
+pub fn point_to_id(z: u8, point: Point) -> Result<SpaceTimeId, String> {
+    if z >= 32 {
+        return Err(format!("Zoom level z must be 0..=31. Got {}", z));
+    }
+    let Point {
+        latitude: lat,
+        longitude: lon,
+        altitude: alt,
+    } = point;
+    if !(lat.is_finite() && lon.is_finite() && alt.is_finite()) {
+        return Err(format!("Point ({}, {}, {}) is not finite", lat, lon, alt));
+    }
+    if lat.abs() > MAX_LATITUDE {
+        return Err(format!(
+            "Latitude {} is outside the Web Mercator range ±{}",
+            lat, MAX_LATITUDE
+        ));
+    }
 
     // ---- 高度 h -> f (Python の h_to_f を Rust に移植) ----
     let factor = 2_f64.powi(z as i32 - 25); // 2^(z-25)
-    let f_id = (factor * alt).floor() as i32;
+    let f_id = (factor * alt).floor();
+    if !(i32::MIN as f64..=i32::MAX as f64).contains(&f_id) {
+        return Err(format!(
+            "Altitude {} is out of range at zoom level {}",
+            alt, z
+        ));
+    }
 
     // ---- 経度 lon -> x ----
-    let n = 2u32.pow(z as u32) as f64;
-    let x_id = ((lon + 180.0) / 360.0 * n).floor() as u32;
+    // 経度は [-180, 180) に折り返す。180° は日付変更線の西端 x = 0 になる
+    let n = 2_u64.pow(z as u32);
+    let max = (n - 1) as f64;
+    let x_id = SpaceTimeId::x_position(lon, n as u32)
+        .rem_euclid(n as f64)
+        .floor()
+        .min(max);
 
     // ---- 緯度 lat -> y (Web Mercator) ----
-    let lat_rad = lat.to_radians();
-    let y_id = ((1.0 - (lat_rad.tan() + 1.0 / lat_rad.cos()).ln() / std::f64::consts::PI) / 2.0 * n)
-        .floor() as u32;
+    // 上限の緯度ちょうどでは丸め誤差で範囲をわずかに外れるので詰める
+    let y_id = SpaceTimeId::y_position(lat, n as u32)
+        .floor()
+        .clamp(0.0, max);
 
     SpaceTimeId::new(
         z,
-        DimensionRange::Single(f_id),
-        DimensionRange::Single(x_id),
-        DimensionRange::Single(y_id),
+        DimensionRange::Single(f_id as i32),
+        DimensionRange::Single(x_id as u32),
+        DimensionRange::Single(y_id as u32),
         0,                   // i = 0（時間なし）
         DimensionRange::Any, // t = Any
     )
-    .unwrap()
+}
+
+/// 高度の基準を指定して Point を SpaceTimeId に変換する
+///
+/// 標高 (Orthometric) はジオイド高を足して楕円体高にしてから f を求める
+pub fn point_to_id_with_reference(
+    z: u8,
+    point: Point,
+    reference: AltitudeReference,
+) -> Result<SpaceTimeId, String> {
+    let altitude = reference.ellipsoidal_height(point.latitude, point.longitude, point.altitude)?;
+    point_to_id(z, Point { altitude, ..point })
 }
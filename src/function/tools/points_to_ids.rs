@@ -0,0 +1,43 @@
+#[cfg(feature = "full")]
+use rayon::prelude::*;
+use std::collections::HashSet;
+
+use crate::{
+    function::tools::point_to_id::point_to_id,
+    id::{SpaceTimeId, coordinates::Point},
+    set::SpaceTimeIdSet,
+};
+
+/// 複数の Point をまとめて SpaceTimeId に変換する (full feature では並列)
+///
+/// 結果は入力と同じ順に並び、範囲外の点はその位置に `Err` が入る
+pub fn points_to_ids(z: u8, points: &[Point]) -> Vec<Result<SpaceTimeId, String>> {
+    #[cfg(feature = "full")]
+    let points = points.par_iter();
+    #[cfg(not(feature = "full"))]
+    let points = points.iter();
+
+    points.map(|&point| point_to_id(z, point)).collect()
+}
+
+/// 複数の Point を重複を除いた SpaceTimeIdSet に変換する (full feature では並列)
+///
+/// 範囲外の点があっても止めずに飛ばし、入力での番号とエラーの組を入力の順に返す
+pub fn points_to_set(z: u8, points: &[Point]) -> (SpaceTimeIdSet, Vec<(usize, String)>) {
+    let mut ids = HashSet::new();
+    let mut errors = Vec::new();
+    for (k, result) in points_to_ids(z, points).into_iter().enumerate() {
+        match result {
+            Ok(id) => {
+                ids.insert(id);
+            }
+            Err(e) => errors.push((k, e)),
+        }
+    }
+
+    // 同じ z の単一ボクセルなので、重複を除けば互いに素
+    (
+        SpaceTimeIdSet::from_disjoint(ids.into_iter().collect()),
+        errors,
+    )
+}
@@ -0,0 +1,278 @@
+use crate::id::coordinates::Point;
+
+/// 投影座標 (メートル)
+///
+/// 平面直角座標系の X (北向き) は `northing`、Y (東向き) は `easting` にあたる
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Projected {
+    pub easting: f64,
+    pub northing: f64,
+    pub altitude: f64,
+}
+
+/// 北半球・南半球 (UTM の false northing を決める)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hemisphere {
+    North,
+    South,
+}
+
+/// GRS80 (JGD2011)
+const GRS80: (f64, f64) = (6_378_137.0, 1.0 / 298.257_222_101);
+/// WGS84
+const WGS84: (f64, f64) = (6_378_137.0, 1.0 / 298.257_223_563);
+
+/// 平面直角座標系 I〜XIX 系の原点 (緯度, 経度の度と分)
+const JGD2011_ORIGINS: [(f64, f64, f64); 19] = [
+    (33.0, 129.0, 30.0),
+    (33.0, 131.0, 0.0),
+    (36.0, 132.0, 10.0),
+    (33.0, 133.0, 30.0),
+    (36.0, 134.0, 20.0),
+    (36.0, 136.0, 0.0),
+    (36.0, 137.0, 10.0),
+    (36.0, 138.0, 30.0),
+    (36.0, 139.0, 50.0),
+    (40.0, 140.0, 50.0),
+    (44.0, 140.0, 15.0),
+    (44.0, 142.0, 15.0),
+    (44.0, 144.0, 15.0),
+    (26.0, 142.0, 0.0),
+    (26.0, 127.0, 30.0),
+    (26.0, 124.0, 0.0),
+    (26.0, 131.0, 0.0),
+    (20.0, 136.0, 0.0),
+    (26.0, 154.0, 0.0),
+];
+
+/// Gauss–Krüger transverse Mercator projection.
+///
+/// The projection uses the Krüger series to sixth order in the third flattening `n`
+/// (Karney, 2011), which is accurate to well below a millimeter within a few thousand
+/// kilometers of the central meridian. Altitudes are passed through unchanged.
+///
+/// # Japanese Note
+///
+/// 横メルカトル図法 (ガウス・クリューゲル)。平面直角座標系と UTM に使う
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransverseMercator {
+    origin_latitude: f64,
+    origin_longitude: f64,
+    scale: f64,
+    false_easting: f64,
+    false_northing: f64,
+    /// 第一離心率
+    e: f64,
+    /// k0 * 子午線弧長の係数 A
+    k0_a: f64,
+    alpha: [f64; 6],
+    beta: [f64; 6],
+    /// 原点緯度の ξ に対応する北距
+    origin_northing: f64,
+}
+
+impl TransverseMercator {
+    /// Creates a projection on the ellipsoid with semi-major axis `a` and flattening `f`.
+    ///
+    /// `origin_latitude` and `origin_longitude` are in degrees. `scale` is the scale
+    /// factor on the central meridian.
+    pub fn new(
+        a: f64,
+        f: f64,
+        origin_latitude: f64,
+        origin_longitude: f64,
+        scale: f64,
+        false_easting: f64,
+        false_northing: f64,
+    ) -> Self {
+        let n = f / (2.0 - f);
+        let n2 = n * n;
+        let n3 = n2 * n;
+        let n4 = n3 * n;
+        let n5 = n4 * n;
+        let n6 = n5 * n;
+
+        let alpha = [
+            n / 2.0 - 2.0 * n2 / 3.0 + 5.0 * n3 / 16.0 + 41.0 * n4 / 180.0 - 127.0 * n5 / 288.0
+                + 7891.0 * n6 / 37800.0,
+            13.0 * n2 / 48.0 - 3.0 * n3 / 5.0 + 557.0 * n4 / 1440.0 + 281.0 * n5 / 630.0
+                - 1983433.0 * n6 / 1935360.0,
+            61.0 * n3 / 240.0 - 103.0 * n4 / 140.0
+                + 15061.0 * n5 / 26880.0
+                + 167603.0 * n6 / 181440.0,
+            49561.0 * n4 / 161280.0 - 179.0 * n5 / 168.0 + 6601661.0 * n6 / 7257600.0,
+            34729.0 * n5 / 80640.0 - 3418889.0 * n6 / 1995840.0,
+            212378941.0 * n6 / 319334400.0,
+        ];
+        let beta = [
+            n / 2.0 - 2.0 * n2 / 3.0 + 37.0 * n3 / 96.0 - n4 / 360.0 - 81.0 * n5 / 512.0
+                + 96199.0 * n6 / 604800.0,
+            n2 / 48.0 + n3 / 15.0 - 437.0 * n4 / 1440.0 + 46.0 * n5 / 105.0
+                - 1118711.0 * n6 / 3870720.0,
+            17.0 * n3 / 480.0 - 37.0 * n4 / 840.0 - 209.0 * n5 / 4480.0 + 5569.0 * n6 / 90720.0,
+            4397.0 * n4 / 161280.0 - 11.0 * n5 / 504.0 - 830251.0 * n6 / 7257600.0,
+            4583.0 * n5 / 161280.0 - 108847.0 * n6 / 3991680.0,
+            20648693.0 * n6 / 638668800.0,
+        ];
+
+        let k0_a = scale * a / (1.0 + n) * (1.0 + n2 / 4.0 + n4 / 64.0 + n6 / 256.0);
+        let mut projection = Self {
+            origin_latitude,
+            origin_longitude,
+            scale,
+            false_easting,
+            false_northing,
+            e: (f * (2.0 - f)).sqrt(),
+            k0_a,
+            alpha,
+            beta,
+            origin_northing: 0.0,
+        };
+        projection.origin_northing = projection.xi_eta(origin_latitude, 0.0).0 * k0_a;
+        projection
+    }
+
+    /// Returns the Japan Plane Rectangular Coordinate System zone `zone` (1 to 19, for
+    /// zones I to XIX) on JGD2011.
+    ///
+    /// The zones use the GRS80 ellipsoid, a scale factor of 0.9999 and no false easting
+    /// or northing. The X axis of the Japanese convention (pointing north) is
+    /// [`Projected::northing`] and the Y axis (pointing east) is [`Projected::easting`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `zone` is not 1 to 19.
+    pub fn jgd2011(zone: u8) -> Result<Self, String> {
+        let Some(&(latitude, degrees, minutes)) =
+            JGD2011_ORIGINS.get((zone as usize).wrapping_sub(1))
+        else {
+            return Err(format!(
+                "Plane rectangular zone must be 1..=19. Got {}",
+                zone
+            ));
+        };
+        Ok(Self::new(
+            GRS80.0,
+            GRS80.1,
+            latitude,
+            degrees + minutes / 60.0,
+            0.9999,
+            0.0,
+            0.0,
+        ))
+    }
+
+    /// Returns the UTM zone `zone` (1 to 60) on WGS84.
+    ///
+    /// The central meridian is `6 * zone - 183` degrees, the scale factor 0.9996 and the
+    /// false easting 500 000 m. The southern hemisphere uses a false northing of
+    /// 10 000 000 m.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `zone` is not 1 to 60.
+    pub fn utm(zone: u8, hemisphere: Hemisphere) -> Result<Self, String> {
+        if !(1..=60).contains(&zone) {
+            return Err(format!("UTM zone must be 1..=60. Got {}", zone));
+        }
+        let false_northing = match hemisphere {
+            Hemisphere::North => 0.0,
+            Hemisphere::South => 10_000_000.0,
+        };
+        Ok(Self::new(
+            WGS84.0,
+            WGS84.1,
+            0.0,
+            6.0 * zone as f64 - 183.0,
+            0.9996,
+            500_000.0,
+            false_northing,
+        ))
+    }
+
+    /// Returns the standard UTM zone (1 to 60) that contains `longitude` in degrees.
+    ///
+    /// The exceptions around Norway and Svalbard are not applied.
+    pub fn utm_zone(longitude: f64) -> u8 {
+        let lon = (longitude + 180.0).rem_euclid(360.0);
+        ((lon / 6.0).floor() as u8).min(59) + 1
+    }
+
+    /// Returns the scale factor on the central meridian.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Returns the latitude and longitude of the origin in degrees.
+    pub fn origin(&self) -> (f64, f64) {
+        (self.origin_latitude, self.origin_longitude)
+    }
+
+    /// Projects a point to easting and northing in meters.
+    pub fn project(&self, point: Point) -> Projected {
+        let lambda = (point.longitude - self.origin_longitude).to_radians();
+        let (xi, eta) = self.xi_eta(point.latitude, lambda);
+        Projected {
+            easting: self.false_easting + self.k0_a * eta,
+            northing: self.false_northing + self.k0_a * xi - self.origin_northing,
+            altitude: point.altitude,
+        }
+    }
+
+    /// Converts easting and northing in meters back to a point.
+    pub fn unproject(&self, projected: Projected) -> Point {
+        let xi = (projected.northing - self.false_northing + self.origin_northing) / self.k0_a;
+        let eta = (projected.easting - self.false_easting) / self.k0_a;
+
+        let (mut xi1, mut eta1) = (xi, eta);
+        for (j, beta) in self.beta.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi1 -= beta * (k * xi).sin() * (k * eta).cosh();
+            eta1 -= beta * (k * xi).cos() * (k * eta).sinh();
+        }
+
+        let tau1 = xi1.sin() / (eta1.sinh().powi(2) + xi1.cos().powi(2)).sqrt();
+        let lambda = eta1.sinh().atan2(xi1.cos());
+
+        // τ' から τ を Newton 法で求める
+        let e2 = self.e * self.e;
+        let mut tau = tau1;
+        for _ in 0..8 {
+            let taup = self.conformal_tau(tau);
+            let delta = (tau1 - taup) / (1.0 + taup * taup).sqrt() * (1.0 + (1.0 - e2) * tau * tau)
+                / ((1.0 - e2) * (1.0 + tau * tau).sqrt());
+            tau += delta;
+            if delta.abs() < 1e-14 {
+                break;
+            }
+        }
+
+        let longitude = self.origin_longitude + lambda.to_degrees();
+        Point {
+            latitude: tau.atan().to_degrees(),
+            longitude: (longitude + 180.0).rem_euclid(360.0) - 180.0,
+            altitude: projected.altitude,
+        }
+    }
+
+    /// 等角緯度の正接 τ'
+    fn conformal_tau(&self, tau: f64) -> f64 {
+        let sigma = (self.e * (self.e * tau / (1.0 + tau * tau).sqrt()).atanh()).sinh();
+        tau * (1.0 + sigma * sigma).sqrt() - sigma * (1.0 + tau * tau).sqrt()
+    }
+
+    /// 緯度 (度) と中央子午線からの経度差 (ラジアン) から (ξ, η) を求める
+    fn xi_eta(&self, latitude: f64, lambda: f64) -> (f64, f64) {
+        let taup = self.conformal_tau(latitude.to_radians().tan());
+        let xi1 = taup.atan2(lambda.cos());
+        let eta1 = (lambda.sin() / (taup * taup + lambda.cos().powi(2)).sqrt()).asinh();
+
+        let (mut xi, mut eta) = (xi1, eta1);
+        for (j, alpha) in self.alpha.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi += alpha * (k * xi1).sin() * (k * eta1).cosh();
+            eta += alpha * (k * xi1).cos() * (k * eta1).sinh();
+        }
+        (xi, eta)
+    }
+}
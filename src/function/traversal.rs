@@ -0,0 +1,252 @@
+use std::collections::VecDeque;
+
+use crate::{
+    function::{
+        shape::{distance, scale, sub},
+        tools::{
+            ECEF, ecef_to_point::ecef_to_point, point_to_ecef::point_to_ecef,
+            point_to_id::MAX_LATITUDE,
+        },
+    },
+    id::{
+        DimensionRange::{Any, Single},
+        SpaceTimeId,
+        coordinates::Point,
+        z_range::{F_MAX, F_MIN},
+    },
+};
+
+/// ボクセル境界の位置を二分探索で求めるときの許容誤差 [m]
+const TOLERANCE: f64 = 1e-6;
+
+/// 線分が通過した 1 つのボクセルと、その中にいた区間
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TraversalStep {
+    /// 通過したボクセル
+    pub id: SpaceTimeId,
+    /// ボクセルに入った位置の始点からの距離 [m]
+    pub enter: f64,
+    /// ボクセルから出た位置の始点からの距離 [m]
+    pub exit: f64,
+}
+
+/// 線分が通過するボクセルを始点に近い順に返すイテレータ
+///
+/// [`traverse`] または [`traverse_ray`] で作成する。
+#[derive(Debug, Clone)]
+pub struct Traversal {
+    z: u8,
+    origin: ECEF,
+    direction: ECEF,
+    length: f64,
+    position: f64,
+    current: Cell,
+    enter: f64,
+    pending: VecDeque<TraversalStep>,
+    finished: bool,
+}
+
+/// index 空間上のボクセル (定義域外も表せるように i64 で持つ)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cell {
+    f: i64,
+    x: i64,
+    y: i64,
+}
+
+/// a から b までの線分が通過するボクセルを順に返す
+///
+/// 線分は ECEF 座標上の直線で、ボクセルの境界を越える位置を二分探索で求めるため、
+/// サンプリングによる取りこぼしなく通過したボクセルを列挙できる。
+/// Web メルカトルの範囲外や F の範囲外を通る部分は返さない。
+pub fn traverse(z: u8, a: Point, b: Point) -> Traversal {
+    let origin = point_to_ecef(a);
+    let target = point_to_ecef(b);
+    let length = distance(origin, target);
+    Traversal::new(z, origin, target, length)
+}
+
+/// origin から target の方向へ max_distance [m] まで伸ばした半直線が通過するボクセルを順に返す
+///
+/// origin と target が一致する場合、または max_distance が正の有限値でない場合は何も返さない。
+pub fn traverse_ray(z: u8, origin: Point, target: Point, max_distance: f64) -> Traversal {
+    let origin = point_to_ecef(origin);
+    let target = point_to_ecef(target);
+    let length = if max_distance.is_finite() && max_distance > 0.0 {
+        max_distance
+    } else {
+        0.0
+    };
+    Traversal::new(z, origin, target, length)
+}
+
+impl Traversal {
+    fn new(z: u8, origin: ECEF, target: ECEF, length: f64) -> Self {
+        let norm = distance(origin, target);
+        let finished = !(norm.is_finite() && norm > 0.0 && length.is_finite() && length > 0.0);
+        let direction = if finished {
+            ECEF {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            }
+        } else {
+            scale(sub(target, origin), 1.0 / norm)
+        };
+
+        let mut traversal = Self {
+            z,
+            origin,
+            direction,
+            length,
+            position: 0.0,
+            current: Cell { f: 0, x: 0, y: 0 },
+            enter: 0.0,
+            pending: VecDeque::new(),
+            finished,
+        };
+        traversal.current = traversal.cell_at(0.0);
+        traversal
+    }
+
+    /// 始点から s [m] の位置にあるボクセル
+    fn cell_at(&self, s: f64) -> Cell {
+        let p = ecef_to_point(ECEF {
+            x: self.origin.x + self.direction.x * s,
+            y: self.origin.y + self.direction.y * s,
+            z: self.origin.z + self.direction.z * s,
+        });
+        let n = 1_i64 << self.z;
+
+        let f = (2_f64.powi(self.z as i32 - 25) * p.altitude).floor() as i64;
+        let x = (SpaceTimeId::x_position(p.longitude, n as u32).floor() as i64).rem_euclid(n);
+        // 範囲外の緯度は -1 と n で表す
+        let y = if p.latitude > MAX_LATITUDE {
+            -1
+        } else if p.latitude < -MAX_LATITUDE {
+            n
+        } else {
+            (SpaceTimeId::y_position(p.latitude, n as u32).floor() as i64).clamp(0, n - 1)
+        };
+
+        Cell { f, x, y }
+    }
+
+    /// ボクセルを取りこぼさない程度に小さい前進量 [m]
+    fn step_length(&self, cell: Cell) -> f64 {
+        let n = 1_i64 << self.z;
+        let height = 2_f64.powi(25 - self.z as i32);
+
+        let y = cell.y.clamp(0, n - 1) as u32;
+        let latitude = SpaceTimeId::latitude(y, n as u32)
+            .abs()
+            .max(SpaceTimeId::latitude(y + 1, n as u32).abs());
+        let width =
+            2.0 * std::f64::consts::PI * 6_378_137.0 * latitude.to_radians().cos() / n as f64;
+
+        (height.min(width) / 4.0).max(TOLERANCE * 16.0)
+    }
+
+    fn is_face_neighbor(&self, a: Cell, b: Cell) -> bool {
+        let n = 1_i64 << self.z;
+        let dx = (a.x - b.x).rem_euclid(n);
+        let dx = dx.min(n - dx);
+        let diffs = [(a.f - b.f).abs(), dx, (a.y - b.y).abs()];
+        diffs.iter().filter(|&&d| d != 0).count() == 1 && diffs.iter().sum::<i64>() == 1
+    }
+
+    /// (lo, hi] の区間でボクセルが切り替わる位置を二分探索で列挙する
+    fn transitions(&self, lo: (f64, Cell), hi: (f64, Cell), out: &mut Vec<(f64, Cell)>) {
+        if lo.1 == hi.1 {
+            return;
+        }
+        let mid = (lo.0 + hi.0) / 2.0;
+        if hi.0 - lo.0 < TOLERANCE {
+            out.push((mid, hi.1));
+            return;
+        }
+        if self.is_face_neighbor(lo.1, hi.1) {
+            // 隣り合うボクセルなら境界は 1 つなので、片側だけを狭めていく
+            let m = self.cell_at(mid);
+            if m == lo.1 {
+                self.transitions((mid, m), hi, out);
+            } else if m == hi.1 {
+                self.transitions(lo, (mid, m), out);
+            } else {
+                self.transitions(lo, (mid, m), out);
+                self.transitions((mid, m), hi, out);
+            }
+            return;
+        }
+        let m = (mid, self.cell_at(mid));
+        self.transitions(lo, m, out);
+        self.transitions(m, hi, out);
+    }
+
+    fn to_step(&self, cell: Cell, enter: f64, exit: f64) -> Option<TraversalStep> {
+        let z = self.z as usize;
+        let n = 1_i64 << self.z;
+        if cell.y < 0 || cell.y >= n || cell.f < F_MIN[z] as i64 || cell.f > F_MAX[z] as i64 {
+            return None;
+        }
+        let id = SpaceTimeId::new(
+            self.z,
+            Single(cell.f as i32),
+            Single(cell.x as u32),
+            Single(cell.y as u32),
+            0,
+            Any,
+        )
+        .ok()?;
+        Some(TraversalStep { id, enter, exit })
+    }
+}
+
+impl Iterator for Traversal {
+    type Item = TraversalStep;
+
+    fn next(&mut self) -> Option<TraversalStep> {
+        loop {
+            if let Some(step) = self.pending.pop_front() {
+                return Some(step);
+            }
+            if self.finished {
+                return None;
+            }
+
+            // ボクセルが変わるか終点に着くまで前進する
+            let mut lo = self.position;
+            let hi = loop {
+                let s = (lo + self.step_length(self.current)).min(self.length);
+                let cell = self.cell_at(s);
+                if cell != self.current {
+                    break Some((s, cell));
+                }
+                if s >= self.length {
+                    break None;
+                }
+                lo = s;
+            };
+
+            let Some(hi) = hi else {
+                self.finished = true;
+                if let Some(step) = self.to_step(self.current, self.enter, self.length) {
+                    return Some(step);
+                }
+                continue;
+            };
+
+            let mut boundaries = Vec::new();
+            self.transitions((lo, self.current), hi, &mut boundaries);
+
+            for (boundary, cell) in boundaries {
+                if let Some(step) = self.to_step(self.current, self.enter, boundary) {
+                    self.pending.push_back(step);
+                }
+                self.current = cell;
+                self.enter = boundary;
+            }
+            self.position = hi.0;
+        }
+    }
+}
@@ -26,8 +26,9 @@ pub fn triangle(z: u8, a: Point, b: Point, c: Point) -> SpaceTimeIdSet {
             if i == 0 {
                 // 始点 a のみ
                 let p = ecef_to_point(ea);
-                let voxel = point_to_id(z, p);
-                local_set.insert(voxel);
+                if let Ok(voxel) = point_to_id(z, p) {
+                    local_set.insert(voxel);
+                }
             } else {
                 let t = i as f64 / steps as f64;
 
@@ -53,11 +54,11 @@ pub fn triangle(z: u8, a: Point, b: Point, c: Point) -> SpaceTimeIdSet {
                         z: line1.z * (1.0 - s) + line2.z * s,
                     };
 
-                    // ECEF → Point → Voxel
+                    // ECEF → Point → Voxel (Web メルカトルで表せない点は飛ばす)
                     let p = ecef_to_point(e);
-                    let voxel = point_to_id(z, p);
-
-                    local_set.insert(voxel);
+                    if let Ok(voxel) = point_to_id(z, p) {
+                        local_set.insert(voxel);
+                    }
                 }
             }
             local_set
@@ -94,8 +95,9 @@ pub fn triangle(z: u8, a: Point, b: Point, c: Point) -> SpaceTimeIdSet {
     for i in 0..=steps {
         if i == 0 {
             let p = ecef_to_point(ea);
-            let voxel = point_to_id(z, p);
-            voxels_set.insert(voxel);
+            if let Ok(voxel) = point_to_id(z, p) {
+                voxels_set.insert(voxel);
+            }
         } else {
             let t = i as f64 / steps as f64;
 
@@ -121,11 +123,11 @@ pub fn triangle(z: u8, a: Point, b: Point, c: Point) -> SpaceTimeIdSet {
                     z: line1.z * (1.0 - s) + line2.z * s,
                 };
 
-                // ECEF → Point → Voxel
+                // ECEF → Point → Voxel (Web メルカトルで表せない点は飛ばす)
                 let p = ecef_to_point(e);
-                let voxel = point_to_id(z, p);
-
-                voxels_set.insert(voxel);
+                if let Ok(voxel) = point_to_id(z, p) {
+                    voxels_set.insert(voxel);
+                }
             }
         }
     }
@@ -0,0 +1,193 @@
+use std::io::Read;
+
+use crate::geoid::GeoidModel;
+
+/// Values at or above this are treated as missing (GSIGEO uses `999.0000`).
+const MISSING: f64 = 999.0;
+
+/// A geoid model interpolated bilinearly from a regular latitude/longitude grid.
+///
+/// # File format
+///
+/// [`GridGeoid::read`] accepts the ASCII format of the GSIGEO geoid files published by
+/// the Geospatial Information Authority of Japan, which other grids such as EGM96 can
+/// easily be converted to. All tokens are separated by whitespace:
+///
+/// - A header with the latitude and longitude of the south-west grid point, the grid
+///   spacing in latitude and longitude (all in degrees), and the number of rows and
+///   columns. Any further tokens on the header line (GSIGEO writes a kind flag and a
+///   version) are ignored.
+/// - `rows * columns` undulations in meters, row by row from the southernmost row, each
+///   row from west to east. Line breaks inside a row are not significant.
+///
+/// Values of `999` or more mark grid points without data.
+///
+/// # Japanese Note
+///
+/// GSIGEO の ASCII 形式のジオイド格子。南西端から行ごとに東向きに値が並ぶ
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridGeoid {
+    latitude: f64,
+    longitude: f64,
+    latitude_step: f64,
+    longitude_step: f64,
+    rows: usize,
+    columns: usize,
+    values: Vec<f64>,
+}
+
+impl GridGeoid {
+    /// Creates a grid from its south-west corner, spacing and row-major values.
+    ///
+    /// `values[row * columns + column]` is the undulation at latitude
+    /// `latitude + row * latitude_step` and longitude
+    /// `longitude + column * longitude_step`. Missing points are written as `999`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the steps are not positive, if there are fewer than two rows
+    /// or columns, or if the number of values is not `rows * columns`.
+    pub fn new(
+        latitude: f64,
+        longitude: f64,
+        latitude_step: f64,
+        longitude_step: f64,
+        rows: usize,
+        columns: usize,
+        values: Vec<f64>,
+    ) -> Result<Self, String> {
+        if !(latitude_step > 0.0 && longitude_step > 0.0) {
+            return Err(format!(
+                "Grid steps must be positive. Got {} and {}",
+                latitude_step, longitude_step
+            ));
+        }
+        if rows < 2 || columns < 2 {
+            return Err(format!(
+                "Grid must have at least 2 rows and 2 columns. Got {} x {}",
+                rows, columns
+            ));
+        }
+        if values.len() != rows * columns {
+            return Err(format!(
+                "Grid of {} x {} needs {} values. Got {}",
+                rows,
+                columns,
+                rows * columns,
+                values.len()
+            ));
+        }
+        Ok(Self {
+            latitude,
+            longitude,
+            latitude_step,
+            longitude_step,
+            rows,
+            columns,
+            values,
+        })
+    }
+
+    /// Reads a grid in the ASCII format described above.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on I/O errors, if a token is not a number, if the header is
+    /// incomplete, or if the number of values does not match the header.
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, String> {
+        let mut text = String::new();
+        reader
+            .read_to_string(&mut text)
+            .map_err(|e| e.to_string())?;
+
+        let mut lines = text.lines();
+        let header: Vec<&str> = lines
+            .by_ref()
+            .find(|line| !line.trim().is_empty())
+            .ok_or("Geoid grid is empty")?
+            .split_whitespace()
+            .collect();
+        if header.len() < 6 {
+            return Err(format!(
+                "Geoid grid header needs 6 values. Got '{}'",
+                header.join(" ")
+            ));
+        }
+        let number = |token: &str| {
+            token
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid number '{}' in geoid grid", token))
+        };
+        let count = |token: &str| {
+            token
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid grid size '{}' in geoid grid", token))
+        };
+
+        let values = lines
+            .flat_map(str::split_whitespace)
+            .map(number)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(
+            number(header[0])?,
+            number(header[1])?,
+            number(header[2])?,
+            number(header[3])?,
+            count(header[4])?,
+            count(header[5])?,
+            values,
+        )
+    }
+
+    fn value(&self, row: usize, column: usize) -> Option<f64> {
+        let value = self.values[row * self.columns + column];
+        (value < MISSING).then_some(value)
+    }
+}
+
+impl GeoidModel for GridGeoid {
+    /// Interpolates the undulation bilinearly from the four surrounding grid points.
+    ///
+    /// Returns an error outside the grid or if a surrounding point with a non-zero weight
+    /// has no data.
+    fn undulation(&self, latitude: f64, longitude: f64) -> Result<f64, String> {
+        let row = (latitude - self.latitude) / self.latitude_step;
+        let column = (longitude - self.longitude) / self.longitude_step;
+        let last_row = (self.rows - 1) as f64;
+        let last_column = (self.columns - 1) as f64;
+        if !(0.0..=last_row).contains(&row) || !(0.0..=last_column).contains(&column) {
+            return Err(format!(
+                "({}, {}) is outside the geoid grid",
+                latitude, longitude
+            ));
+        }
+
+        // 北端・東端の点は一つ内側のセルで補間する
+        let r0 = (row.floor() as usize).min(self.rows - 2);
+        let c0 = (column.floor() as usize).min(self.columns - 2);
+        let (dr, dc) = (row - r0 as f64, column - c0 as f64);
+
+        // 重みが 0 の点は欠測でもかまわない
+        let corners = [
+            (r0, c0, (1.0 - dr) * (1.0 - dc)),
+            (r0, c0 + 1, (1.0 - dr) * dc),
+            (r0 + 1, c0, dr * (1.0 - dc)),
+            (r0 + 1, c0 + 1, dr * dc),
+        ];
+        let mut undulation = 0.0;
+        for (r, c, weight) in corners {
+            if weight == 0.0 {
+                continue;
+            }
+            let Some(value) = self.value(r, c) else {
+                return Err(format!(
+                    "Geoid grid has no data around ({}, {})",
+                    latitude, longitude
+                ));
+            };
+            undulation += value * weight;
+        }
+        Ok(undulation)
+    }
+}
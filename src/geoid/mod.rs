@@ -0,0 +1,93 @@
+//! Altitude reference systems and geoid models.
+//!
+//! The F dimension of a space-time ID is defined on ellipsoidal heights (height above
+//! the WGS84 ellipsoid). Survey and aviation data usually give orthometric heights
+//! (height above the geoid, roughly mean sea level) instead. The two are related by the
+//! geoid undulation `N`, the height of the geoid above the ellipsoid:
+//!
+//! ```text
+//! ellipsoidal height = orthometric height + N
+//! ```
+//!
+//! [`AltitudeReference`] tells a conversion which kind of height it is given or should
+//! return, and a [`GeoidModel`] supplies `N`. [`ConstantGeoid`] uses a single offset,
+//! which is enough for small areas, and [`grid::GridGeoid`] interpolates a geoid grid
+//! file such as GSIGEO or EGM96.
+
+pub mod grid;
+
+/// A model of the geoid undulation.
+pub trait GeoidModel {
+    /// Returns the geoid undulation `N` in meters at the given latitude and longitude
+    /// in degrees.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the model has no value at that position.
+    fn undulation(&self, latitude: f64, longitude: f64) -> Result<f64, String>;
+}
+
+/// A geoid with the same undulation everywhere.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConstantGeoid {
+    /// Geoid undulation `N` in meters.
+    pub undulation: f64,
+}
+
+impl ConstantGeoid {
+    /// Creates a geoid with the undulation `undulation` in meters.
+    pub fn new(undulation: f64) -> Self {
+        Self { undulation }
+    }
+}
+
+impl GeoidModel for ConstantGeoid {
+    fn undulation(&self, _latitude: f64, _longitude: f64) -> Result<f64, String> {
+        Ok(self.undulation)
+    }
+}
+
+/// The kind of height an altitude is measured in.
+#[derive(Clone, Copy, Default)]
+pub enum AltitudeReference<'a> {
+    /// Height above the WGS84 ellipsoid. This is what space-time IDs use internally.
+    #[default]
+    Ellipsoidal,
+    /// Height above the geoid given by the model.
+    Orthometric(&'a dyn GeoidModel),
+}
+
+impl AltitudeReference<'_> {
+    /// Converts an altitude in this reference to an ellipsoidal height.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the geoid model has no value at the position.
+    pub fn ellipsoidal_height(
+        self,
+        latitude: f64,
+        longitude: f64,
+        altitude: f64,
+    ) -> Result<f64, String> {
+        match self {
+            AltitudeReference::Ellipsoidal => Ok(altitude),
+            AltitudeReference::Orthometric(model) => {
+                Ok(altitude + model.undulation(latitude, longitude)?)
+            }
+        }
+    }
+
+    /// Converts an ellipsoidal height to an altitude in this reference.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the geoid model has no value at the position.
+    pub fn altitude(self, latitude: f64, longitude: f64, height: f64) -> Result<f64, String> {
+        match self {
+            AltitudeReference::Ellipsoidal => Ok(height),
+            AltitudeReference::Orthometric(model) => {
+                Ok(height - model.undulation(latitude, longitude)?)
+            }
+        }
+    }
+}
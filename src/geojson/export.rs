@@ -0,0 +1,434 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use serde_json::{Map, Value, json};
+
+use crate::{
+    geojson::iso_time,
+    id::{SpaceTimeId, time::TimeAxis},
+    set::{
+        SpaceTimeIdSet,
+        boxes::{Grid, IndexBox, T_UNBOUNDED},
+    },
+};
+
+/// Options for [`SpaceTimeIdSet::to_geojson`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExportOptions {
+    /// If `true`, footprints that share the same altitude band and time window are
+    /// merged into one `MultiPolygon` feature instead of one feature per ID.
+    pub merge: bool,
+}
+
+impl SpaceTimeId {
+    /// Converts the ID into a GeoJSON `Feature`.
+    ///
+    /// The geometry is the `Polygon` footprint of the ID taken from
+    /// [`coordinates`](Self::coordinates), with a counterclockwise exterior ring as
+    /// required by RFC 7946. The properties hold:
+    ///
+    /// - `id`: the ID in its string notation
+    /// - `z`, `i`: numbers
+    /// - `f`, `x`, `y`, `t`: the dimension ranges in string notation (`"3"`, `"3:5"`, `"-:5"`, `"-"`)
+    /// - `altitude_min`, `altitude_max`: the vertical extent in meters
+    /// - `time_start`, `time_end`: the time window as ISO 8601 strings (temporal IDs only).
+    ///   `time_end` is exclusive and is `null` if the ID has no end.
+    ///
+    /// Time indices are counted in units of `i` seconds from the UNIX epoch.
+    pub fn to_geojson(&self) -> Value {
+        let c = self.coordinates();
+        let west = c.longitude.0.min(c.longitude.1);
+        let east = c.longitude.0.max(c.longitude.1);
+        let south = c.latitude.0.min(c.latitude.1);
+        let north = c.latitude.0.max(c.latitude.1);
+
+        let mut properties = Map::new();
+        properties.insert("id".into(), json!(self.to_string()));
+        properties.insert("z".into(), json!(self.z()));
+        properties.insert("f".into(), json!(self.f().to_string()));
+        properties.insert("x".into(), json!(self.x().to_string()));
+        properties.insert("y".into(), json!(self.y().to_string()));
+        properties.insert("i".into(), json!(self.i()));
+        properties.insert("t".into(), json!(self.t().to_string()));
+        properties.insert("altitude_min".into(), json!(c.altitude.0.min(c.altitude.1)));
+        properties.insert("altitude_max".into(), json!(c.altitude.0.max(c.altitude.1)));
+
+        if self.i() != 0 {
+            let (start, end) = self.t_bounds();
+            let end = (end != u32::MAX).then_some(end as u64 + 1);
+            insert_time_window(&mut properties, self.i(), start as u64, end);
+        }
+
+        json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [rectangle(west, south, east, north)],
+            },
+            "properties": properties,
+        })
+    }
+}
+
+impl SpaceTimeIdSet {
+    /// Converts the set into a GeoJSON `FeatureCollection`.
+    ///
+    /// Without merging, the collection holds one feature per ID as produced by
+    /// [`SpaceTimeId::to_geojson`].
+    ///
+    /// With [`ExportOptions::merge`], all IDs are rescaled to the finest zoom level of
+    /// the set and cut into altitude bands (and time windows). The footprints of each
+    /// band are merged into a `MultiPolygon` whose rings follow the outline of the
+    /// union, including holes. Consecutive bands with the same footprint are combined.
+    /// Each merged feature has the properties `z`, `f`, `i`, `t`, `altitude_min`,
+    /// `altitude_max` and, for temporal sets, `time_start` and `time_end`.
+    /// Footprints are not merged across the antimeridian.
+    ///
+    /// # Japanese Note
+    ///
+    /// 集合を GeoJSON の FeatureCollection に変換する。
+    /// `merge` を指定すると高度帯ごとに隣接するフットプリントをまとめる
+    pub fn to_geojson(&self, options: ExportOptions) -> Value {
+        let features: Vec<Value> = if options.merge {
+            merged_features(self)
+        } else {
+            self.iter().map(|id| id.to_geojson()).collect()
+        };
+
+        json!({
+            "type": "FeatureCollection",
+            "features": features,
+        })
+    }
+}
+
+/// 反時計回りの長方形のリング
+fn rectangle(west: f64, south: f64, east: f64, north: f64) -> Value {
+    json!([
+        [west, south],
+        [east, south],
+        [east, north],
+        [west, north],
+        [west, south]
+    ])
+}
+
+fn insert_time_window(properties: &mut Map<String, Value>, i: u32, start: u64, end: Option<u64>) {
+    let axis = TimeAxis::default();
+    let time = |t: u64| axis.time_of(i, t).ok().map(iso_time);
+    properties.insert("time_start".into(), json!(time(start)));
+    properties.insert("time_end".into(), json!(end.and_then(time)));
+}
+
+/// 高度帯と時間帯ごとにフットプリントをまとめた Feature を作る
+fn merged_features(set: &SpaceTimeIdSet) -> Vec<Value> {
+    let Some((grid, boxes)) = set.to_boxes() else {
+        return Vec::new();
+    };
+
+    let mut features = Vec::new();
+    for t in elementary_ranges(boxes.iter().map(|b| b.t)) {
+        let in_time: Vec<&IndexBox> = boxes
+            .iter()
+            .filter(|b| b.t.0 <= t.0 && t.1 <= b.t.1)
+            .collect();
+
+        // 同じフットプリントが続く高度帯はひとまとめにする
+        let mut bands: Vec<((i64, i64), Vec<Rect>)> = Vec::new();
+        for f in elementary_ranges(in_time.iter().map(|b| b.f)) {
+            let mut rects: Vec<Rect> = in_time
+                .iter()
+                .filter(|b| b.f.0 <= f.0 && f.1 <= b.f.1)
+                .map(|b| (b.x, b.y))
+                .collect();
+            if rects.is_empty() {
+                continue;
+            }
+            rects.sort_unstable();
+
+            match bands.last_mut() {
+                Some((range, last)) if range.1 + 1 == f.0 && *last == rects => range.1 = f.1,
+                _ => bands.push((f, rects)),
+            }
+        }
+
+        for (f, rects) in bands {
+            features.push(merged_feature(grid, f, t, &rects));
+        }
+    }
+    features
+}
+
+/// 区間の列を、どの区間の端もまたがない最小の区間に切り分ける
+fn elementary_ranges<I>(ranges: I) -> Vec<(i64, i64)>
+where
+    I: IntoIterator<Item = (i64, i64)>,
+{
+    let mut breaks = BTreeSet::new();
+    let mut list = Vec::new();
+    for (s, e) in ranges {
+        breaks.insert(s);
+        breaks.insert(e.saturating_add(1));
+        list.push((s, e));
+    }
+
+    let breaks: Vec<i64> = breaks.into_iter().collect();
+    breaks
+        .windows(2)
+        .map(|w| (w[0], w[1] - 1))
+        .filter(|&(s, e)| list.iter().any(|r| r.0 <= s && e <= r.1))
+        .collect()
+}
+
+/// x と y の index の閉区間の組
+type Rect = ((i64, i64), (i64, i64));
+
+fn merged_feature(grid: Grid, f: (i64, i64), t: (i64, i64), rects: &[Rect]) -> Value {
+    let n = 1_u64 << grid.z;
+    let lon = |x: i64| SpaceTimeId::longitude(x as u32, n as u32);
+    let lat = |y: i64| SpaceTimeId::latitude(y as u32, n as u32);
+    let height = 2_f64.powi(25 - grid.z as i32);
+
+    let polygons: Vec<Value> = outline(rects)
+        .into_iter()
+        .map(|rings| {
+            Value::Array(
+                rings
+                    .into_iter()
+                    .map(|ring| {
+                        Value::Array(
+                            ring.into_iter()
+                                .map(|(x, y)| json!([lon(x), lat(y)]))
+                                .collect(),
+                        )
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+
+    let range = |(s, e): (i64, i64)| {
+        if s == e {
+            s.to_string()
+        } else {
+            format!("{}:{}", s, e)
+        }
+    };
+
+    let mut properties = Map::new();
+    properties.insert("z".into(), json!(grid.z));
+    properties.insert("f".into(), json!(range(f)));
+    properties.insert("i".into(), json!(grid.i));
+    properties.insert("altitude_min".into(), json!(f.0 as f64 * height));
+    properties.insert("altitude_max".into(), json!((f.1 + 1) as f64 * height));
+
+    if grid.i == 0 {
+        properties.insert("t".into(), json!("-"));
+    } else {
+        let end = (t.1 < T_UNBOUNDED).then_some(t.1 as u64 + 1);
+        let t_string = match end {
+            Some(_) => range(t),
+            None => format!("{}:-", t.0),
+        };
+        properties.insert("t".into(), json!(t_string));
+        insert_time_window(&mut properties, grid.i, t.0 as u64, end);
+    }
+
+    json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "MultiPolygon",
+            "coordinates": polygons,
+        },
+        "properties": properties,
+    })
+}
+
+/// 頂点 (x, y) の index。y は南向きに増える
+type Vertex = (i64, i64);
+
+/// 互いに重ならない長方形の和集合の輪郭を求める
+///
+/// 長方形の端で座標を圧縮した格子上で境界の辺を内部が左手になる向きに並べ、
+/// 辺をつないでリングにする。反時計回りのリングが外周、時計回りのリングが穴になる。
+/// 戻り値は Polygon ごとの [外周, 穴...] の列。
+fn outline(rects: &[Rect]) -> Vec<Vec<Vec<Vertex>>> {
+    let xs: Vec<i64> = rects
+        .iter()
+        .flat_map(|r| [r.0.0, r.0.1 + 1])
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let ys: Vec<i64> = rects
+        .iter()
+        .flat_map(|r| [r.1.0, r.1.1 + 1])
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let x_index: HashMap<i64, usize> = xs.iter().enumerate().map(|(k, &x)| (x, k)).collect();
+    let y_index: HashMap<i64, usize> = ys.iter().enumerate().map(|(k, &y)| (y, k)).collect();
+
+    let mut covered: HashSet<(usize, usize)> = HashSet::new();
+    for r in rects {
+        for i in x_index[&r.0.0]..x_index[&(r.0.1 + 1)] {
+            for j in y_index[&r.1.0]..y_index[&(r.1.1 + 1)] {
+                covered.insert((i, j));
+            }
+        }
+    }
+    let is_covered =
+        |i: i64, j: i64| i >= 0 && j >= 0 && covered.contains(&(i as usize, j as usize));
+
+    // 辺 (始点, 終点, 辺を生んだセル)。頂点は圧縮後の index
+    let mut edges: Vec<(Vertex, Vertex, (i64, i64))> = Vec::new();
+    let mut cells: Vec<&(usize, usize)> = covered.iter().collect();
+    cells.sort_unstable();
+    for &&(i, j) in &cells {
+        let (i, j) = (i as i64, j as i64);
+        if !is_covered(i, j + 1) {
+            edges.push(((i, j + 1), (i + 1, j + 1), (i, j)));
+        }
+        if !is_covered(i + 1, j) {
+            edges.push(((i + 1, j + 1), (i + 1, j), (i, j)));
+        }
+        if !is_covered(i, j - 1) {
+            edges.push(((i + 1, j), (i, j), (i, j)));
+        }
+        if !is_covered(i - 1, j) {
+            edges.push(((i, j), (i, j + 1), (i, j)));
+        }
+    }
+
+    let mut outgoing: HashMap<Vertex, Vec<usize>> = HashMap::new();
+    for (k, edge) in edges.iter().enumerate() {
+        outgoing.entry(edge.0).or_default().push(k);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut outers: Vec<(Vec<Vertex>, f64)> = Vec::new();
+    let mut holes: Vec<(Vec<Vertex>, (i64, i64))> = Vec::new();
+
+    for start in 0..edges.len() {
+        if used[start] {
+            continue;
+        }
+        let mut ring = vec![edges[start].0];
+        let mut current = start;
+        loop {
+            used[current] = true;
+            let (from, to, _) = edges[current];
+            ring.push(to);
+            if to == edges[start].0 {
+                break;
+            }
+            // 角で接する場合は左折を優先して、リングが自分自身に接しないようにする
+            let direction = (to.0 - from.0, to.1 - from.1);
+            let next = outgoing[&to]
+                .iter()
+                .copied()
+                .filter(|&k| !used[k])
+                .min_by_key(|&k| {
+                    let (a, b, _) = edges[k];
+                    turn(direction, (b.0 - a.0, b.1 - a.1))
+                });
+            match next {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+
+        let ring = simplify_ring(ring);
+        let area = signed_area(&ring);
+        if area > 0.0 {
+            outers.push((ring, area));
+        } else {
+            holes.push((ring, edges[start].2));
+        }
+    }
+
+    let to_index = |ring: &[Vertex]| -> Vec<Vertex> {
+        ring.iter()
+            .map(|&(i, j)| (xs[i as usize], ys[j as usize]))
+            .collect()
+    };
+
+    let mut polygons: Vec<Vec<Vec<Vertex>>> = outers
+        .iter()
+        .map(|(ring, _)| vec![to_index(ring)])
+        .collect();
+
+    // 穴は、穴に接するセルを含む外周のうち最も小さいものに属する
+    for (hole, (i, j)) in holes {
+        let point = (i as f64 + 0.5, j as f64 + 0.5);
+        let owner = outers
+            .iter()
+            .enumerate()
+            .filter(|(_, (ring, _))| contains(ring, point))
+            .min_by(|a, b| a.1.1.total_cmp(&b.1.1))
+            .map(|(k, _)| k);
+        if let Some(k) = owner {
+            polygons[k].push(to_index(&hole));
+        }
+    }
+
+    polygons
+}
+
+/// 進行方向の変化を 左折 < 直進 < 右折 < U ターン の順に並べる
+fn turn(from: (i64, i64), to: (i64, i64)) -> u8 {
+    // y は南向きなので、北を上にした地図上での左折は (dx, dy) -> (dy, -dx)
+    if to == (from.1, -from.0) {
+        0
+    } else if to == from {
+        1
+    } else if to == (-from.1, from.0) {
+        2
+    } else {
+        3
+    }
+}
+
+/// 一直線上に並ぶ頂点を取り除く (始点と終点は同じ点のまま残す)
+fn simplify_ring(ring: Vec<Vertex>) -> Vec<Vertex> {
+    let mut points: Vec<Vertex> = ring[..ring.len() - 1].to_vec();
+    let len = points.len();
+    let keep: Vec<bool> = (0..len)
+        .map(|k| {
+            let prev = points[(k + len - 1) % len];
+            let here = points[k];
+            let next = points[(k + 1) % len];
+            (here.0 - prev.0) * (next.1 - here.1) != (here.1 - prev.1) * (next.0 - here.0)
+        })
+        .collect();
+    let mut k = 0;
+    points.retain(|_| {
+        k += 1;
+        keep[k - 1]
+    });
+    points.push(points[0]);
+    points
+}
+
+/// 北を上にした地図上での符号付き面積 (反時計回りが正)
+fn signed_area(ring: &[Vertex]) -> f64 {
+    let twice: i64 = ring
+        .windows(2)
+        .map(|w| w[0].0 * (-w[1].1) - w[1].0 * (-w[0].1))
+        .sum();
+    twice as f64 / 2.0
+}
+
+/// 点がリングの内部にあるかどうか (境界上の点は渡さない前提)
+fn contains(ring: &[Vertex], point: (f64, f64)) -> bool {
+    let mut inside = false;
+    for w in ring.windows(2) {
+        let (x0, y0) = (w[0].0 as f64, w[0].1 as f64);
+        let (x1, y1) = (w[1].0 as f64, w[1].1 as f64);
+        if (y0 > point.1) != (y1 > point.1) {
+            let x = x0 + (point.1 - y0) / (y1 - y0) * (x1 - x0);
+            if point.0 < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
@@ -0,0 +1,493 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+
+use crate::{
+    function::{tools::point_to_id::MAX_LATITUDE, traversal::traverse},
+    id::{
+        SpaceTimeId,
+        coordinates::Point,
+        time::{TimeAxis, TimeRounding},
+        z_range::{F_MAX, F_MIN, XY_MAX},
+    },
+    set::{
+        SpaceTimeIdSet,
+        boxes::{Grid, IndexBox, T_UNBOUNDED, disjoint_union},
+    },
+};
+
+/// Options for [`SpaceTimeIdSet::from_geojson`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportOptions {
+    /// Zoom level of the generated IDs.
+    pub z: u8,
+    /// Time interval `i` in seconds used for features with a time window.
+    /// Must be non-zero if any feature has a time window.
+    pub interval: u32,
+    /// Property holding the lower altitude in meters.
+    pub altitude_min: String,
+    /// Property holding the upper altitude in meters.
+    pub altitude_max: String,
+    /// Property holding the start of the time window.
+    pub time_start: String,
+    /// Property holding the (exclusive) end of the time window.
+    pub time_end: String,
+}
+
+impl ImportOptions {
+    /// Creates options for zoom level `z` that read the same property names as
+    /// [`SpaceTimeIdSet::to_geojson`] writes (`altitude_min`, `altitude_max`,
+    /// `time_start`, `time_end`), with no time interval.
+    pub fn new(z: u8) -> Self {
+        Self {
+            z,
+            interval: 0,
+            altitude_min: "altitude_min".into(),
+            altitude_max: "altitude_max".into(),
+            time_start: "time_start".into(),
+            time_end: "time_end".into(),
+        }
+    }
+}
+
+/// A feature that could not be converted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureError {
+    /// Position of the feature in the `features` array.
+    pub index: usize,
+    /// The `id` member of the feature, if any.
+    pub id: Option<Value>,
+    /// Why the feature was rejected.
+    pub message: String,
+}
+
+/// Result of [`SpaceTimeIdSet::from_geojson`].
+#[derive(Debug, Clone)]
+pub struct ImportReport {
+    /// Union of all converted features.
+    pub set: SpaceTimeIdSet,
+    /// Number of features that were converted.
+    pub converted: usize,
+    /// Features that were skipped, in input order.
+    pub errors: Vec<FeatureError>,
+}
+
+/// Feature の id, geometry, properties
+type RawFeature<'a> = (Option<Value>, Option<&'a Value>, Map<String, Value>);
+
+/// 1 行の中で連続する x の範囲と、その行の y
+type Run = ((i64, i64), i64);
+
+impl SpaceTimeIdSet {
+    /// Builds a set from a GeoJSON `FeatureCollection`, `Feature` or bare geometry.
+    ///
+    /// Geometries are converted at zoom level `options.z`:
+    ///
+    /// - `Point`: the voxel containing the point.
+    /// - `LineString`: every voxel crossed by the straight ECEF segments between the
+    ///   vertices, using the same exact traversal as [`raycast`](Self::raycast).
+    /// - `Polygon`: every column whose center lies inside the polygon (holes are
+    ///   respected), extruded from the `altitude_min` to the `altitude_max` property.
+    ///   Polygons must not cross the antimeridian (split them as RFC 7946 recommends).
+    /// - `MultiPoint`, `MultiLineString` and `MultiPolygon` are converted part by part.
+    ///
+    /// Points and line vertices use their third coordinate as altitude, or the
+    /// `altitude_min` property if there is none, or `0.0`. If the `time_start` or
+    /// `time_end` property is present, the feature becomes temporal with interval
+    /// `options.interval`; times are ISO 8601 strings or UNIX seconds and are rounded
+    /// outwards to whole intervals. A missing `time_end` means no end.
+    ///
+    /// All processing is local; no network access is needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `value` is not a GeoJSON object, or if the zoom level is
+    /// invalid. Features that cannot be converted (unsupported or malformed geometry,
+    /// coordinates outside the Web Mercator range, missing altitudes, bad time values)
+    /// are skipped and listed in [`ImportReport::errors`].
+    ///
+    /// # Japanese Note
+    ///
+    /// GeoJSON を読み込んで集合を作る。変換できない Feature はエラーとして一覧にする
+    pub fn from_geojson(value: &Value, options: &ImportOptions) -> Result<ImportReport, String> {
+        if options.z >= 32 {
+            return Err(format!("Zoom level z must be 0..=31. Got {}", options.z));
+        }
+
+        let features: Vec<RawFeature> = match value.get("type").and_then(Value::as_str) {
+            Some("FeatureCollection") => value
+                .get("features")
+                .and_then(Value::as_array)
+                .ok_or("FeatureCollection has no features array")?
+                .iter()
+                .map(Self::split_feature)
+                .collect(),
+            Some("Feature") => vec![Self::split_feature(value)],
+            Some(_) => vec![(None, Some(value), Map::new())],
+            None => return Err("GeoJSON object has no type".into()),
+        };
+
+        let grid = Grid {
+            z: options.z,
+            i: options.interval,
+        };
+        let mut boxes: HashSet<IndexBox> = HashSet::new();
+        let mut errors = Vec::new();
+        let mut converted = 0;
+
+        for (index, (id, geometry, properties)) in features.into_iter().enumerate() {
+            let result = match geometry {
+                Some(geometry) => Self::convert_feature(grid, geometry, &properties, options),
+                None => Err("feature has no geometry".to_string()),
+            };
+            match result {
+                Ok(feature_boxes) => {
+                    converted += 1;
+                    boxes.extend(feature_boxes);
+                }
+                Err(message) => errors.push(FeatureError { index, id, message }),
+            }
+        }
+
+        let set = Self::from_boxes(grid, disjoint_union(boxes))?;
+        Ok(ImportReport {
+            set,
+            converted,
+            errors,
+        })
+    }
+
+    /// JSON 文字列から [`from_geojson`](Self::from_geojson) を呼ぶ
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `text` is not valid JSON, or for the same reasons as
+    /// [`from_geojson`](Self::from_geojson).
+    pub fn from_geojson_str(text: &str, options: &ImportOptions) -> Result<ImportReport, String> {
+        let value: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        Self::from_geojson(&value, options)
+    }
+
+    fn split_feature(feature: &Value) -> RawFeature<'_> {
+        let id = feature.get("id").cloned();
+        let geometry = feature.get("geometry").filter(|g| !g.is_null());
+        let properties = feature
+            .get("properties")
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default();
+        (id, geometry, properties)
+    }
+
+    fn convert_feature(
+        grid: Grid,
+        geometry: &Value,
+        properties: &Map<String, Value>,
+        options: &ImportOptions,
+    ) -> Result<Vec<IndexBox>, String> {
+        let t = Self::time_range(grid, properties, options)?;
+        let altitude_min = Self::number_property(properties, &options.altitude_min)?;
+        let altitude_max = Self::number_property(properties, &options.altitude_max)?;
+        let default_altitude = altitude_min.unwrap_or(0.0);
+
+        let kind = geometry
+            .get("type")
+            .and_then(Value::as_str)
+            .ok_or("geometry has no type")?;
+        let coordinates = geometry
+            .get("coordinates")
+            .ok_or_else(|| format!("{} has no coordinates", kind))?;
+
+        let cells = match kind {
+            "Point" => vec![Self::point_cell(
+                grid.z,
+                Self::position(coordinates, default_altitude)?,
+            )?],
+            "MultiPoint" => Self::array(coordinates)?
+                .iter()
+                .map(|c| Self::point_cell(grid.z, Self::position(c, default_altitude)?))
+                .collect::<Result<Vec<_>, _>>()?,
+            "LineString" => Self::line_cells(grid.z, coordinates, default_altitude)?,
+            "MultiLineString" => {
+                let mut cells = Vec::new();
+                for line in Self::array(coordinates)? {
+                    cells.extend(Self::line_cells(grid.z, line, default_altitude)?);
+                }
+                cells
+            }
+            "Polygon" | "MultiPolygon" => {
+                let (Some(low), Some(high)) = (altitude_min, altitude_max) else {
+                    return Err(format!(
+                        "{} needs the properties {} and {}",
+                        kind, options.altitude_min, options.altitude_max
+                    ));
+                };
+                let f = Self::f_range(grid.z, low, high)?;
+                let polygons = if kind == "Polygon" {
+                    vec![coordinates]
+                } else {
+                    Self::array(coordinates)?.iter().collect()
+                };
+
+                let mut cells = Vec::new();
+                for polygon in polygons {
+                    cells.extend(Self::polygon_columns(grid.z, polygon)?.into_iter().map(
+                        |(x, y)| IndexBox {
+                            f,
+                            x,
+                            y: (y, y),
+                            t: (0, 0),
+                        },
+                    ));
+                }
+                if cells.is_empty() {
+                    return Err(format!(
+                        "{} covers no voxel center at zoom level {}",
+                        kind, grid.z
+                    ));
+                }
+                cells
+            }
+            other => return Err(format!("unsupported geometry type {}", other)),
+        };
+
+        Ok(cells.into_iter().map(|b| IndexBox { t, ..b }).collect())
+    }
+
+    fn array(value: &Value) -> Result<&Vec<Value>, String> {
+        value
+            .as_array()
+            .ok_or_else(|| format!("expected an array, got {}", value))
+    }
+
+    /// [経度, 緯度, 高度] の配列を Point に変換する
+    fn position(value: &Value, default_altitude: f64) -> Result<Point, String> {
+        let values = Self::array(value)?;
+        let number = |k: usize| values.get(k).and_then(Value::as_f64);
+        let (Some(longitude), Some(latitude)) = (number(0), number(1)) else {
+            return Err(format!("invalid position {}", value));
+        };
+        Ok(Point {
+            latitude,
+            longitude,
+            altitude: number(2).unwrap_or(default_altitude),
+        })
+    }
+
+    fn point_cell(z: u8, point: Point) -> Result<IndexBox, String> {
+        if !point.latitude.is_finite() || point.latitude.abs() > MAX_LATITUDE {
+            return Err(format!(
+                "latitude {} is outside the Web Mercator range",
+                point.latitude
+            ));
+        }
+        if !point.longitude.is_finite() || point.longitude.abs() > 180.0 {
+            return Err(format!("longitude {} is out of range", point.longitude));
+        }
+
+        let n = 2_f64.powi(z as i32);
+        let xy_max = XY_MAX[z as usize] as i64;
+        // 経度 180° は -180° と同じ列になる
+        let x = (SpaceTimeId::x_position(point.longitude, n as u32).floor() as i64)
+            .rem_euclid(xy_max + 1);
+        let y = SpaceTimeId::y_position(point.latitude, n as u32).floor() as i64;
+        let f = Self::f_of(z, point.altitude)?;
+
+        Ok(IndexBox {
+            f: (f, f),
+            x: (x, x),
+            y: (y.clamp(0, xy_max), y.clamp(0, xy_max)),
+            t: (0, 0),
+        })
+    }
+
+    fn f_of(z: u8, altitude: f64) -> Result<i64, String> {
+        let f = (2_f64.powi(z as i32 - 25) * altitude).floor();
+        if !f.is_finite() || f < F_MIN[z as usize] as f64 || f > F_MAX[z as usize] as f64 {
+            return Err(format!("altitude {} is out of range", altitude));
+        }
+        Ok(f as i64)
+    }
+
+    /// 高度の範囲を、その範囲を覆う f の範囲に変換する
+    fn f_range(z: u8, low: f64, high: f64) -> Result<(i64, i64), String> {
+        if low > high {
+            return Err(format!("altitude range {}..{} is empty", low, high));
+        }
+        let start = Self::f_of(z, low)?;
+        let end = (2_f64.powi(z as i32 - 25) * high).ceil() as i64 - 1;
+        Ok((start, end.max(start).min(F_MAX[z as usize] as i64)))
+    }
+
+    fn line_cells(z: u8, value: &Value, default_altitude: f64) -> Result<Vec<IndexBox>, String> {
+        let points = Self::array(value)?
+            .iter()
+            .map(|p| Self::position(p, default_altitude))
+            .collect::<Result<Vec<_>, _>>()?;
+        if points.len() < 2 {
+            return Err("LineString needs at least two positions".into());
+        }
+
+        let mut cells = vec![Self::point_cell(z, points[0])?];
+        for pair in points.windows(2) {
+            Self::point_cell(z, pair[1])?;
+            // ECEF の弦は長いと地下に潜るので、経緯度と高度で細かく区切ってからたどる
+            let pieces = Self::densify(z, pair[0], pair[1]);
+            for piece in pieces.windows(2) {
+                for step in traverse(z, piece[0], piece[1]) {
+                    let (f, _) = step.id.f_bounds();
+                    let (x, _) = step.id.x_bounds();
+                    let (y, _) = step.id.y_bounds();
+                    let (f, x, y) = (f as i64, x as i64, y as i64);
+                    let cell = IndexBox {
+                        f: (f, f),
+                        x: (x, x),
+                        y: (y, y),
+                        t: (0, 0),
+                    };
+                    if cells.last() != Some(&cell) {
+                        cells.push(cell);
+                    }
+                }
+            }
+        }
+        Ok(cells)
+    }
+
+    /// 線分を経度・緯度・高度の線形補間で、1 区間がボクセルの半分以下になるように区切る
+    ///
+    /// 経度は短い方の向きに補間するので、日付変更線をまたぐ線分もそのまま扱える
+    fn densify(z: u8, a: Point, b: Point) -> Vec<Point> {
+        let n = 2_f64.powi(z as i32);
+        let d_lon = (b.longitude - a.longitude + 180.0).rem_euclid(360.0) - 180.0;
+        let d_x = d_lon / 360.0 * n;
+        let d_y = SpaceTimeId::y_position(b.latitude, n as u32)
+            - SpaceTimeId::y_position(a.latitude, n as u32);
+        let d_f = 2_f64.powi(z as i32 - 25) * (b.altitude - a.altitude);
+        let pieces = (2.0 * d_x.abs().max(d_y.abs()).max(d_f.abs()))
+            .ceil()
+            .max(1.0) as usize;
+
+        (0..=pieces)
+            .map(|i| {
+                let k = i as f64 / pieces as f64;
+                Point {
+                    latitude: a.latitude + k * (b.latitude - a.latitude),
+                    longitude: a.longitude + k * d_lon,
+                    altitude: a.altitude + k * (b.altitude - a.altitude),
+                }
+            })
+            .collect()
+    }
+
+    /// 中心がポリゴンの内部にある列を、行ごとの x の範囲として返す
+    fn polygon_columns(z: u8, value: &Value) -> Result<Vec<Run>, String> {
+        let rings = Self::array(value)?
+            .iter()
+            .map(|ring| {
+                Self::array(ring)?
+                    .iter()
+                    .map(|p| Self::position(p, 0.0))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let Some(outer) = rings.first() else {
+            return Err("Polygon has no rings".into());
+        };
+        if rings.iter().any(|ring| ring.len() < 4) {
+            return Err("Polygon rings need at least four positions".into());
+        }
+        for point in rings.iter().flatten() {
+            Self::point_cell(z, *point)?;
+        }
+
+        let n = 2_f64.powi(z as i32);
+        let xy_max = XY_MAX[z as usize] as i64;
+        let y_of = |lat: f64| SpaceTimeId::y_position(lat, n as u32);
+        let north = outer.iter().map(|p| p.latitude).fold(f64::MIN, f64::max);
+        let south = outer.iter().map(|p| p.latitude).fold(f64::MAX, f64::min);
+        let y_start = (y_of(north).floor() as i64).clamp(0, xy_max);
+        let y_end = (y_of(south).floor() as i64).clamp(0, xy_max);
+
+        let mut columns = Vec::new();
+        for y in y_start..=y_end {
+            // 行の中心の緯度で走査線を引き、偶奇規則で内部の区間を求める
+            let latitude = (std::f64::consts::PI * (1.0 - 2.0 * (y as f64 + 0.5) / n))
+                .sinh()
+                .atan()
+                .to_degrees();
+            let mut crossings: Vec<f64> = Vec::new();
+            for ring in &rings {
+                for edge in ring.windows(2) {
+                    let (a, b) = (edge[0], edge[1]);
+                    if (a.latitude > latitude) != (b.latitude > latitude) {
+                        let k = (latitude - a.latitude) / (b.latitude - a.latitude);
+                        crossings.push(a.longitude + k * (b.longitude - a.longitude));
+                    }
+                }
+            }
+            crossings.sort_by(f64::total_cmp);
+
+            for pair in crossings.chunks_exact(2) {
+                let start = (SpaceTimeId::x_position(pair[0], n as u32) - 0.5).ceil() as i64;
+                let end = (SpaceTimeId::x_position(pair[1], n as u32) - 0.5).floor() as i64;
+                let (start, end) = (start.max(0), end.min(xy_max));
+                if start <= end {
+                    columns.push(((start, end), y));
+                }
+            }
+        }
+        Ok(columns)
+    }
+
+    fn number_property(properties: &Map<String, Value>, name: &str) -> Result<Option<f64>, String> {
+        match properties.get(name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => value
+                .as_f64()
+                .filter(|v| v.is_finite())
+                .map(Some)
+                .ok_or_else(|| format!("property {} must be a number, got {}", name, value)),
+        }
+    }
+
+    /// ISO 8601 の文字列または UNIX 時刻 [秒] を読む
+    fn time_property(
+        properties: &Map<String, Value>,
+        name: &str,
+    ) -> Result<Option<DateTime<Utc>>, String> {
+        match properties.get(name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(text)) => DateTime::parse_from_rfc3339(text)
+                .map(|time| Some(time.to_utc()))
+                .map_err(|e| format!("property {}: {}", name, e)),
+            Some(value) => value
+                .as_i64()
+                .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+                .map(Some)
+                .ok_or_else(|| format!("property {} must be a time, got {}", name, value)),
+        }
+    }
+
+    /// 時間帯を外側に丸めて t の範囲にする
+    fn time_range(
+        grid: Grid,
+        properties: &Map<String, Value>,
+        options: &ImportOptions,
+    ) -> Result<(i64, i64), String> {
+        let start = Self::time_property(properties, &options.time_start)?;
+        let end = Self::time_property(properties, &options.time_end)?;
+        if start.is_none() && end.is_none() {
+            return Ok((0, T_UNBOUNDED));
+        }
+        if grid.i == 0 {
+            return Err("the feature has a time window but the interval is 0".into());
+        }
+
+        let axis = TimeAxis::default();
+        let start = start.unwrap_or(axis.epoch());
+        let (t_start, t_end) = axis.t_bounds(grid.i, start, end, TimeRounding::Outward)?;
+        Ok((t_start as i64, t_end.map_or(T_UNBOUNDED, |t| t as i64)))
+    }
+}
@@ -0,0 +1,13 @@
+//! Conversion between space-time IDs and GeoJSON (RFC 7946).
+//!
+//! Available with the `serde_support` feature.
+
+pub mod export;
+pub mod import;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+/// 時刻を ISO 8601 (RFC 3339) の文字列に変換する
+pub(crate) fn iso_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
@@ -0,0 +1,87 @@
+use crate::id::DimensionRange::{self, AfterUnLimitRange, Any, BeforeUnLimitRange, LimitRange};
+use crate::id::SpaceTimeId;
+use crate::id::z_range::XY_MAX;
+use crate::set::SpaceTimeIdSet;
+
+impl SpaceTimeId {
+    /// Creates the IDs for a range whose X dimension may cross the antimeridian.
+    ///
+    /// X wraps around the globe: `x = 2^z - 1` (ending at 180°) is followed by `x = 0`
+    /// (starting at -180°). An `x` of `LimitRange(start, end)` with `start > end` is read as
+    /// the wrapped range `start..=2^z - 1` followed by `0..=end`, and is split into two IDs
+    /// at the antimeridian. A wrapped range that covers every column becomes a single ID
+    /// with `x == Any`.
+    ///
+    /// Any other `x` gives the same single ID as [`SpaceTimeId::new`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`SpaceTimeId::new`] for out-of-bounds values and invalid
+    /// `t`.
+    ///
+    /// # Japanese Note
+    ///
+    /// x の範囲 s > e を日付変更線をまたぐ範囲として扱い、2 つの ID に分ける
+    pub fn new_wrapping(
+        z: u8,
+        f: DimensionRange<i32>,
+        x: DimensionRange<u32>,
+        y: DimensionRange<u32>,
+        i: u32,
+        t: DimensionRange<u32>,
+    ) -> Result<Vec<SpaceTimeId>, String> {
+        let LimitRange(start, end) = x else {
+            return Ok(vec![SpaceTimeId::new(z, f, x, y, i, t)?]);
+        };
+        if start <= end {
+            return Ok(vec![SpaceTimeId::new(z, f, x, y, i, t)?]);
+        }
+        if z >= 32 {
+            return Err(format!("Zoom level z must be 0..=31. Got {}", z));
+        }
+        if start > XY_MAX[z as usize] {
+            return Err(format!("XY start {} > max {}", start, XY_MAX[z as usize]));
+        }
+
+        // 一周していれば分けずに Any にする
+        if end + 1 == start {
+            return Ok(vec![SpaceTimeId::new(z, f, Any, y, i, t)?]);
+        }
+        Ok(vec![
+            SpaceTimeId::new(z, f, AfterUnLimitRange(start), y, i, t)?,
+            SpaceTimeId::new(z, f, BeforeUnLimitRange(end), y, i, t)?,
+        ])
+    }
+}
+
+impl SpaceTimeIdSet {
+    /// Creates the set covered by a range whose X dimension may cross the antimeridian.
+    ///
+    /// This is the set form of [`SpaceTimeId::new_wrapping`]: an `x` of
+    /// `LimitRange(start, end)` with `start > end` gives the two IDs on either side of
+    /// the antimeridian. Decoders that read IDs from outside the crate, such as
+    /// [`parse_wrapping`](Self::parse_wrapping), [`from_bytes`](Self::from_bytes) and
+    /// [`LineReader::into_set`](crate::io::reader::LineReader::into_set), use it so that a
+    /// wrapped range is read instead of rejected.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`SpaceTimeId::new_wrapping`].
+    ///
+    /// # Japanese Note
+    ///
+    /// 日付変更線をまたぐ範囲を、分けた ID の集合として作る
+    pub fn from_wrapping(
+        z: u8,
+        f: DimensionRange<i32>,
+        x: DimensionRange<u32>,
+        y: DimensionRange<u32>,
+        i: u32,
+        t: DimensionRange<u32>,
+    ) -> Result<SpaceTimeIdSet, String> {
+        // 分けた 2 つは日付変更線の両側にあって重ならない
+        Ok(SpaceTimeIdSet::from_disjoint(SpaceTimeId::new_wrapping(
+            z, f, x, y, i, t,
+        )?))
+    }
+}
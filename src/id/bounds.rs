@@ -0,0 +1,62 @@
+use crate::id::DimensionRange::{AfterUnLimitRange, Any, BeforeUnLimitRange, LimitRange, Single};
+use crate::id::z_range::{F_MAX, F_MIN, XY_MAX};
+use crate::id::{DimensionRange, SpaceTimeId};
+
+impl SpaceTimeId {
+    /// Returns the inclusive index bounds `(min, max)` of the X dimension.
+    ///
+    /// Unlimited ranges and `Any` are resolved against the valid domain of the
+    /// current zoom level, so the result always describes concrete tile indices.
+    pub fn x_bounds(&self) -> (u32, u32) {
+        Self::xy_bounds(self.x, XY_MAX[self.z as usize])
+    }
+
+    /// Returns the inclusive index bounds `(min, max)` of the Y dimension.
+    ///
+    /// Unlimited ranges and `Any` are resolved against the valid domain of the
+    /// current zoom level.
+    pub fn y_bounds(&self) -> (u32, u32) {
+        Self::xy_bounds(self.y, XY_MAX[self.z as usize])
+    }
+
+    /// Returns the inclusive index bounds `(min, max)` of the F dimension.
+    ///
+    /// Unlimited ranges and `Any` are resolved against `F_MIN` and `F_MAX`
+    /// of the current zoom level.
+    pub fn f_bounds(&self) -> (i32, i32) {
+        let min = F_MIN[self.z as usize];
+        let max = F_MAX[self.z as usize];
+        match self.f {
+            Single(v) => (v, v),
+            LimitRange(s, e) => (s, e),
+            BeforeUnLimitRange(e) => (min, e),
+            AfterUnLimitRange(s) => (s, max),
+            Any => (min, max),
+        }
+    }
+
+    /// Returns the inclusive index bounds `(min, max)` of the T dimension.
+    ///
+    /// The time axis has no upper limit, so `AfterUnLimitRange` and `Any`
+    /// report `u32::MAX` as their end. Spatial IDs (`i == 0`) always return
+    /// `(0, u32::MAX)`.
+    pub fn t_bounds(&self) -> (u32, u32) {
+        match self.t {
+            Single(v) => (v, v),
+            LimitRange(s, e) => (s, e),
+            BeforeUnLimitRange(e) => (0, e),
+            AfterUnLimitRange(s) => (s, u32::MAX),
+            Any => (0, u32::MAX),
+        }
+    }
+
+    fn xy_bounds(range: DimensionRange<u32>, max: u32) -> (u32, u32) {
+        match range {
+            Single(v) => (v, v),
+            LimitRange(s, e) => (s, e),
+            BeforeUnLimitRange(e) => (0, e),
+            AfterUnLimitRange(s) => (s, max),
+            Any => (0, max),
+        }
+    }
+}
@@ -37,7 +37,7 @@ impl SpaceTimeId {
         let y_inversions = Self::split_xy_dimension(&self.y, self.z);
         let f_inversions = Self::split_f_dimension(&self.f, self.z);
         let t_inversions = if is_pure_space {
-            vec![self.t] // 時間次元はそのまま
+            vec![] // 時間次元はそのまま (時間の補集合は足さない)
         } else {
             Self::split_t_dimension(&self.t)
         };
@@ -155,8 +155,6 @@ impl SpaceTimeId {
             })
             .collect();
 
-        println!("{:?}", t_inversions);
-
         // 時間軸の補集合を展開
         for ele in tmp {
             match t_inversions.as_slice() {
@@ -1,3 +1,4 @@
+use crate::geoid::AltitudeReference;
 use crate::id::DimensionRange::{AfterUnLimitRange, Any, BeforeUnLimitRange, LimitRange, Single};
 use crate::id::{DimensionRange, SpaceTimeId};
 use std::f64::consts::PI;
@@ -28,10 +29,16 @@ impl SpaceTimeId {
     /// Converts the `SpaceTimeId` into geographic coordinates (latitude, longitude, and altitude).
     ///
     /// This method interprets the spatial and vertical components of the ID.
+    /// Altitudes are ellipsoidal heights; use
+    /// [`coordinates_with_reference`](Self::coordinates_with_reference) for other references.
     ///
     /// # Returns
     /// A [`Coordinates`] struct containing the latitude, longitude, and altitude ranges
     /// as floating-point tuples `(start, end)`, representing the spatial extent.
+    ///
+    /// The longitude range always runs west to east within `-180.0..=180.0`. An area that
+    /// crosses the antimeridian is made of two IDs (see [`SpaceTimeId::new_wrapping`]),
+    /// one ending at `180.0` and one starting at `-180.0`.
     pub fn coordinates(&self) -> Coordinates {
         let n = 2_u32.pow(self.z as u32);
 
@@ -46,11 +53,42 @@ impl SpaceTimeId {
         }
     }
 
-    fn longitude(x: u32, n: u32) -> f64 {
+    /// Converts the `SpaceTimeId` into geographic coordinates with altitudes in the given
+    /// reference.
+    ///
+    /// The latitude and longitude ranges are the same as [`coordinates`](Self::coordinates).
+    /// The altitude range is converted with the geoid undulation at the horizontal center
+    /// of the ID, so it is exact only where the undulation does not vary across the ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the geoid model has no value at the center of the ID.
+    ///
+    /// # Japanese Note
+    ///
+    /// 高度はボクセル中心のジオイド高で変換する
+    pub fn coordinates_with_reference(
+        &self,
+        reference: AltitudeReference,
+    ) -> Result<Coordinates, String> {
+        let coordinates = self.coordinates();
+        let latitude = (coordinates.latitude.0 + coordinates.latitude.1) / 2.0;
+        let longitude = (coordinates.longitude.0 + coordinates.longitude.1) / 2.0;
+        let (bottom, top) = coordinates.altitude;
+        Ok(Coordinates {
+            altitude: (
+                reference.altitude(latitude, longitude, bottom)?,
+                reference.altitude(latitude, longitude, top)?,
+            ),
+            ..coordinates
+        })
+    }
+
+    pub(crate) fn longitude(x: u32, n: u32) -> f64 {
         360.0 * (x as f64 / n as f64) - 180.0
     }
 
-    fn latitude(y: u32, n: u32) -> f64 {
+    pub(crate) fn latitude(y: u32, n: u32) -> f64 {
         let y_f64 = y as f64;
         let n_f64 = n as f64;
         let exponent = (1.0 - 2.0 * y_f64 / n_f64) * PI;
@@ -58,6 +96,17 @@ impl SpaceTimeId {
         lat_rad.to_degrees()
     }
 
+    /// 経度 (度) を x 方向のタイル位置にする (小数部はタイル内の位置)。折り返しも切り詰めもしない
+    pub(crate) fn x_position(longitude: f64, n: u32) -> f64 {
+        (longitude + 180.0) / 360.0 * n as f64
+    }
+
+    /// 緯度 (度) を Web メルカトルの y 方向のタイル位置にする (小数部はタイル内の位置)。切り詰めはしない
+    pub(crate) fn y_position(latitude: f64, n: u32) -> f64 {
+        let lat_rad = latitude.to_radians();
+        (1.0 - (lat_rad.tan() + 1.0 / lat_rad.cos()).ln() / PI) / 2.0 * n as f64
+    }
+
     fn altitude(f: i32, n: u32) -> f64 {
         let f64_val = f as f64;
         let n64_val = n as f64;
@@ -0,0 +1,357 @@
+use crate::id::DimensionRange::{Any, Single};
+use crate::id::SpaceTimeId;
+use crate::id::z_range::{F_MIN, XY_MAX};
+
+/// Space-filling curve used to linearize voxels into keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Curve {
+    /// Z-order curve: the bits of F, Y and X are interleaved.
+    Morton,
+    /// Hilbert curve: consecutive keys are always face-adjacent voxels.
+    Hilbert,
+}
+
+impl SpaceTimeId {
+    /// Returns the Morton (Z-order) key of a single voxel.
+    ///
+    /// F is shifted by `-F_MIN` so that it is non-negative, and each of F, X and Y is
+    /// given `z + 1` bits. The bits are interleaved from the most significant one,
+    /// in the order F, Y, X, so the key uses `3 * (z + 1)` bits of the `u128`.
+    ///
+    /// Keys are only comparable between voxels of the same zoom level. The T dimension
+    /// is not part of the key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if F, X or Y is not a `Single` value.
+    ///
+    /// # Japanese Note
+    ///
+    /// 単一ボクセルの Morton キー。f は F_MIN だけずらして非負にする
+    pub fn morton_key(&self) -> Result<u128, String> {
+        self.voxel_key(Curve::Morton)
+    }
+
+    /// Returns the Hilbert key of a single voxel.
+    ///
+    /// Uses the same axes and bit width as [`morton_key`](Self::morton_key), ordered
+    /// along a 3D Hilbert curve instead of a Z-order curve.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if F, X or Y is not a `Single` value.
+    ///
+    /// # Japanese Note
+    ///
+    /// 単一ボクセルの Hilbert キー
+    pub fn hilbert_key(&self) -> Result<u128, String> {
+        self.voxel_key(Curve::Hilbert)
+    }
+
+    /// Rebuilds the voxel of zoom level `z` from its Morton key.
+    ///
+    /// The result is a spatial ID (`i == 0`, `t == Any`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `z` is invalid or the key does not belong to a voxel of
+    /// zoom level `z`.
+    pub fn from_morton_key(z: u8, key: u128) -> Result<SpaceTimeId, String> {
+        Self::from_key(z, key, Curve::Morton)
+    }
+
+    /// Rebuilds the voxel of zoom level `z` from its Hilbert key.
+    ///
+    /// The result is a spatial ID (`i == 0`, `t == Any`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `z` is invalid or the key does not belong to a voxel of
+    /// zoom level `z`.
+    pub fn from_hilbert_key(z: u8, key: u128) -> Result<SpaceTimeId, String> {
+        Self::from_key(z, key, Curve::Hilbert)
+    }
+
+    /// Returns the keys of all voxels covered by this ID as at most `max_ranges`
+    /// contiguous intervals.
+    ///
+    /// The intervals are inclusive `(start, end)` pairs of keys at the zoom level of
+    /// this ID, sorted and separated by at least one key that is not in the result.
+    /// When the exact decomposition fits in `max_ranges` intervals, a range scan over
+    /// each interval of a store sorted by the same curve visits exactly the voxels of
+    /// this ID.
+    ///
+    /// Otherwise the result is a superset: the octree is refined only while the
+    /// intervals fit, and the intervals separated by the smallest gaps are then merged.
+    /// A range scan can visit voxels outside this ID, which the caller must filter out.
+    /// Thin IDs such as a single `f` layer over the whole X/Y domain need this, because
+    /// their exact decomposition has one interval per voxel column. A `max_ranges` of
+    /// `0` is treated as `1`.
+    ///
+    /// The T dimension is not part of the key and is ignored.
+    ///
+    /// # Japanese Note
+    ///
+    /// 八分木を 1 段ずつ分割し、区間数が max_ranges を超えたらそこで止めて、
+    /// 隙間の小さい区間同士を結合する (このときは元の ID を含む上位集合になる)
+    pub fn key_ranges(&self, curve: Curve, max_ranges: usize) -> Vec<(u128, u128)> {
+        let max_ranges = max_ranges.max(1);
+        let bits = self.z as u32 + 1;
+        let offset = -(F_MIN[self.z as usize] as i64);
+        let (f0, f1) = self.f_bounds();
+        let (x0, x1) = self.x_bounds();
+        let (y0, y1) = self.y_bounds();
+        let target = [
+            (f0 as i64 + offset, f1 as i64 + offset),
+            (y0 as i64, y1 as i64),
+            (x0 as i64, x1 as i64),
+        ];
+        let range = |corner: [i64; 3], level: u32| cell_range(corner, level, bits, curve);
+
+        // 完全に含まれるセルの区間と、一部だけ含むセル
+        let mut inside = Vec::new();
+        let mut partial = Vec::new();
+        let place = |corner: [i64; 3],
+                     level: u32,
+                     inside: &mut Vec<(u128, u128)>,
+                     partial: &mut Vec<([i64; 3], u32)>| {
+            match classify(&target, corner, level) {
+                Some(true) => inside.push(range(corner, level)),
+                Some(false) => partial.push((corner, level)),
+                None => {}
+            }
+        };
+        place([0; 3], bits, &mut inside, &mut partial);
+
+        while !partial.is_empty() {
+            let mut next = Vec::new();
+            for (corner, level) in partial {
+                let half = 1_i64 << (level - 1);
+                for child in 0..8 {
+                    let corner = [
+                        corner[0] + if child & 4 != 0 { half } else { 0 },
+                        corner[1] + if child & 2 != 0 { half } else { 0 },
+                        corner[2] + if child & 1 != 0 { half } else { 0 },
+                    ];
+                    place(corner, level - 1, &mut inside, &mut next);
+                }
+            }
+            partial = next;
+
+            // 分割しても区間は減らないので、超えた時点でこれ以上分けない
+            let count = merge_adjacent(
+                inside
+                    .iter()
+                    .copied()
+                    .chain(partial.iter().map(|&(c, l)| range(c, l)))
+                    .collect(),
+            )
+            .len();
+            if count > max_ranges {
+                break;
+            }
+        }
+
+        let ranges = inside
+            .into_iter()
+            .chain(partial.into_iter().map(|(c, l)| range(c, l)))
+            .collect();
+        merge_closest(merge_adjacent(ranges), max_ranges)
+    }
+
+    fn voxel_key(&self, curve: Curve) -> Result<u128, String> {
+        let (Single(f), Single(x), Single(y)) = (self.f, self.x, self.y) else {
+            return Err(format!("{} is not a single voxel", self));
+        };
+        let bits = self.z as u32 + 1;
+        let axes = [
+            (f as i64 - F_MIN[self.z as usize] as i64) as u64,
+            y as u64,
+            x as u64,
+        ];
+        Ok(encode(axes, bits, curve))
+    }
+
+    fn from_key(z: u8, key: u128, curve: Curve) -> Result<SpaceTimeId, String> {
+        if z >= 32 {
+            return Err(format!("Zoom level z must be 0..=31. Got {}", z));
+        }
+        let bits = z as u32 + 1;
+        if key >> (3 * bits) != 0 {
+            return Err(format!("Key {} is out of range for zoom level {}", key, z));
+        }
+
+        let [f, y, x] = decode(key, bits, curve);
+        let max = XY_MAX[z as usize] as u64;
+        if x > max || y > max {
+            return Err(format!(
+                "Key {} is outside the X/Y domain of zoom level {}",
+                key, z
+            ));
+        }
+        let f = f as i64 + F_MIN[z as usize] as i64;
+        SpaceTimeId::new(
+            z,
+            Single(f as i32),
+            Single(x as u32),
+            Single(y as u32),
+            0,
+            Any,
+        )
+    }
+}
+
+/// 各軸 bits ビットの座標 (f, y, x) をキーに変換する
+fn encode(mut axes: [u64; 3], bits: u32, curve: Curve) -> u128 {
+    if curve == Curve::Hilbert {
+        axes_to_transpose(&mut axes, bits);
+    }
+    let mut key = 0_u128;
+    for k in (0..bits).rev() {
+        for axis in axes {
+            key = (key << 1) | ((axis >> k) & 1) as u128;
+        }
+    }
+    key
+}
+
+fn decode(key: u128, bits: u32, curve: Curve) -> [u64; 3] {
+    let mut axes = [0_u64; 3];
+    for k in 0..bits {
+        for (a, axis) in axes.iter_mut().enumerate() {
+            let bit = (key >> (3 * k + 2 - a as u32)) & 1;
+            *axis |= (bit as u64) << k;
+        }
+    }
+    if curve == Curve::Hilbert {
+        transpose_to_axes(&mut axes, bits);
+    }
+    axes
+}
+
+/// Skilling (2004) の方法で、座標を Hilbert キーの転置形式に変換する
+fn axes_to_transpose(x: &mut [u64; 3], bits: u32) {
+    let m = 1_u64 << (bits - 1);
+
+    let mut q = m;
+    while q > 1 {
+        let p = q - 1;
+        for i in 0..3 {
+            if x[i] & q != 0 {
+                x[0] ^= p;
+            } else {
+                let t = (x[0] ^ x[i]) & p;
+                x[0] ^= t;
+                x[i] ^= t;
+            }
+        }
+        q >>= 1;
+    }
+
+    for i in 1..3 {
+        x[i] ^= x[i - 1];
+    }
+    let mut t = 0;
+    let mut q = m;
+    while q > 1 {
+        if x[2] & q != 0 {
+            t ^= q - 1;
+        }
+        q >>= 1;
+    }
+    for axis in x.iter_mut() {
+        *axis ^= t;
+    }
+}
+
+/// axes_to_transpose の逆変換
+fn transpose_to_axes(x: &mut [u64; 3], bits: u32) {
+    let n = 2_u64 << (bits - 1);
+
+    let t = x[2] >> 1;
+    for i in (1..3).rev() {
+        x[i] ^= x[i - 1];
+    }
+    x[0] ^= t;
+
+    let mut q = 2;
+    while q != n {
+        let p = q - 1;
+        for i in (0..3).rev() {
+            if x[i] & q != 0 {
+                x[0] ^= p;
+            } else {
+                let t = (x[0] ^ x[i]) & p;
+                x[0] ^= t;
+                x[i] ^= t;
+            }
+        }
+        q <<= 1;
+    }
+}
+
+/// 一辺 2^level の整列したセル (左下 corner) と target の関係。
+/// 交わらなければ None、完全に含まれれば Some(true)、一部だけなら Some(false)
+fn classify(target: &[(i64, i64); 3], corner: [i64; 3], level: u32) -> Option<bool> {
+    let side = 1_i64 << level;
+    let mut inside = true;
+    for (axis, &(s, e)) in target.iter().enumerate() {
+        let (c0, c1) = (corner[axis], corner[axis] + side - 1);
+        if c1 < s || e < c0 {
+            return None;
+        }
+        if c0 < s || e < c1 {
+            inside = false;
+        }
+    }
+    Some(inside)
+}
+
+/// 整列したセルのキー区間。セルのキーは連続していて、下位 3 * level ビットだけが異なる
+fn cell_range(corner: [i64; 3], level: u32, bits: u32, curve: Curve) -> (u128, u128) {
+    let key = encode(corner.map(|c| c as u64), bits, curve);
+    let span = (1_u128 << (3 * level)) - 1;
+    let start = key & !span;
+    (start, start + span)
+}
+
+/// 区間を昇順に並べ、隣接する区間を結合する
+fn merge_adjacent(mut ranges: Vec<(u128, u128)>) -> Vec<(u128, u128)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(u128, u128)> = Vec::with_capacity(ranges.len());
+    for (s, e) in ranges {
+        match merged.last_mut() {
+            Some(last) if last.1 + 1 == s => last.1 = e,
+            _ => merged.push((s, e)),
+        }
+    }
+    merged
+}
+
+/// 区間が max 個以下になるまで、隙間の小さい順に隣の区間と結合する
+fn merge_closest(ranges: Vec<(u128, u128)>, max: usize) -> Vec<(u128, u128)> {
+    if ranges.len() <= max {
+        return ranges;
+    }
+    let mut gaps: Vec<(u128, usize)> = ranges
+        .windows(2)
+        .enumerate()
+        .map(|(i, w)| (w[1].0 - w[0].1, i))
+        .collect();
+    gaps.sort_unstable();
+
+    // 結合する隙間 (i 番目と i + 1 番目の間)
+    let mut joined = vec![false; ranges.len()];
+    for &(_, i) in &gaps[..ranges.len() - max] {
+        joined[i] = true;
+    }
+
+    let mut result: Vec<(u128, u128)> = Vec::with_capacity(max);
+    for (i, &(s, e)) in ranges.iter().enumerate() {
+        match result.last_mut() {
+            Some(last) if i > 0 && joined[i - 1] => last.1 = e,
+            _ => result.push((s, e)),
+        }
+    }
+    result
+}
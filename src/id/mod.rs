@@ -1,9 +1,18 @@
+pub mod antimeridian;
+pub mod bounds;
 pub mod center;
 pub mod complement;
 pub mod coordinates;
+pub mod key;
+pub mod parse;
 pub mod pure;
 pub mod relation;
 pub mod scale;
+#[cfg(feature = "serde_support")]
+pub mod serde_string;
+pub mod tilehash;
+pub mod time;
+pub mod translate;
 pub mod value;
 pub mod vertex;
 pub mod with;
@@ -31,8 +40,8 @@ pub enum DimensionRange<T> {
     Any,
 }
 
-use std::fmt;
 use DimensionRange::{AfterUnLimitRange, Any, BeforeUnLimitRange, LimitRange, Single};
+use std::fmt;
 
 use crate::id::z_range::{F_MAX, F_MIN, XY_MAX};
 
@@ -67,6 +76,10 @@ where
 /// - No normalization applied (used as-is).
 ///
 /// ## `LimitRange(start, end)`
+/// - If `start > end`:
+///   - For x: an error is returned. A range that crosses the antimeridian is built with
+///     `SpaceTimeId::new_wrapping`, which splits it into two IDs.
+///   - For y/f/t: the bounds are swapped.
 /// - If `start == end`: converted to `Single(start)`.
 /// - If the range spans the entire valid domain:
 ///   - For x/y: `0..=2^z - 1`
@@ -122,6 +135,8 @@ impl SpaceTimeId {
     /// redundant or equivalent representations are reduced to a canonical form.
     ///
     /// ## XY dimensions
+    /// - `LimitRange(s, e)` with `s > e` is an error for X (see [`new_wrapping`](Self::new_wrapping))
+    ///   and is swapped for Y
     /// - `AfterUnLimitRange(0)` → `Any`
     /// - `AfterUnLimitRange(max)` → `Single(max)`
     /// - `BeforeUnLimitRange(max)` → `Any`
@@ -141,6 +156,7 @@ impl SpaceTimeId {
     ///
     /// # Errors
     /// - If any range value is outside its valid bounds for the given zoom `z`
+    /// - If `x` is a `LimitRange(s, e)` with `s > e`
     /// - If `t` is not `Any` when `i == 0`
     pub fn new(
         z: u8,
@@ -217,6 +233,20 @@ impl SpaceTimeId {
             }
         }
 
+        // x は経度方向に一周するので、s > e は入れ替えずに誤りとする
+        fn normalize_x(
+            dim: &DimensionRange<u32>,
+            xy_max: u32,
+        ) -> Result<DimensionRange<u32>, String> {
+            match *dim {
+                DimensionRange::LimitRange(s, e) if s > e => Err(format!(
+                    "X range {}:{} has start > end; use SpaceTimeId::new_wrapping or SpaceTimeIdSet::from_wrapping for a range that crosses the antimeridian",
+                    s, e
+                )),
+                _ => normalize_xy(dim, xy_max),
+            }
+        }
+
         fn normalize_f(
             dim: &DimensionRange<i32>,
             f_min: i32,
@@ -329,7 +359,7 @@ impl SpaceTimeId {
 
         Ok(Self {
             z,
-            x: normalize_xy(&x, xy_max)?,
+            x: normalize_x(&x, xy_max)?,
             y: normalize_xy(&y, xy_max)?,
             f: normalize_f(&f, f_min, f_max)?,
             i,
@@ -0,0 +1,116 @@
+use std::str::FromStr;
+
+use crate::id::DimensionRange::{
+    self, AfterUnLimitRange, Any, BeforeUnLimitRange, LimitRange, Single,
+};
+use crate::id::SpaceTimeId;
+use crate::set::SpaceTimeIdSet;
+
+impl<T> FromStr for DimensionRange<T>
+where
+    T: FromStr,
+{
+    type Err = String;
+
+    /// Parses the notation written by `Display`: `5`, `5:10`, `-:10`, `5:-` or `-`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = |v: &str| {
+            v.parse::<T>()
+                .map_err(|_| format!("Invalid value '{}' in '{}'", v, s))
+        };
+
+        if s == "-" {
+            return Ok(Any);
+        }
+        match s.split_once(':') {
+            Some(("-", "-")) => Err(format!("Invalid range '{}'", s)),
+            Some(("-", end)) => Ok(BeforeUnLimitRange(value(end)?)),
+            Some((start, "-")) => Ok(AfterUnLimitRange(value(start)?)),
+            Some((start, end)) => Ok(LimitRange(value(start)?, value(end)?)),
+            None => Ok(Single(value(s)?)),
+        }
+    }
+}
+
+impl FromStr for SpaceTimeId {
+    type Err = String;
+
+    /// Parses the notation written by `Display`, `z/f/x/y_i/t`.
+    ///
+    /// The temporal part `_i/t` may be omitted for spatial IDs, so `4/10/5/3` is read as
+    /// `4/10/5/3_0/-`. The result is validated and normalized by [`SpaceTimeId::new`],
+    /// so an X range that crosses the antimeridian is rejected; read it with
+    /// [`SpaceTimeIdSet::parse_wrapping`] instead.
+    ///
+    /// # Japanese Note
+    ///
+    /// Display の表記から ID を読み取る。時間の部分は省略できる
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (z, f, x, y, i, t) = parts(s)?;
+        SpaceTimeId::new(z, f, x, y, i, t)
+    }
+}
+
+impl SpaceTimeIdSet {
+    /// Parses the notation written by `Display`, allowing an X range that crosses the
+    /// antimeridian.
+    ///
+    /// The notation is the same as for [`SpaceTimeId::from_str`](FromStr::from_str), but
+    /// an X range `start:end` with `start > end` is read as the wrapped range and split
+    /// with [`SpaceTimeIdSet::from_wrapping`], so `3/0/6:1/2` gives the IDs `3/0/6:-/2`
+    /// and `3/0/-:1/2`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the notation is malformed or the values are rejected by
+    /// [`SpaceTimeId::new_wrapping`].
+    ///
+    /// # Japanese Note
+    ///
+    /// x の s > e を日付変更線をまたぐ範囲として読む
+    pub fn parse_wrapping(s: &str) -> Result<SpaceTimeIdSet, String> {
+        let (z, f, x, y, i, t) = parts(s)?;
+        SpaceTimeIdSet::from_wrapping(z, f, x, y, i, t)
+    }
+}
+
+/// 検証前の z, f, x, y, i, t
+type Parts = (
+    u8,
+    DimensionRange<i32>,
+    DimensionRange<u32>,
+    DimensionRange<u32>,
+    u32,
+    DimensionRange<u32>,
+);
+
+/// 表記を z, f, x, y, i, t に分けて読む。値の検証はしない
+fn parts(s: &str) -> Result<Parts, String> {
+    let (space, time) = match s.split_once('_') {
+        Some((space, time)) => (space, Some(time)),
+        None => (s, None),
+    };
+
+    let parts: Vec<&str> = space.split('/').collect();
+    let [z, f, x, y] = parts[..] else {
+        return Err(format!("Expected 'z/f/x/y_i/t', got '{}'", s));
+    };
+    let z = z
+        .parse::<u8>()
+        .map_err(|_| format!("Invalid zoom level '{}' in '{}'", z, s))?;
+
+    let (i, t) = match time {
+        None => (0, Any),
+        Some(time) => {
+            let Some((i, t)) = time.split_once('/') else {
+                return Err(format!("Expected 'z/f/x/y_i/t', got '{}'", s));
+            };
+            let i = i
+                .parse::<u32>()
+                .map_err(|_| format!("Invalid interval '{}' in '{}'", i, s))?;
+            (i, t.parse()?)
+        }
+    };
+
+    Ok((z, f.parse()?, x.parse()?, y.parse()?, i, t))
+}
@@ -0,0 +1,110 @@
+//! Serde support for the compact string form of `SpaceTimeId`.
+//!
+//! By default `SpaceTimeId` serializes as an object with tagged `DimensionRange`
+//! values. This module writes it as its `Display` notation instead, such as
+//! `"4/10:-/5/3_60/100:200"`, either through `#[serde(with = ...)]` on a field or
+//! through the [`SpaceTimeIdString`] newtype.
+//!
+//! ```ignore
+//! #[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+//! struct Request {
+//!     #[serde(with = "kasane_logic::id::serde_string")]
+//!     #[schemars(with = "kasane_logic::id::serde_string::SpaceTimeIdString")]
+//!     id: SpaceTimeId,
+//! }
+//! ```
+//!
+//! Available with the `serde_support` feature.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+use crate::id::SpaceTimeId;
+use crate::set::SpaceTimeIdSet;
+
+/// Regular expression of the string form, used in the JSON schema.
+///
+/// The temporal part `_i/t` is optional, as in [`SpaceTimeId::from_str`](std::str::FromStr).
+pub const PATTERN: &str = r"^\d+/(-|-?\d+|-?\d+:-?\d+|-:-?\d+|-?\d+:-)/(-|\d+|\d+:\d+|-:\d+|\d+:-)/(-|\d+|\d+:\d+|-:\d+|\d+:-)(_\d+/(-|\d+|\d+:\d+|-:\d+|\d+:-))?$";
+
+/// Serializes a `SpaceTimeId` as its string form.
+pub fn serialize<S: Serializer>(id: &SpaceTimeId, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(id)
+}
+
+/// Deserializes a `SpaceTimeId` from its string form.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SpaceTimeId, D::Error> {
+    struct Visitor;
+
+    impl de::Visitor<'_> for Visitor {
+        type Value = SpaceTimeId;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a space-time ID string such as \"4/10/5/3_60/100\"")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<SpaceTimeId, E> {
+            v.parse().map_err(E::custom)
+        }
+    }
+
+    deserializer.deserialize_str(Visitor)
+}
+
+/// Deserializes the string form into a set, reading an X range with `start > end` as a
+/// range that crosses the antimeridian.
+///
+/// Use it with `#[serde(deserialize_with = ...)]` on a `SpaceTimeIdSet` field. The
+/// string is parsed with [`SpaceTimeIdSet::parse_wrapping`].
+pub fn deserialize_wrapping<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<SpaceTimeIdSet, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    SpaceTimeIdSet::parse_wrapping(&s).map_err(de::Error::custom)
+}
+
+/// `SpaceTimeId` that serializes as its string form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SpaceTimeIdString(pub SpaceTimeId);
+
+impl From<SpaceTimeId> for SpaceTimeIdString {
+    fn from(id: SpaceTimeId) -> Self {
+        Self(id)
+    }
+}
+
+impl From<SpaceTimeIdString> for SpaceTimeId {
+    fn from(id: SpaceTimeIdString) -> Self {
+        id.0
+    }
+}
+
+impl Serialize for SpaceTimeIdString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SpaceTimeIdString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer).map(Self)
+    }
+}
+
+impl JsonSchema for SpaceTimeIdString {
+    fn schema_name() -> Cow<'static, str> {
+        "SpaceTimeIdString".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "pattern": PATTERN,
+            "description": "Space-time ID in the form z/f/x/y_i/t. Each of f, x, y and t is a value (5), a range (5:10), an open range (-:10 or 5:-) or any (-).",
+            "examples": ["4/10:-/5/3_60/100:200", "20/0/931000/412000"]
+        })
+    }
+}
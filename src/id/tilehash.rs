@@ -0,0 +1,223 @@
+use crate::id::DimensionRange::{Any, Single};
+use crate::id::SpaceTimeId;
+
+/// 出力するセル (z, f, x, y)
+type Cell = (u8, i64, i64, i64);
+
+impl SpaceTimeId {
+    /// Encodes a single voxel as a ZFXY tilehash.
+    ///
+    /// The tilehash has one digit per zoom level, from the coarsest to the finest.
+    /// Each digit is `1 + x_bit + 2 * y_bit + 4 * f_bit` (`1` to `8`), taken from the
+    /// bits of X, Y and `|F|` at that level. A negative F is written as `-` followed by
+    /// the digits of `|F|`. Zoom level 0 with `F = 0` is the empty string.
+    ///
+    /// The T dimension is not part of the tilehash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if F, X or Y is not a `Single` value, or if `F == -2^z`,
+    /// whose absolute value does not fit in `z` digits.
+    ///
+    /// # Japanese Note
+    ///
+    /// 単一ボクセルを ZFXY の tilehash に変換する。負の f は '-' と |f| で表す
+    pub fn to_tilehash(&self) -> Result<String, String> {
+        let (Single(f), Single(x), Single(y)) = (self.f, self.x, self.y) else {
+            return Err(format!("{} is not a single voxel", self));
+        };
+        tilehash(self.z, f as i64, x as i64, y as i64)
+    }
+
+    /// Parses a ZFXY tilehash written by [`to_tilehash`](Self::to_tilehash).
+    ///
+    /// The zoom level is the number of digits. The result is a spatial ID
+    /// (`i == 0`, `t == Any`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string contains characters other than a leading `-`
+    /// and the digits `1` to `8`, if it has more than 31 digits, or if it is a negative
+    /// zero (`-` followed only by digits with no F bit).
+    pub fn from_tilehash(hash: &str) -> Result<SpaceTimeId, String> {
+        let (negative, digits) = match hash.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, hash),
+        };
+        if digits.len() > 31 {
+            return Err(format!("Tilehash '{}' has more than 31 digits", hash));
+        }
+
+        let (mut f, mut x, mut y) = (0_i64, 0_u32, 0_u32);
+        for c in digits.chars() {
+            let d = match c {
+                '1'..='8' => c as u32 - '1' as u32,
+                _ => return Err(format!("Invalid tilehash digit '{}' in '{}'", c, hash)),
+            };
+            x = (x << 1) | (d & 1);
+            y = (y << 1) | ((d >> 1) & 1);
+            f = (f << 1) | ((d >> 2) & 1) as i64;
+        }
+        if negative {
+            if f == 0 {
+                return Err(format!("Tilehash '{}' is a negative zero", hash));
+            }
+            f = -f;
+        }
+
+        SpaceTimeId::new(
+            digits.len() as u8,
+            Single(f as i32),
+            Single(x),
+            Single(y),
+            0,
+            Any,
+        )
+    }
+
+    /// Encodes the voxels of this ID as the minimal list of tilehashes.
+    ///
+    /// Blocks of voxels that form a whole voxel of a coarser zoom level are written as
+    /// the shorter tilehash of that voxel. A coarser voxel with `F == -2^z` cannot be
+    /// written and is split into its children instead.
+    ///
+    /// The T dimension is not part of the tilehash and is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ID contains a voxel with `F == -2^z` at its own zoom
+    /// level.
+    ///
+    /// # Japanese Note
+    ///
+    /// 範囲を持つ ID を、できるだけ粗いボクセルの tilehash の列に分解する
+    pub fn to_tilehashes(&self) -> Result<Vec<String>, String> {
+        let (f0, f1) = self.f_bounds();
+        let (x0, x1) = self.x_bounds();
+        let (y0, y1) = self.y_bounds();
+        let target = [
+            (f0 as i64, f1 as i64),
+            (x0 as i64, x1 as i64),
+            (y0 as i64, y1 as i64),
+        ];
+
+        let mut cells = Vec::new();
+        for f in [-1, 0] {
+            cover(self.z, &target, (0, f, 0, 0), true, &mut cells)?;
+        }
+        cells
+            .into_iter()
+            .map(|(z, f, x, y)| tilehash(z, f, x, y))
+            .collect()
+    }
+
+    /// Encodes the X/Y footprint of a single voxel as a quadkey.
+    ///
+    /// The quadkey has one digit `0` to `3` per zoom level, `x_bit + 2 * y_bit`, as
+    /// used by Bing Maps and other web map tile services. F and T are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if X or Y is not a `Single` value.
+    pub fn to_quadkey(&self) -> Result<String, String> {
+        let (Single(x), Single(y)) = (self.x, self.y) else {
+            return Err(format!("{} does not have a single X/Y tile", self));
+        };
+        Ok(quadkey(self.z, x as i64, y as i64))
+    }
+
+    /// Encodes the X/Y footprint of this ID as the minimal list of quadkeys.
+    ///
+    /// Blocks of tiles that form a whole tile of a coarser zoom level are written as
+    /// the shorter quadkey of that tile. F and T are ignored.
+    pub fn to_quadkeys(&self) -> Vec<String> {
+        let (x0, x1) = self.x_bounds();
+        let (y0, y1) = self.y_bounds();
+        let target = [(0, 0), (x0 as i64, x1 as i64), (y0 as i64, y1 as i64)];
+
+        let mut cells = Vec::new();
+        cover(self.z, &target, (0, 0, 0, 0), false, &mut cells)
+            .expect("quadkeys have no unencodable cells");
+        cells
+            .into_iter()
+            .map(|(z, _, x, y)| quadkey(z, x, y))
+            .collect()
+    }
+}
+
+fn tilehash(z: u8, f: i64, x: i64, y: i64) -> Result<String, String> {
+    let abs_f = f.abs();
+    if abs_f >= 1_i64 << z {
+        return Err(format!(
+            "F value {} at zoom level {} cannot be written as a tilehash",
+            f, z
+        ));
+    }
+
+    let mut out = String::with_capacity(z as usize + 1);
+    if f < 0 {
+        out.push('-');
+    }
+    for level in (0..z).rev() {
+        let digit = 1 + ((x >> level) & 1) + 2 * ((y >> level) & 1) + 4 * ((abs_f >> level) & 1);
+        out.push(char::from(b'0' + digit as u8));
+    }
+    Ok(out)
+}
+
+fn quadkey(z: u8, x: i64, y: i64) -> String {
+    (0..z)
+        .rev()
+        .map(|level| char::from(b'0' + (((x >> level) & 1) + 2 * ((y >> level) & 1)) as u8))
+        .collect()
+}
+
+/// z ズームの範囲 target を、粗いセルから順に覆う。with_f が false なら f は分割しない
+fn cover(
+    z: u8,
+    target: &[(i64, i64); 3],
+    cell: Cell,
+    with_f: bool,
+    out: &mut Vec<Cell>,
+) -> Result<(), String> {
+    let (cz, f, x, y) = cell;
+    let k = z - cz;
+    let span = |v: i64| (v << k, ((v + 1) << k) - 1);
+    let spans = [span(f), span(x), span(y)];
+
+    let mut inside = true;
+    for (axis, (&(s, e), (c0, c1))) in target.iter().zip(spans).enumerate() {
+        if axis == 0 && !with_f {
+            continue;
+        }
+        if c1 < s || e < c0 {
+            return Ok(());
+        }
+        if c0 < s || e < c1 {
+            inside = false;
+        }
+    }
+
+    // f = -2^cz は tilehash で書けないので、子に分ける
+    let encodable = !with_f || f > -(1_i64 << cz);
+    if inside && encodable {
+        out.push(cell);
+        return Ok(());
+    }
+    if cz == z {
+        return Err(format!(
+            "F value {} at zoom level {} cannot be written as a tilehash",
+            f, z
+        ));
+    }
+
+    let fs: &[i64] = if with_f { &[2 * f, 2 * f + 1] } else { &[0] };
+    for &child_f in fs {
+        for child_y in [2 * y, 2 * y + 1] {
+            for child_x in [2 * x, 2 * x + 1] {
+                cover(z, target, (cz + 1, child_f, child_x, child_y), with_f, out)?;
+            }
+        }
+    }
+    Ok(())
+}
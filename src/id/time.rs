@@ -0,0 +1,242 @@
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::id::DimensionRange::{self, AfterUnLimitRange, Any, LimitRange, Single};
+use crate::id::SpaceTimeId;
+
+/// How a time window that does not align with the interval `i` is turned into whole
+/// time indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeRounding {
+    /// Cover the whole window: the result may start earlier and end later.
+    #[default]
+    Outward,
+    /// Stay inside the window: only intervals fully within it are kept.
+    Inward,
+}
+
+/// Origin of the time index `t`.
+///
+/// With interval `i` seconds, the index `t` covers the half-open window
+/// `[epoch + t * i, epoch + (t + 1) * i)`. The default epoch is the UNIX epoch
+/// (1970-01-01T00:00:00Z), which is what the rest of the library assumes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeAxis {
+    epoch: DateTime<Utc>,
+}
+
+impl Default for TimeAxis {
+    fn default() -> Self {
+        Self {
+            epoch: DateTime::UNIX_EPOCH,
+        }
+    }
+}
+
+impl TimeAxis {
+    /// Creates a time axis whose index `0` starts at `epoch`.
+    pub fn new(epoch: DateTime<Utc>) -> Self {
+        Self { epoch }
+    }
+
+    /// Returns the start of index `0`.
+    pub fn epoch(&self) -> DateTime<Utc> {
+        self.epoch
+    }
+
+    /// Converts the half-open window `[start, end)` into a range of time indices with
+    /// interval `i`.
+    ///
+    /// `end == None` means the window has no end and gives `AfterUnLimitRange`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `i == 0`, if `start` is before the epoch, if the window is
+    /// empty (`end <= start`, or no whole interval fits in it with
+    /// [`TimeRounding::Inward`]), or if an index does not fit in `u32`.
+    ///
+    /// # Japanese Note
+    ///
+    /// 時刻の区間を、間隔 i の時間インデックスの範囲に丸める
+    pub fn t_range(
+        &self,
+        i: u32,
+        start: DateTime<Utc>,
+        end: Option<DateTime<Utc>>,
+        rounding: TimeRounding,
+    ) -> Result<DimensionRange<u32>, String> {
+        Ok(match self.t_bounds(i, start, end, rounding)? {
+            (s, None) => AfterUnLimitRange(s),
+            (s, Some(e)) if s == e => Single(s),
+            (s, Some(e)) => LimitRange(s, e),
+        })
+    }
+
+    /// Returns the start of time index `t` with interval `i`.
+    ///
+    /// `t` may be one past the last `u32` index, to get the exclusive end of a window.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the time is outside the range of `DateTime`.
+    pub fn time_of(&self, i: u32, t: u64) -> Result<DateTime<Utc>, String> {
+        let seconds = t
+            .checked_mul(i as u64)
+            .and_then(|s| i64::try_from(s).ok())
+            .and_then(TimeDelta::try_seconds)
+            .ok_or_else(|| format!("time index {} with interval {} is out of range", t, i))?;
+        self.epoch
+            .checked_add_signed(seconds)
+            .ok_or_else(|| format!("time index {} with interval {} is out of range", t, i))
+    }
+
+    /// 開始と終了 (含む) のインデックス。終了がなければ None
+    pub(crate) fn t_bounds(
+        &self,
+        i: u32,
+        start: DateTime<Utc>,
+        end: Option<DateTime<Utc>>,
+        rounding: TimeRounding,
+    ) -> Result<(u32, Option<u32>), String> {
+        if i == 0 {
+            return Err("the time interval i must be non-zero".into());
+        }
+        if start < self.epoch {
+            return Err(format!("time {} is before the epoch {}", start, self.epoch));
+        }
+        if let Some(end) = end.filter(|&end| end <= start) {
+            return Err(format!("time window {}..{} is empty", start, end));
+        }
+
+        let length = i as i128 * 1_000_000_000;
+        let offset = |time: DateTime<Utc>| {
+            let d = time.signed_duration_since(self.epoch);
+            d.num_seconds() as i128 * 1_000_000_000 + d.subsec_nanos() as i128
+        };
+        let index = |value: i128| {
+            u32::try_from(value).map_err(|_| format!("time index {} is out of u32 range", value))
+        };
+
+        let start_offset = offset(start);
+        let t_start = match rounding {
+            TimeRounding::Outward => start_offset.div_euclid(length),
+            TimeRounding::Inward => {
+                start_offset.div_euclid(length) + (start_offset.rem_euclid(length) != 0) as i128
+            }
+        };
+
+        let Some(end) = end else {
+            return Ok((index(t_start)?, None));
+        };
+        let end_offset = offset(end);
+        let t_end = match rounding {
+            TimeRounding::Outward => {
+                end_offset.div_euclid(length) + (end_offset.rem_euclid(length) != 0) as i128
+            }
+            TimeRounding::Inward => end_offset.div_euclid(length),
+        } - 1;
+        if t_end < t_start {
+            return Err(format!(
+                "no whole interval of {} s fits in {}..{}",
+                i, start, end
+            ));
+        }
+        Ok((index(t_start)?, Some(index(t_end)?)))
+    }
+}
+
+impl SpaceTimeId {
+    /// Returns a copy of this ID restricted to the time window `[start, end)`.
+    ///
+    /// The interval becomes `i` and `t` is computed on `axis` with
+    /// [`TimeAxis::t_range`]. `end == None` means the window has no end.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`TimeAxis::t_range`].
+    ///
+    /// # Japanese Note
+    ///
+    /// 時刻の区間から時間 ID を作る
+    pub fn with_time(
+        &self,
+        i: u32,
+        start: DateTime<Utc>,
+        end: Option<DateTime<Utc>>,
+        axis: &TimeAxis,
+        rounding: TimeRounding,
+    ) -> Result<Self, String> {
+        let t = axis.t_range(i, start, end, rounding)?;
+        Self::new(self.z, self.f, self.x, self.y, i, t)
+    }
+
+    /// Returns the time window `[start, end)` covered by this ID on `axis`.
+    ///
+    /// The end is `None` if the time range has no end (`AfterUnLimitRange` or `Any`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for spatial IDs (`i == 0`), and if the window is outside the
+    /// range of `DateTime`.
+    ///
+    /// # Japanese Note
+    ///
+    /// 時間 ID が表す時刻の区間を返す
+    pub fn time_window(
+        &self,
+        axis: &TimeAxis,
+    ) -> Result<(DateTime<Utc>, Option<DateTime<Utc>>), String> {
+        if self.i == 0 {
+            return Err(format!("{} is a spatial ID and has no time window", self));
+        }
+        let (start, end) = self.t_bounds();
+        let end = match self.t {
+            AfterUnLimitRange(_) | Any => None,
+            _ => Some(axis.time_of(self.i, end as u64 + 1)?),
+        };
+        Ok((axis.time_of(self.i, start as u64)?, end))
+    }
+}
+
+/// An instant on the time axis of the library (counted from the UNIX epoch).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimePoint {
+    /// Seconds since the UNIX epoch.
+    Seconds(u64),
+    /// Start of the time index `t` with interval `i`.
+    Index {
+        /// Time interval in seconds.
+        i: u32,
+        /// Time index.
+        t: u32,
+    },
+    /// A calendar time. Sub-second parts are truncated.
+    DateTime(DateTime<Utc>),
+}
+
+impl TimePoint {
+    /// Returns the instant in seconds since the UNIX epoch.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for calendar times before the UNIX epoch.
+    pub fn seconds(&self) -> Result<u64, String> {
+        match *self {
+            TimePoint::Seconds(seconds) => Ok(seconds),
+            TimePoint::Index { i, t } => Ok(i as u64 * t as u64),
+            TimePoint::DateTime(time) => u64::try_from(time.timestamp())
+                .map_err(|_| format!("time {} is before the UNIX epoch", time)),
+        }
+    }
+}
+
+impl From<u64> for TimePoint {
+    fn from(seconds: u64) -> Self {
+        TimePoint::Seconds(seconds)
+    }
+}
+
+impl From<DateTime<Utc>> for TimePoint {
+    fn from(time: DateTime<Utc>) -> Self {
+        TimePoint::DateTime(time)
+    }
+}
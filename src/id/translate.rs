@@ -0,0 +1,120 @@
+use std::f64::consts::PI;
+
+use crate::{
+    id::SpaceTimeId,
+    set::boxes::{Grid, IndexBox},
+};
+
+/// WGS84 の長半径 [m]
+const EQUATORIAL_RADIUS: f64 = 6_378_137.0;
+
+impl SpaceTimeId {
+    /// Moves the ID by `dx`, `dy` and `df` voxels of its own zoom level.
+    ///
+    /// X wraps around the antimeridian: a range that ends up crossing it is split into
+    /// a western and an eastern part, so the result has one or two IDs. The T dimension
+    /// is unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Y or F would leave the valid domain of the zoom level.
+    ///
+    /// # Japanese Note
+    ///
+    /// インデックスで平行移動する。x は経度方向に回り込み、日付変更線をまたぐ範囲は 2 つに分ける
+    pub fn translate(&self, dx: i64, dy: i64, df: i64) -> Result<Vec<SpaceTimeId>, String> {
+        let grid = Grid {
+            z: self.z,
+            i: self.i,
+        };
+        translate_box(grid, &grid.to_box(self), dx, dy, df)?
+            .iter()
+            .map(|b| grid.to_id(b))
+            .collect()
+    }
+
+    /// Moves the ID by a metric offset, `east`, `north` and `up` in meters.
+    ///
+    /// The offset is converted to whole voxels with the local scale at the latitude of
+    /// the center of the ID, and rounded to the nearest voxel. See
+    /// [`translate`](Self::translate) for the handling of X, Y and F.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an offset is not finite, or the errors of
+    /// [`translate`](Self::translate).
+    pub fn translate_meters(
+        &self,
+        east: f64,
+        north: f64,
+        up: f64,
+    ) -> Result<Vec<SpaceTimeId>, String> {
+        let (dx, dy, df) = meters_to_voxels(self.z, self.center().latitude, east, north, up)?;
+        self.translate(dx, dy, df)
+    }
+}
+
+/// 直方体を平行移動する。x は回り込ませ、y と f は範囲外ならエラー
+pub(crate) fn translate_box(
+    grid: Grid,
+    b: &IndexBox,
+    dx: i64,
+    dy: i64,
+    df: i64,
+) -> Result<Vec<IndexBox>, String> {
+    let shift = |(s, e): (i64, i64), d: i64| (s + d, e + d);
+    let moved = IndexBox {
+        f: shift(b.f, df),
+        x: shift(b.x, dx),
+        y: shift(b.y, dy),
+        t: b.t,
+    };
+
+    if moved.y.0 < 0 || moved.y.1 > grid.xy_max() {
+        return Err(format!(
+            "Y range {}..={} is outside 0..={} at zoom level {}",
+            moved.y.0,
+            moved.y.1,
+            grid.xy_max(),
+            grid.z
+        ));
+    }
+    if moved.f.0 < grid.f_min() || moved.f.1 > grid.f_max() {
+        return Err(format!(
+            "F range {}..={} is outside {}..={} at zoom level {}",
+            moved.f.0,
+            moved.f.1,
+            grid.f_min(),
+            grid.f_max(),
+            grid.z
+        ));
+    }
+    Ok(moved.wrap_x(grid.xy_max()))
+}
+
+/// 緯度 latitude での z ズームのボクセルの大きさから、メートルの移動量をボクセル数にする
+pub(crate) fn meters_to_voxels(
+    z: u8,
+    latitude: f64,
+    east: f64,
+    north: f64,
+    up: f64,
+) -> Result<(i64, i64, i64), String> {
+    if !(east.is_finite() && north.is_finite() && up.is_finite()) {
+        return Err(format!(
+            "offset ({}, {}, {}) must be finite",
+            east, north, up
+        ));
+    }
+    let n = (1_u64 << z) as f64;
+    // Web Mercator のタイルは、その緯度では東西と南北の大きさが等しい
+    let width = 2.0 * PI * EQUATORIAL_RADIUS * latitude.to_radians().cos() / n;
+    let height = 2_f64.powi(25) / n;
+
+    // y は南向きに増える
+    Ok((
+        (east / width).round() as i64,
+        (-north / width).round() as i64,
+        (up / height).round() as i64,
+    ))
+}
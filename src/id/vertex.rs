@@ -1,4 +1,7 @@
-use crate::id::{SpaceTimeId, coordinates::Point};
+use crate::{
+    function::tools::{ENU, point_to_enu::point_to_enu},
+    id::{SpaceTimeId, coordinates::Point},
+};
 
 impl SpaceTimeId {
     /// Returns the eight corner vertices of the spatial volume represented by this `SpaceTimeId`.
@@ -58,4 +61,12 @@ impl SpaceTimeId {
             },
         ]
     }
+
+    /// Returns the eight corner vertices of this `SpaceTimeId` in the local East-North-Up
+    /// frame at `origin`, in meters.
+    ///
+    /// The vertices are in the same order as [`vertex`](Self::vertex).
+    pub fn vertex_enu(&self, origin: Point) -> [ENU; 8] {
+        self.vertex().map(|point| point_to_enu(origin, point))
+    }
 }
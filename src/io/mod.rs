@@ -0,0 +1,8 @@
+//! Line-oriented text input and output of space-time IDs.
+//!
+//! [`reader::LineReader`] reads IDs in their `Display` notation one by one from any
+//! [`std::io::BufRead`], plain lines and CSV alike, and [`writer::TextWriter`] writes
+//! them as plain lines or as CSV rows. Both work on streams, so large files never need to fit in memory.
+
+pub mod reader;
+pub mod writer;
@@ -0,0 +1,146 @@
+use std::collections::VecDeque;
+use std::io::BufRead;
+
+use crate::id::SpaceTimeId;
+use crate::set::SpaceTimeIdSet;
+
+/// Reads space-time IDs written in their `Display` notation (`z/f/x/y_i/t`) from text.
+///
+/// The input is read line by line and is tolerant of hand-written files:
+///
+/// - IDs may be separated by commas, and a trailing comma is ignored, so both
+///   `4/10/5/3_0/-,` on each line and several IDs on one line are accepted.
+/// - `#` and `//` start a comment that runs to the end of the line.
+/// - Blank lines and surrounding whitespace are skipped.
+/// - CSV written by [`TextWriter`](crate::io::writer::TextWriter) is accepted: if the
+///   first line that is not blank or a comment has `id` as its first column, it is
+///   taken as a header and only the first column of the following rows is read.
+///
+/// Each ID is yielded as `Ok` or, if it cannot be parsed, as `Err` with its line
+/// number; reading continues with the next ID. An I/O error is yielded once and ends
+/// the iteration. Use [`into_set`](Self::into_set) to also accept X ranges that cross the
+/// antimeridian.
+pub struct LineReader<R: BufRead> {
+    inner: R,
+    line: String,
+    line_number: usize,
+    pending: VecDeque<(usize, String)>,
+    /// 先頭行から決まる形式。None はまだ内容のある行を読んでいない
+    csv: Option<bool>,
+    done: bool,
+}
+
+impl<R: BufRead> LineReader<R> {
+    /// Creates a reader over `inner`.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            line: String::new(),
+            line_number: 0,
+            pending: VecDeque::new(),
+            csv: None,
+            done: false,
+        }
+    }
+
+    /// Reads all remaining IDs into a set, reporting the ones that cannot be read.
+    ///
+    /// Unlike the iterator, an X range with `start > end` is read as a range that crosses
+    /// the antimeridian (see [`SpaceTimeIdSet::parse_wrapping`]). Overlapping IDs are
+    /// merged as by [`SpaceTimeIdSet::insert`]. The errors carry their line number, as
+    /// those of the iterator do.
+    ///
+    /// # Japanese Note
+    ///
+    /// x の s > e も読めるように、集合として読み込む
+    pub fn into_set(mut self) -> (SpaceTimeIdSet, Vec<String>) {
+        let mut set = SpaceTimeIdSet::new();
+        let mut errors = Vec::new();
+        while let Some(token) = self.next_token() {
+            let parsed = token.and_then(|(line_number, token)| {
+                SpaceTimeIdSet::parse_wrapping(&token)
+                    .map_err(|e| format!("line {}: {}", line_number, e))
+            });
+            match parsed {
+                Ok(ids) => ids.iter().for_each(|id| set.insert(*id)),
+                Err(e) => errors.push(e),
+            }
+        }
+        (set, errors)
+    }
+
+    /// 次の ID の候補と行番号。I/O エラーは一度だけ返して終わる
+    fn next_token(&mut self) -> Option<Result<(usize, String), String>> {
+        while self.pending.is_empty() {
+            if self.done {
+                return None;
+            }
+            match self.fill() {
+                Ok(true) => {}
+                Ok(false) => self.done = true,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        self.pending.pop_front().map(Ok)
+    }
+
+    /// 次の行を読み、ID の候補を pending に積む。入力の終わりなら false
+    fn fill(&mut self) -> Result<bool, String> {
+        self.line.clear();
+        let read = self
+            .inner
+            .read_line(&mut self.line)
+            .map_err(|e| format!("line {}: {}", self.line_number + 1, e))?;
+        if read == 0 {
+            return Ok(false);
+        }
+        self.line_number += 1;
+
+        let mut content = self.line.as_str();
+        for marker in ["#", "//"] {
+            if let Some(position) = content.find(marker) {
+                content = &content[..position];
+            }
+        }
+        let mut columns = content.split(',').map(str::trim);
+        match self.csv {
+            // CSV では先頭の id 列だけを読む
+            Some(true) => {
+                if let Some(id) = columns.next().filter(|id| !id.is_empty()) {
+                    self.pending.push_back((self.line_number, id.to_string()));
+                }
+                return Ok(true);
+            }
+            Some(false) => {}
+            None if content.trim().is_empty() => return Ok(true),
+            None => {
+                let header = columns.clone().next() == Some("id");
+                self.csv = Some(header);
+                if header {
+                    return Ok(true);
+                }
+            }
+        }
+        self.pending.extend(
+            columns
+                .filter(|token| !token.is_empty())
+                .map(|token| (self.line_number, token.to_string())),
+        );
+        Ok(true)
+    }
+}
+
+impl<R: BufRead> Iterator for LineReader<R> {
+    type Item = Result<SpaceTimeId, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_token()?.and_then(|(line_number, token)| {
+            token
+                .parse::<SpaceTimeId>()
+                .map_err(|e| format!("line {}: {}", line_number, e))
+        }))
+    }
+}
@@ -0,0 +1,114 @@
+use std::io::{self, Write};
+
+use crate::id::SpaceTimeId;
+
+/// Output format of [`TextWriter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineFormat {
+    /// One ID per line in its `Display` notation.
+    #[default]
+    Lines,
+    /// CSV with a header row and one row per ID. See [`TextWriter`] for the columns.
+    Csv,
+}
+
+/// CSV の列名
+const CSV_HEADER: &str = "id,z,f_min,f_max,x_min,x_max,y_min,y_max,i,t_min,t_max,\
+latitude_min,latitude_max,longitude_min,longitude_max,altitude_min,altitude_max";
+
+/// Writes space-time IDs as text, one ID per line.
+///
+/// With [`LineFormat::Csv`] each row has the columns `id`, `z`, the inclusive index
+/// bounds `f_min` .. `y_max`, `i`, `t_min`, `t_max`, and the geographic bounds
+/// `latitude_min` .. `altitude_max` in degrees and meters. Unlimited ranges are
+/// resolved against the domain of the zoom level. For spatial IDs (`i == 0`) the time
+/// columns are empty, and an open-ended time range leaves `t_max` empty.
+///
+/// Output is written as IDs arrive; wrap unbuffered sinks such as files in a
+/// [`std::io::BufWriter`].
+pub struct TextWriter<W: Write> {
+    inner: W,
+    format: LineFormat,
+}
+
+impl<W: Write> TextWriter<W> {
+    /// Creates a writer and, for CSV, writes the header row.
+    pub fn new(mut inner: W, format: LineFormat) -> io::Result<Self> {
+        if format == LineFormat::Csv {
+            writeln!(inner, "{}", CSV_HEADER)?;
+        }
+        Ok(Self { inner, format })
+    }
+
+    /// Writes one ID.
+    pub fn write(&mut self, id: &SpaceTimeId) -> io::Result<()> {
+        match self.format {
+            LineFormat::Lines => writeln!(self.inner, "{}", id),
+            LineFormat::Csv => self.write_csv(id),
+        }
+    }
+
+    /// Writes every ID of `ids`.
+    pub fn write_all<'a, I>(&mut self, ids: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = &'a SpaceTimeId>,
+    {
+        for id in ids {
+            self.write(id)?;
+        }
+        Ok(())
+    }
+
+    /// Flushes and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_csv(&mut self, id: &SpaceTimeId) -> io::Result<()> {
+        let (f_min, f_max) = id.f_bounds();
+        let (x_min, x_max) = id.x_bounds();
+        let (y_min, y_max) = id.y_bounds();
+        let (t_min, t_max) = match id.i() {
+            0 => (String::new(), String::new()),
+            _ => {
+                let (s, e) = id.t_bounds();
+                let e = if e == u32::MAX {
+                    String::new()
+                } else {
+                    e.to_string()
+                };
+                (s.to_string(), e)
+            }
+        };
+
+        // 緯度は (北端, 南端) の順に返る
+        let c = id.coordinates();
+        let latitude = (
+            c.latitude.0.min(c.latitude.1),
+            c.latitude.0.max(c.latitude.1),
+        );
+
+        writeln!(
+            self.inner,
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            id,
+            id.z(),
+            f_min,
+            f_max,
+            x_min,
+            x_max,
+            y_min,
+            y_max,
+            id.i(),
+            t_min,
+            t_max,
+            latitude.0,
+            latitude.1,
+            c.longitude.0,
+            c.longitude.1,
+            c.altitude.0,
+            c.altitude.1
+        )
+    }
+}
@@ -27,10 +27,19 @@
 //! let complement = !&set_a;
 //! ```
 
+pub mod binary;
 pub mod function;
+pub mod geoid;
+#[cfg(feature = "serde_support")]
+pub mod geojson;
 pub mod id;
+pub mod io;
 pub mod map;
+pub mod mesh;
+pub mod pointcloud;
+pub mod schedule;
 pub mod set;
+pub mod terrain;
 
 #[cfg(test)]
 pub mod tests;
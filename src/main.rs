@@ -9,10 +9,11 @@ use kasane_logic::{
         triangle::triangle,
     },
     id::{DimensionRange, SpaceTimeId, coordinates::Point},
+    io::writer::{LineFormat, TextWriter},
     set::SpaceTimeIdSet,
 };
 use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::BufWriter;
 
 fn main() -> std::io::Result<()> {
     let a = Point {
@@ -37,19 +38,13 @@ fn main() -> std::io::Result<()> {
 
     // ファイルを作成
     let file = File::create("voxels.txt")?;
-    let mut writer = BufWriter::new(file);
+    let mut writer = TextWriter::new(BufWriter::new(file), LineFormat::Lines)?;
 
-    for ele in result.pure() {
-        let line_str = format!("{},\n", ele);
-
-        // コンソール出力
-
-        // ファイル出力
-        writer.write_all(line_str.as_bytes())?;
-    }
+    // ファイル出力
+    writer.write_all(&result.pure())?;
 
     // バッファをフラッシュ
-    writer.flush()?;
+    writer.finish()?;
 
     Ok(())
 }
@@ -1,3 +1,5 @@
+pub mod voxel;
+
 use crate::id::SpaceTimeId;
 use std::collections::hash_map::Iter;
 
@@ -0,0 +1,158 @@
+use crate::id::DimensionRange::{Any, Single};
+use crate::id::SpaceTimeId;
+use crate::map::{Children, Inner, SpaceTimeIdMap};
+
+/// 単一ボクセルの、根から葉までの子の番号 (f, y, x のビット) と、上下どちらの木か
+fn path(id: &SpaceTimeId) -> Result<(bool, Vec<u8>), String> {
+    let (Single(f), Single(x), Single(y), 0) = (id.f(), id.x(), id.y(), id.i()) else {
+        return Err(format!("{} is not a single spatial voxel", id));
+    };
+    // 地表面より下は f = -1, -2, ... を 0, 1, ... として扱う
+    let (up, f) = if f >= 0 {
+        (true, f as u32)
+    } else {
+        (false, !f as u32)
+    };
+    let path = (0..id.z())
+        .rev()
+        .map(|level| (((f >> level) & 1) << 2 | ((y >> level) & 1) << 1 | ((x >> level) & 1)) as u8)
+        .collect();
+    Ok((up, path))
+}
+
+impl<T> Children<T> {
+    /// 子 child が nodes の何番目にあるか
+    fn position(&self, child: u8) -> usize {
+        (self.mask & ((1_u16 << child) - 1) as u8).count_ones() as usize
+    }
+
+    fn get(&self, child: u8) -> Option<&Inner<T>> {
+        (self.mask & (1 << child) != 0).then(|| &*self.nodes[self.position(child)])
+    }
+}
+
+impl<T> SpaceTimeIdMap<T> {
+    /// Inserts `value` for a single spatial voxel and returns the previous value of
+    /// that voxel.
+    ///
+    /// Voxels of different zoom levels may be stored in the same map as long as they
+    /// do not overlap.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `id` is not a single voxel with `i == 0`, or if it overlaps
+    /// a voxel of another zoom level that is already in the map.
+    ///
+    /// # Japanese Note
+    ///
+    /// 八分木をたどって葉に値を置く。z の異なるボクセルとの重なりはエラーにする
+    pub fn insert(&mut self, id: SpaceTimeId, value: T) -> Result<Option<T>, String> {
+        let (up, path) = path(&id)?;
+        let mut node = if up {
+            &mut self.up_inner
+        } else {
+            &mut self.down_inner
+        };
+
+        for &child in &path {
+            let Inner::Children(children) = node else {
+                return Err(format!("{} overlaps a coarser voxel in the map", id));
+            };
+            let position = children.position(child);
+            if children.mask & (1 << child) == 0 {
+                children.mask |= 1 << child;
+                children.nodes.insert(
+                    position,
+                    Box::new(Inner::Children(Children {
+                        mask: 0,
+                        nodes: Vec::new(),
+                    })),
+                );
+            }
+            node = &mut children.nodes[position];
+        }
+
+        match node {
+            Inner::Children(children) if children.mask != 0 => {
+                Err(format!("{} overlaps finer voxels in the map", id))
+            }
+            _ => match std::mem::replace(node, Inner::Value(value)) {
+                Inner::Value(old) => Ok(Some(old)),
+                Inner::Children(_) => Ok(None),
+            },
+        }
+    }
+
+    /// Returns the value stored for exactly this voxel.
+    ///
+    /// Returns `None` if `id` is not a single spatial voxel or has no value of its own.
+    pub fn get(&self, id: &SpaceTimeId) -> Option<&T> {
+        let (up, path) = path(id).ok()?;
+        let mut node = if up { &self.up_inner } else { &self.down_inner };
+        for &child in &path {
+            let Inner::Children(children) = node else {
+                return None;
+            };
+            node = children.get(child)?;
+        }
+        match node {
+            Inner::Value(value) => Some(value),
+            Inner::Children(_) => None,
+        }
+    }
+
+    /// Returns all voxels and their values.
+    ///
+    /// The voxels below the ground (`f < 0`) come first; within each half the order
+    /// follows the octree.
+    pub fn iter(&self) -> impl Iterator<Item = (SpaceTimeId, &T)> {
+        let mut out = Vec::new();
+        collect(&self.down_inner, false, 0, [0; 3], &mut out);
+        collect(&self.up_inner, true, 0, [0; 3], &mut out);
+        out.into_iter()
+    }
+
+    /// Returns the number of voxels with a value.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Returns `true` if the map has no values.
+    pub fn is_empty(&self) -> bool {
+        let empty = |inner: &Inner<T>| matches!(inner, Inner::Children(c) if c.mask == 0);
+        empty(&self.up_inner) && empty(&self.down_inner)
+    }
+}
+
+impl<T: Default + Clone> Default for SpaceTimeIdMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 深さ z のノードの (f, y, x) から ID を組み立てながら葉を集める
+fn collect<'a, T>(
+    node: &'a Inner<T>,
+    up: bool,
+    z: u8,
+    [f, y, x]: [u32; 3],
+    out: &mut Vec<(SpaceTimeId, &'a T)>,
+) {
+    match node {
+        Inner::Value(value) => {
+            let f = if up { f as i32 } else { !(f as i32) };
+            let id = SpaceTimeId::new(z, Single(f), Single(x), Single(y), 0, Any)
+                .expect("octree paths are valid voxels");
+            out.push((id, value));
+        }
+        Inner::Children(children) => {
+            for child in 0..8_u8 {
+                if let Some(next) = children.get(child) {
+                    let bit = |k: u8| ((child >> k) & 1) as u32;
+                    let corner = [2 * f + bit(2), 2 * y + bit(1), 2 * x + bit(0)];
+                    collect(next, up, z + 1, corner, out);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use serde_json::{Value, json};
+
+use crate::mesh::{Frame, Mesh};
+
+/// glTF のバイナリ形式のマジックナンバー ("glTF")
+const MAGIC: u32 = 0x4654_6C67;
+/// チャンクの種類 "JSON"
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+/// チャンクの種類 "BIN\0"
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+/// glTF のコンポーネント型
+const FLOAT: u32 = 5126;
+const UNSIGNED_BYTE: u32 = 5121;
+const UNSIGNED_INT: u32 = 5125;
+
+/// bufferView の target
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+impl Mesh {
+    /// Writes the mesh as a binary glTF 2.0 bundle (`.glb`).
+    ///
+    /// glTF stores positions as 32-bit floats, which is not precise enough for ECEF
+    /// coordinates. The positions are therefore written relative to the center of their
+    /// bounding box, and the center is stored as the translation of the node.
+    /// ENU meshes are rotated so that up is `+Y`, as glTF expects.
+    pub fn to_glb(&self) -> Vec<u8> {
+        let mut min = [f64::MAX; 3];
+        let mut max = [f64::MIN; 3];
+        for p in &self.positions {
+            for k in 0..3 {
+                min[k] = min[k].min(p[k]);
+                max[k] = max[k].max(p[k]);
+            }
+        }
+        let center: [f64; 3] = if self.positions.is_empty() {
+            [0.0; 3]
+        } else {
+            std::array::from_fn(|k| (min[k] + max[k]) / 2.0)
+        };
+
+        let mut bin: Vec<u8> = Vec::new();
+        let mut local_min = [f32::MAX; 3];
+        let mut local_max = [f32::MIN; 3];
+        for p in &self.positions {
+            for k in 0..3 {
+                let v = (p[k] - center[k]) as f32;
+                local_min[k] = local_min[k].min(v);
+                local_max[k] = local_max[k].max(v);
+                bin.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        let positions_length = bin.len();
+
+        let colors_offset = bin.len();
+        if let Some(colors) = &self.colors {
+            for rgba in colors {
+                bin.extend_from_slice(rgba);
+            }
+        }
+        let colors_length = bin.len() - colors_offset;
+
+        let indices_offset = bin.len();
+        for triangle in &self.triangles {
+            for index in triangle {
+                bin.extend_from_slice(&index.to_le_bytes());
+            }
+        }
+        let indices_length = bin.len() - indices_offset;
+
+        let mut buffer_views = vec![json!({
+            "buffer": 0, "byteOffset": 0, "byteLength": positions_length, "target": ARRAY_BUFFER,
+        })];
+        let mut accessors = vec![json!({
+            "bufferView": 0,
+            "componentType": FLOAT,
+            "count": self.positions.len(),
+            "type": "VEC3",
+            "min": if self.positions.is_empty() { json!([0.0, 0.0, 0.0]) } else { json!(local_min) },
+            "max": if self.positions.is_empty() { json!([0.0, 0.0, 0.0]) } else { json!(local_max) },
+        })];
+        let mut attributes = serde_json::Map::new();
+        attributes.insert("POSITION".into(), json!(0));
+
+        if self.colors.is_some() {
+            buffer_views.push(json!({
+                "buffer": 0, "byteOffset": colors_offset, "byteLength": colors_length, "target": ARRAY_BUFFER,
+            }));
+            accessors.push(json!({
+                "bufferView": buffer_views.len() - 1,
+                "componentType": UNSIGNED_BYTE,
+                "normalized": true,
+                "count": self.positions.len(),
+                "type": "VEC4",
+            }));
+            attributes.insert("COLOR_0".into(), json!(accessors.len() - 1));
+        }
+
+        buffer_views.push(json!({
+            "buffer": 0, "byteOffset": indices_offset, "byteLength": indices_length,
+            "target": ELEMENT_ARRAY_BUFFER,
+        }));
+        accessors.push(json!({
+            "bufferView": buffer_views.len() - 1,
+            "componentType": UNSIGNED_INT,
+            "count": self.triangles.len() * 3,
+            "type": "SCALAR",
+        }));
+
+        let mut node = json!({ "mesh": 0, "translation": center });
+        if let Frame::Enu { .. } = self.frame {
+            // 東・北・上 (Z が上) を glTF の Y が上の座標系に合わせる
+            let half = std::f64::consts::FRAC_1_SQRT_2;
+            node["rotation"] = json!([-half, 0.0, 0.0, half]);
+        }
+
+        let document: Value = json!({
+            "asset": { "version": "2.0", "generator": "kasane-logic" },
+            "scene": 0,
+            "scenes": [{ "nodes": [0] }],
+            "nodes": [node],
+            "meshes": [{
+                "primitives": [{
+                    "attributes": attributes,
+                    "indices": accessors.len() - 1,
+                    "mode": 4,
+                }],
+            }],
+            "buffers": [{ "byteLength": bin.len() }],
+            "bufferViews": buffer_views,
+            "accessors": accessors,
+        });
+
+        let mut json_chunk = document.to_string().into_bytes();
+        while !json_chunk.len().is_multiple_of(4) {
+            json_chunk.push(b' ');
+        }
+        while !bin.len().is_multiple_of(4) {
+            bin.push(0);
+        }
+
+        let total = 12 + 8 + json_chunk.len() + 8 + bin.len();
+        let mut out = Vec::with_capacity(total);
+        out.extend_from_slice(&MAGIC.to_le_bytes());
+        out.extend_from_slice(&2_u32.to_le_bytes());
+        out.extend_from_slice(&(total as u32).to_le_bytes());
+        out.extend_from_slice(&(json_chunk.len() as u32).to_le_bytes());
+        out.extend_from_slice(&CHUNK_JSON.to_le_bytes());
+        out.extend_from_slice(&json_chunk);
+        out.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        out.extend_from_slice(&CHUNK_BIN.to_le_bytes());
+        out.extend_from_slice(&bin);
+        out
+    }
+}
@@ -0,0 +1,416 @@
+//! Triangle meshes of `SpaceTimeIdSet` for 3D viewers.
+//!
+//! A set is turned into a [`Mesh`] with [`SpaceTimeIdSet::to_mesh`] and then written as
+//! Wavefront OBJ, PLY or binary glTF (`.glb`).
+
+pub mod glb;
+pub mod obj;
+pub mod ply;
+
+use std::collections::HashMap;
+
+use crate::{
+    function::tools::{ecef_to_enu::ecef_to_enu, point_to_ecef::point_to_ecef},
+    id::{SpaceTimeId, coordinates::Point},
+    map::SpaceTimeIdMap,
+    set::{
+        SpaceTimeIdSet,
+        boxes::{Grid, IndexBox, disjoint_union, subtract_all},
+    },
+};
+
+/// 1 つの面を 1 辺あたり最大いくつに分割するか
+const MAX_SEGMENTS: i64 = 64;
+
+/// Coordinate frame of the mesh vertices.
+#[derive(Debug, Clone, Copy)]
+pub enum Frame {
+    /// Earth-centered, Earth-fixed coordinates in meters.
+    Ecef,
+    /// Local East-North-Up coordinates in meters, relative to `origin`.
+    Enu {
+        /// Origin of the local frame.
+        origin: Point,
+    },
+}
+
+/// Triangle mesh with optional per-vertex colors.
+#[derive(Debug, Clone)]
+pub struct Mesh {
+    /// Frame of [`positions`](Self::positions).
+    pub frame: Frame,
+    /// Vertex positions in meters.
+    pub positions: Vec<[f64; 3]>,
+    /// RGBA color of each vertex, aligned with `positions`, if colors were requested.
+    pub colors: Option<Vec<[u8; 4]>>,
+    /// Triangles as indices into `positions`, counterclockwise when seen from outside.
+    pub triangles: Vec<[u32; 3]>,
+}
+
+impl SpaceTimeIdSet {
+    /// Builds a triangle mesh of the surface of the set.
+    ///
+    /// All IDs are rescaled to the finest zoom level of the set. Faces shared by two
+    /// adjacent IDs are culled, so only the outer surface (including the walls of
+    /// inner cavities) is emitted. Each face is split along the voxel grid, up to
+    /// 64 segments per edge, so that the curvature of large range IDs is followed.
+    /// X wraps around the antimeridian.
+    ///
+    /// Temporal IDs are drawn with their spatial extent; a face is culled only if it is
+    /// hidden for the whole time range of its ID. Slice the set in time first to
+    /// render a single moment.
+    ///
+    /// # Japanese Note
+    ///
+    /// 集合の表面を三角形メッシュに変換する。隣接する ID 同士の内部の面は取り除く
+    pub fn to_mesh(&self, frame: Frame) -> Mesh {
+        self.build_mesh(frame, None::<fn(&SpaceTimeId) -> [u8; 4]>)
+    }
+
+    /// Same as [`to_mesh`](Self::to_mesh), with every vertex colored by `color`
+    /// applied to the ID that owns its face.
+    pub fn to_mesh_with_colors<C>(&self, frame: Frame, color: C) -> Mesh
+    where
+        C: Fn(&SpaceTimeId) -> [u8; 4],
+    {
+        self.build_mesh(frame, Some(color))
+    }
+
+    /// Same as [`to_mesh`](Self::to_mesh), with every vertex colored from the value of
+    /// its ID in `values`.
+    ///
+    /// `color` receives the value stored in `values` for the ID that owns the face, or
+    /// `None` if the map has no value for exactly that ID. Values are looked up with
+    /// [`SpaceTimeIdMap::get`], so only single spatial voxels can be found in the map.
+    ///
+    /// # Japanese Note
+    ///
+    /// ID ごとの色を、その ID に対応するマップの値から決める
+    pub fn to_mesh_with_map<T, C>(&self, frame: Frame, values: &SpaceTimeIdMap<T>, color: C) -> Mesh
+    where
+        C: Fn(Option<&T>) -> [u8; 4],
+    {
+        self.build_mesh(frame, Some(|id: &SpaceTimeId| color(values.get(id))))
+    }
+
+    fn build_mesh<C>(&self, frame: Frame, color: Option<C>) -> Mesh
+    where
+        C: Fn(&SpaceTimeId) -> [u8; 4],
+    {
+        let mut builder = MeshBuilder::new(frame, color.is_some());
+        let Some((grid, boxes)) = self.to_boxes() else {
+            return builder.finish();
+        };
+        let neighbors = Neighbors::new(&boxes);
+
+        for (owner, (id, b)) in self.iter().zip(&boxes).enumerate() {
+            let rgba = color.as_ref().map(|c| c(id));
+            for face in Face::ALL {
+                for rect in visible_parts(grid, &boxes, &neighbors, b, face) {
+                    builder.add_face(grid, owner, face, &rect, rgba);
+                }
+            }
+        }
+        builder.finish()
+    }
+}
+
+/// 直方体の 6 つの面
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Face {
+    West,
+    East,
+    North,
+    South,
+    Bottom,
+    Top,
+}
+
+impl Face {
+    const ALL: [Face; 6] = [
+        Face::West,
+        Face::East,
+        Face::North,
+        Face::South,
+        Face::Bottom,
+        Face::Top,
+    ];
+
+    /// 面の法線の次元 (IndexBox::dim の番号)
+    fn dim(self) -> usize {
+        match self {
+            Face::Bottom | Face::Top => 0,
+            Face::West | Face::East => 1,
+            Face::North | Face::South => 2,
+        }
+    }
+
+    /// index が増える側の面かどうか
+    fn is_upper(self) -> bool {
+        matches!(self, Face::East | Face::South | Face::Top)
+    }
+}
+
+/// 面のうち、隣接する直方体に覆われていない部分
+///
+/// 面のすぐ外側の 1 層を直方体として作り、そこから他の直方体を取り除いた残りが見える部分になる。
+fn visible_parts(
+    grid: Grid,
+    boxes: &[IndexBox],
+    neighbors: &Neighbors,
+    b: &IndexBox,
+    face: Face,
+) -> Vec<IndexBox> {
+    let dim = face.dim();
+    let (s, e) = b.dim(dim);
+    let outside = if face.is_upper() { e + 1 } else { s - 1 };
+
+    let mut layer = *b;
+    *layer.dim_mut(dim) = match dim {
+        // x は経度方向に循環する
+        1 => {
+            let x = outside.rem_euclid(grid.xy_max() + 1);
+            (x, x)
+        }
+        _ => (outside, outside),
+    };
+
+    let neighbors: Vec<IndexBox> = neighbors
+        .touching(dim, face.is_upper(), &layer)
+        .into_iter()
+        .map(|k| boxes[k])
+        .filter(|other| other.intersects(&layer))
+        .collect();
+    if neighbors.is_empty() {
+        *layer.dim_mut(dim) = (outside, outside);
+        return vec![IndexBox { t: (0, 0), ..layer }];
+    }
+
+    // 時間方向に分かれた残りは、空間上の同じ面として 1 つにまとめる
+    let pieces = subtract_all(vec![layer], &neighbors)
+        .into_iter()
+        .map(|mut piece| {
+            *piece.dim_mut(dim) = (outside, outside);
+            IndexBox { t: (0, 0), ..piece }
+        });
+    disjoint_union(pieces)
+}
+
+/// 面に接する直方体を引くための索引
+///
+/// 集合の ID は互いに素なので、面のすぐ外側の層に重なる直方体は、その層の位置から始まる
+/// (下側の面なら終わる) ものに限られる。(次元, 始まりか終わりか, 位置) ごとに、残り 2 次元の
+/// 始まりの順に並べておき、面の範囲にかかり得るものだけを二分探索で取り出す
+struct Neighbors {
+    planes: HashMap<(usize, bool, i64), Plane>,
+}
+
+/// 1 つの平面に接する直方体
+#[derive(Default)]
+struct Plane {
+    /// (u の始まり, v の始まり, 直方体の番号) の昇順
+    entries: Vec<(i64, i64, usize)>,
+    /// u, v 方向の長さ - 1 の最大値
+    reach: (i64, i64),
+}
+
+impl Neighbors {
+    fn new(boxes: &[IndexBox]) -> Self {
+        let mut planes: HashMap<(usize, bool, i64), Plane> = HashMap::new();
+        for (k, b) in boxes.iter().enumerate() {
+            for dim in 0..3 {
+                let (u, v) = Self::plane_dims(dim);
+                let (bu, bv) = (b.dim(u), b.dim(v));
+                let (s, e) = b.dim(dim);
+                for key in [(dim, true, s), (dim, false, e)] {
+                    let plane = planes.entry(key).or_default();
+                    plane.entries.push((bu.0, bv.0, k));
+                    plane.reach.0 = plane.reach.0.max(bu.1 - bu.0);
+                    plane.reach.1 = plane.reach.1.max(bv.1 - bv.0);
+                }
+            }
+        }
+        for plane in planes.values_mut() {
+            plane.entries.sort_unstable();
+        }
+        Self { planes }
+    }
+
+    /// dim に垂直な平面上の 2 つの次元
+    fn plane_dims(dim: usize) -> (usize, usize) {
+        match dim {
+            0 => (1, 2),
+            1 => (0, 2),
+            _ => (0, 1),
+        }
+    }
+
+    /// 上側の面なら layer の位置から始まり、下側の面なら終わる直方体のうち、layer にかかり得るもの
+    fn touching(&self, dim: usize, upper: bool, layer: &IndexBox) -> Vec<usize> {
+        let Some(plane) = self.planes.get(&(dim, upper, layer.dim(dim).0)) else {
+            return Vec::new();
+        };
+        let (u, v) = Self::plane_dims(dim);
+        let (lu, lv) = (layer.dim(u), layer.dim(v));
+        let entries = &plane.entries;
+
+        let mut result = Vec::new();
+        let mut k = entries.partition_point(|e| e.0 < lu.0 - plane.reach.0);
+        let end = entries.partition_point(|e| e.0 <= lu.1);
+        while k < end {
+            let group = entries[k].0;
+            let group_end = k + entries[k..end].partition_point(|e| e.0 == group);
+            let rows = &entries[k..group_end];
+            let lo = rows.partition_point(|e| e.1 < lv.0 - plane.reach.1);
+            let hi = rows.partition_point(|e| e.1 <= lv.1);
+            result.extend(rows[lo..hi].iter().map(|e| e.2));
+            k = group_end;
+        }
+        result
+    }
+}
+
+struct MeshBuilder {
+    frame: Frame,
+    enu_origin: Option<Point>,
+    positions: Vec<[f64; 3]>,
+    colors: Option<Vec<[u8; 4]>>,
+    triangles: Vec<[u32; 3]>,
+    vertices: HashMap<(usize, [i64; 3]), u32>,
+}
+
+impl MeshBuilder {
+    fn new(frame: Frame, colored: bool) -> Self {
+        Self {
+            frame,
+            enu_origin: match frame {
+                Frame::Ecef => None,
+                Frame::Enu { origin } => Some(origin),
+            },
+            positions: Vec::new(),
+            colors: colored.then(Vec::new),
+            triangles: Vec::new(),
+            vertices: HashMap::new(),
+        }
+    }
+
+    fn finish(self) -> Mesh {
+        Mesh {
+            frame: self.frame,
+            positions: self.positions,
+            colors: self.colors,
+            triangles: self.triangles,
+        }
+    }
+
+    /// index 空間の頂点 (f, x, y) を登録する。頂点は ID ごとに共有する
+    fn vertex(&mut self, grid: Grid, owner: usize, corner: [i64; 3], rgba: Option<[u8; 4]>) -> u32 {
+        if let Some(&index) = self.vertices.get(&(owner, corner)) {
+            return index;
+        }
+
+        let n = 1_u64 << grid.z;
+        let [f, x, y] = corner;
+        let point = Point {
+            latitude: SpaceTimeId::latitude(y as u32, n as u32),
+            longitude: SpaceTimeId::longitude(x as u32, n as u32),
+            altitude: f as f64 * 2_f64.powi(25 - grid.z as i32),
+        };
+        let ecef = point_to_ecef(point);
+        let position = match self.enu_origin {
+            None => [ecef.x, ecef.y, ecef.z],
+            Some(origin) => {
+                let enu = ecef_to_enu(origin, ecef);
+                [enu.east, enu.north, enu.up]
+            }
+        };
+
+        let index = self.positions.len() as u32;
+        self.positions.push(position);
+        if let (Some(colors), Some(rgba)) = (&mut self.colors, rgba) {
+            colors.push(rgba);
+        }
+        self.vertices.insert((owner, corner), index);
+        index
+    }
+
+    fn add_face(
+        &mut self,
+        grid: Grid,
+        owner: usize,
+        face: Face,
+        rect: &IndexBox,
+        rgba: Option<[u8; 4]>,
+    ) {
+        let dim = face.dim();
+        // 面の位置は、直方体の外側の 1 層の手前の境界
+        let plane = if face.is_upper() {
+            rect.dim(dim).0
+        } else {
+            rect.dim(dim).0 + 1
+        };
+        let (u_dim, v_dim) = match dim {
+            0 => (1, 2),
+            1 => (2, 0),
+            _ => (0, 1),
+        };
+
+        let u_breaks = segments(rect.dim(u_dim));
+        let v_breaks = segments(rect.dim(v_dim));
+
+        let corner = |u: i64, v: i64| {
+            let mut c = [0_i64; 3];
+            c[dim] = plane;
+            c[u_dim] = u;
+            c[v_dim] = v;
+            c
+        };
+
+        // 北を上にした (東, 北, 上) で u × v が外向きになるかどうか
+        let flip = orientation(dim, u_dim, v_dim) != face.is_upper();
+
+        for u in u_breaks.windows(2) {
+            for v in v_breaks.windows(2) {
+                let quad = [
+                    self.vertex(grid, owner, corner(u[0], v[0]), rgba),
+                    self.vertex(grid, owner, corner(u[1], v[0]), rgba),
+                    self.vertex(grid, owner, corner(u[1], v[1]), rgba),
+                    self.vertex(grid, owner, corner(u[0], v[1]), rgba),
+                ];
+                if flip {
+                    self.triangles.push([quad[0], quad[2], quad[1]]);
+                    self.triangles.push([quad[0], quad[3], quad[2]]);
+                } else {
+                    self.triangles.push([quad[0], quad[1], quad[2]]);
+                    self.triangles.push([quad[0], quad[2], quad[3]]);
+                }
+            }
+        }
+    }
+}
+
+/// 閉区間 (s, e) のセルの境界を最大 MAX_SEGMENTS 個の区間に分ける
+fn segments((s, e): (i64, i64)) -> Vec<i64> {
+    let cells = e - s + 1;
+    let count = cells.min(MAX_SEGMENTS);
+    (0..=count).map(|k| s + cells * k / count).collect()
+}
+
+/// index の次元 (f, x, y) を (上, 東, 北) に対応させたとき、u × v が法線の正の向きになるか
+fn orientation(dim: usize, u_dim: usize, v_dim: usize) -> bool {
+    // f → 上, x → 東, y → 南 (北の逆)
+    let axis = |d: usize| -> [f64; 3] {
+        match d {
+            0 => [0.0, 0.0, 1.0],
+            1 => [1.0, 0.0, 0.0],
+            _ => [0.0, -1.0, 0.0],
+        }
+    };
+    let (u, v, n) = (axis(u_dim), axis(v_dim), axis(dim));
+    let cross = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    cross[0] * n[0] + cross[1] * n[1] + cross[2] * n[2] > 0.0
+}
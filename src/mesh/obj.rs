@@ -0,0 +1,40 @@
+use std::fmt::Write;
+
+use crate::mesh::Mesh;
+
+impl Mesh {
+    /// Writes the mesh as Wavefront OBJ text.
+    ///
+    /// Vertex colors, if any, are written after the position as `v x y z r g b`
+    /// with components in `0.0..=1.0`, which most viewers understand. Alpha is dropped.
+    pub fn to_obj(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "# kasane-logic mesh").unwrap();
+
+        for (k, p) in self.positions.iter().enumerate() {
+            match &self.colors {
+                Some(colors) => {
+                    let [r, g, b, _] = colors[k];
+                    writeln!(
+                        out,
+                        "v {} {} {} {} {} {}",
+                        p[0],
+                        p[1],
+                        p[2],
+                        r as f64 / 255.0,
+                        g as f64 / 255.0,
+                        b as f64 / 255.0
+                    )
+                    .unwrap();
+                }
+                None => writeln!(out, "v {} {} {}", p[0], p[1], p[2]).unwrap(),
+            }
+        }
+
+        // OBJ の頂点番号は 1 始まり
+        for [a, b, c] in &self.triangles {
+            writeln!(out, "f {} {} {}", a + 1, b + 1, c + 1).unwrap();
+        }
+        out
+    }
+}
@@ -0,0 +1,41 @@
+use std::fmt::Write;
+
+use crate::mesh::Mesh;
+
+impl Mesh {
+    /// Writes the mesh as ASCII PLY text.
+    ///
+    /// Positions are written as `double` properties `x`, `y`, `z`, followed by
+    /// `red`, `green`, `blue` and `alpha` as `uchar` if the mesh has colors.
+    pub fn to_ply(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "ply").unwrap();
+        writeln!(out, "format ascii 1.0").unwrap();
+        writeln!(out, "comment kasane-logic mesh").unwrap();
+        writeln!(out, "element vertex {}", self.positions.len()).unwrap();
+        for axis in ["x", "y", "z"] {
+            writeln!(out, "property double {}", axis).unwrap();
+        }
+        if self.colors.is_some() {
+            for channel in ["red", "green", "blue", "alpha"] {
+                writeln!(out, "property uchar {}", channel).unwrap();
+            }
+        }
+        writeln!(out, "element face {}", self.triangles.len()).unwrap();
+        writeln!(out, "property list uchar uint vertex_indices").unwrap();
+        writeln!(out, "end_header").unwrap();
+
+        for (k, p) in self.positions.iter().enumerate() {
+            write!(out, "{} {} {}", p[0], p[1], p[2]).unwrap();
+            if let Some(colors) = &self.colors {
+                let [r, g, b, a] = colors[k];
+                write!(out, " {} {} {} {}", r, g, b, a).unwrap();
+            }
+            writeln!(out).unwrap();
+        }
+        for [a, b, c] in &self.triangles {
+            writeln!(out, "3 {} {} {}", a, b, c).unwrap();
+        }
+        out
+    }
+}
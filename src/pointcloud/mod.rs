@@ -0,0 +1,156 @@
+//! Point cloud ingestion into occupancy sets and maps.
+//!
+//! Points are read from ASCII XYZ files with [`xyz::XyzReader`] or from PLY files with
+//! [`ply::PlyReader`]. Both yield `Result<Point, String>` one point at a time, so large
+//! scans never need to fit in memory. [`voxel_counts`] then counts the points in each
+//! voxel of a zoom level, and [`occupied_voxels`] keeps the voxels with enough points
+//! as a [`SpaceTimeIdSet`]. Points that cannot be read or converted are skipped and
+//! reported instead of aborting the scan.
+
+pub mod ply;
+pub mod xyz;
+
+use std::collections::HashMap;
+
+use crate::{
+    function::tools::{ECEF, ecef_to_point::ecef_to_point, point_to_id::point_to_id},
+    id::{SpaceTimeId, coordinates::Point},
+    map::SpaceTimeIdMap,
+    set::SpaceTimeIdSet,
+};
+
+/// Meaning of the three coordinate columns of a point file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PointLayout {
+    /// Latitude and longitude in degrees, then ellipsoidal height in meters.
+    #[default]
+    LatLonAlt,
+    /// Longitude and latitude in degrees, then ellipsoidal height in meters.
+    LonLatAlt,
+    /// Earth-centered, Earth-fixed X, Y and Z in meters.
+    Ecef,
+}
+
+impl PointLayout {
+    pub(crate) fn point(self, [a, b, c]: [f64; 3]) -> Point {
+        match self {
+            PointLayout::LatLonAlt => Point {
+                latitude: a,
+                longitude: b,
+                altitude: c,
+            },
+            PointLayout::LonLatAlt => Point {
+                latitude: b,
+                longitude: a,
+                altitude: c,
+            },
+            PointLayout::Ecef => ecef_to_point(ECEF { x: a, y: b, z: c }),
+        }
+    }
+}
+
+/// A point that could not be counted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointError {
+    /// Position of the point in the input, counting the points that failed to read.
+    pub index: usize,
+    /// Why the point was skipped.
+    pub message: String,
+}
+
+/// Result of [`voxel_counts`].
+#[derive(Debug)]
+pub struct CountReport {
+    /// Number of points in each kept voxel.
+    pub counts: SpaceTimeIdMap<u32>,
+    /// Number of points that were counted, including those in dropped voxels.
+    pub counted: usize,
+    /// Points that were skipped, in input order.
+    pub errors: Vec<PointError>,
+}
+
+/// Result of [`occupied_voxels`].
+#[derive(Debug, Clone)]
+pub struct OccupancyReport {
+    /// The voxels with enough points.
+    pub set: SpaceTimeIdSet,
+    /// Number of points that were counted, including those in dropped voxels.
+    pub counted: usize,
+    /// Points that were skipped, in input order.
+    pub errors: Vec<PointError>,
+}
+
+/// Counts the points in each voxel of zoom level `z` and keeps the voxels with at
+/// least `min_points` points.
+///
+/// A `min_points` of 0 or 1 keeps every voxel that has a point.
+///
+/// # Errors
+///
+/// Returns an error if `z` is invalid. Points that cannot be read or converted to a
+/// voxel (see [`point_to_id`]) do not stop the scan; they are skipped and listed in
+/// [`CountReport::errors`].
+///
+/// # Japanese Note
+///
+/// ボクセルごとの点数を数え、min_points 未満のボクセルはノイズとして捨てる。読めない点は飛ばして一覧にする
+pub fn voxel_counts<I>(z: u8, points: I, min_points: u32) -> Result<CountReport, String>
+where
+    I: IntoIterator<Item = Result<Point, String>>,
+{
+    let (counts, counted, errors) = count(z, points, min_points)?;
+    let mut map = SpaceTimeIdMap::new();
+    for (id, n) in counts {
+        map.insert(id, n)?;
+    }
+    Ok(CountReport {
+        counts: map,
+        counted,
+        errors,
+    })
+}
+
+/// Returns the voxels of zoom level `z` that contain at least `min_points` points.
+///
+/// # Errors
+///
+/// Same as [`voxel_counts`]; skipped points are listed in [`OccupancyReport::errors`].
+pub fn occupied_voxels<I>(z: u8, points: I, min_points: u32) -> Result<OccupancyReport, String>
+where
+    I: IntoIterator<Item = Result<Point, String>>,
+{
+    let (counts, counted, errors) = count(z, points, min_points)?;
+    Ok(OccupancyReport {
+        // 同じ z の単一ボクセルなので互いに素
+        set: SpaceTimeIdSet::from_disjoint(counts.into_keys().collect()),
+        counted,
+        errors,
+    })
+}
+
+/// ボクセルごとの点数, 数えた点の数, 飛ばした点
+type Counts = (HashMap<SpaceTimeId, u32>, usize, Vec<PointError>);
+
+fn count<I>(z: u8, points: I, min_points: u32) -> Result<Counts, String>
+where
+    I: IntoIterator<Item = Result<Point, String>>,
+{
+    if z >= 32 {
+        return Err(format!("Zoom level z must be 0..=31. Got {}", z));
+    }
+    let mut counts = HashMap::new();
+    let mut counted = 0;
+    let mut errors = Vec::new();
+    for (index, point) in points.into_iter().enumerate() {
+        let id = point.and_then(|point| point_to_id(z, point));
+        match id {
+            Ok(id) => {
+                *counts.entry(id).or_insert(0_u32) += 1;
+                counted += 1;
+            }
+            Err(message) => errors.push(PointError { index, message }),
+        }
+    }
+    counts.retain(|_, n| *n >= min_points);
+    Ok((counts, counted, errors))
+}
@@ -0,0 +1,281 @@
+use std::io::{BufRead, Read};
+
+use crate::{id::coordinates::Point, pointcloud::PointLayout};
+
+/// PLY の本体の形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+/// PLY のスカラー型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Result<Self, String> {
+        Ok(match name {
+            "char" | "int8" => Scalar::I8,
+            "uchar" | "uint8" => Scalar::U8,
+            "short" | "int16" => Scalar::I16,
+            "ushort" | "uint16" => Scalar::U16,
+            "int" | "int32" => Scalar::I32,
+            "uint" | "uint32" => Scalar::U32,
+            "float" | "float32" => Scalar::F32,
+            "double" | "float64" => Scalar::F64,
+            _ => return Err(format!("unknown PLY type '{}'", name)),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+
+    fn decode(self, bytes: &[u8], format: Format) -> f64 {
+        macro_rules! read {
+            ($t:ty) => {{
+                let raw = bytes[..std::mem::size_of::<$t>()].try_into().unwrap();
+                if format == Format::BinaryBigEndian {
+                    <$t>::from_be_bytes(raw) as f64
+                } else {
+                    <$t>::from_le_bytes(raw) as f64
+                }
+            }};
+        }
+        match self {
+            Scalar::I8 => read!(i8),
+            Scalar::U8 => read!(u8),
+            Scalar::I16 => read!(i16),
+            Scalar::U16 => read!(u16),
+            Scalar::I32 => read!(i32),
+            Scalar::U32 => read!(u32),
+            Scalar::F32 => read!(f32),
+            Scalar::F64 => read!(f64),
+        }
+    }
+}
+
+/// ヘッダーの要素 (vertex など)
+struct Element {
+    name: String,
+    count: usize,
+    /// スカラーの property (名前, 型)。list を含む要素は None
+    properties: Option<Vec<(String, Scalar)>>,
+}
+
+/// Reads the vertices of a PLY file as points.
+///
+/// ASCII, binary little-endian and binary big-endian files are supported. The `x`,
+/// `y` and `z` properties of the `vertex` element are the three coordinates, read
+/// according to the [`PointLayout`]; other properties are ignored. Elements after the
+/// vertices, such as faces, are not read.
+///
+/// The header is read by [`PlyReader::new`]. Vertices are then yielded one by one; a
+/// vertex that cannot be read is yielded as `Err` and ends the iteration.
+pub struct PlyReader<R: BufRead> {
+    inner: R,
+    layout: PointLayout,
+    format: Format,
+    /// x, y, z の位置 (ASCII では列番号、バイナリではバイト位置) と型
+    xyz: [(usize, Scalar); 3],
+    record_size: usize,
+    remaining: usize,
+    line: String,
+    buffer: Vec<u8>,
+}
+
+impl<R: BufRead> PlyReader<R> {
+    /// Reads the header of a PLY file and creates a reader over its vertices.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the header is malformed, if the format is not supported, if
+    /// the `vertex` element has no `x`, `y` or `z` property or has list properties, or
+    /// if an element before the vertices cannot be skipped (a binary element with list
+    /// properties).
+    pub fn new(mut inner: R, layout: PointLayout) -> Result<Self, String> {
+        let mut line = String::new();
+        let mut next_line = |line: &mut String| -> Result<(), String> {
+            line.clear();
+            let read = inner.read_line(line).map_err(|e| e.to_string())?;
+            if read == 0 {
+                return Err("unexpected end of PLY header".into());
+            }
+            Ok(())
+        };
+
+        next_line(&mut line)?;
+        if line.trim_end() != "ply" {
+            return Err("not a PLY file".into());
+        }
+
+        let mut format = None;
+        let mut elements: Vec<Element> = Vec::new();
+        loop {
+            next_line(&mut line)?;
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match tokens.as_slice() {
+                ["end_header"] => break,
+                [] | ["comment", ..] | ["obj_info", ..] => {}
+                ["format", name, "1.0"] => {
+                    format = Some(match *name {
+                        "ascii" => Format::Ascii,
+                        "binary_little_endian" => Format::BinaryLittleEndian,
+                        "binary_big_endian" => Format::BinaryBigEndian,
+                        _ => return Err(format!("unsupported PLY format '{}'", name)),
+                    });
+                }
+                ["element", name, count] => elements.push(Element {
+                    name: name.to_string(),
+                    count: count
+                        .parse()
+                        .map_err(|_| format!("invalid element count '{}'", count))?,
+                    properties: Some(Vec::new()),
+                }),
+                ["property", "list", ..] => {
+                    let element = elements.last_mut().ok_or("property before any element")?;
+                    element.properties = None;
+                }
+                ["property", kind, name] => {
+                    let element = elements.last_mut().ok_or("property before any element")?;
+                    let scalar = Scalar::parse(kind)?;
+                    if let Some(properties) = &mut element.properties {
+                        properties.push((name.to_string(), scalar));
+                    }
+                }
+                _ => return Err(format!("invalid PLY header line '{}'", line.trim_end())),
+            }
+        }
+        let format = format.ok_or("PLY header has no format line")?;
+
+        let vertex_index = elements
+            .iter()
+            .position(|element| element.name == "vertex")
+            .ok_or("PLY file has no vertex element")?;
+        let vertex = &elements[vertex_index];
+        let properties = vertex
+            .properties
+            .as_ref()
+            .ok_or("PLY vertex element has list properties")?;
+
+        let scalars: Vec<Scalar> = properties.iter().map(|&(_, scalar)| scalar).collect();
+        let mut xyz = [(0, Scalar::F64); 3];
+        for (slot, axis) in xyz.iter_mut().zip(["x", "y", "z"]) {
+            let index = properties
+                .iter()
+                .position(|(name, _)| name == axis)
+                .ok_or_else(|| format!("PLY vertex element has no '{}' property", axis))?;
+            let position = match format {
+                Format::Ascii => index,
+                _ => scalars[..index].iter().map(|s| s.size()).sum(),
+            };
+            *slot = (position, scalars[index]);
+        }
+
+        let mut reader = Self {
+            inner,
+            layout,
+            format,
+            record_size: scalars.iter().map(|s| s.size()).sum(),
+            xyz,
+            remaining: vertex.count,
+            line: String::new(),
+            buffer: Vec::new(),
+        };
+
+        // vertex より前の要素を読み飛ばす
+        for element in &elements[..vertex_index] {
+            reader.skip_element(element)?;
+        }
+        Ok(reader)
+    }
+
+    fn skip_element(&mut self, element: &Element) -> Result<(), String> {
+        if self.format == Format::Ascii {
+            for _ in 0..element.count {
+                self.line.clear();
+                self.inner
+                    .read_line(&mut self.line)
+                    .map_err(|e| e.to_string())?;
+            }
+            return Ok(());
+        }
+        let properties = element.properties.as_ref().ok_or_else(|| {
+            format!(
+                "cannot skip binary PLY element '{}' with list properties",
+                element.name
+            )
+        })?;
+        let size: usize = properties.iter().map(|(_, s)| s.size()).sum();
+        std::io::copy(
+            &mut self.inner.by_ref().take((size * element.count) as u64),
+            &mut std::io::sink(),
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn read_vertex(&mut self) -> Result<Point, String> {
+        let mut values = [0.0; 3];
+        if self.format == Format::Ascii {
+            self.line.clear();
+            let read = self
+                .inner
+                .read_line(&mut self.line)
+                .map_err(|e| e.to_string())?;
+            if read == 0 {
+                return Err("unexpected end of PLY vertices".into());
+            }
+            let tokens: Vec<&str> = self.line.split_whitespace().collect();
+            for (value, &(index, _)) in values.iter_mut().zip(&self.xyz) {
+                let token = tokens
+                    .get(index)
+                    .ok_or_else(|| format!("PLY vertex '{}' is too short", self.line.trim()))?;
+                *value = token
+                    .parse()
+                    .map_err(|_| format!("invalid number '{}' in PLY vertex", token))?;
+            }
+        } else {
+            self.buffer.resize(self.record_size, 0);
+            self.inner
+                .read_exact(&mut self.buffer)
+                .map_err(|e| format!("unexpected end of PLY vertices: {}", e))?;
+            for (value, &(offset, scalar)) in values.iter_mut().zip(&self.xyz) {
+                *value = scalar.decode(&self.buffer[offset..], self.format);
+            }
+        }
+        Ok(self.layout.point(values))
+    }
+}
+
+impl<R: BufRead> Iterator for PlyReader<R> {
+    type Item = Result<Point, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let point = self.read_vertex();
+        if point.is_err() {
+            self.remaining = 0;
+        }
+        Some(point)
+    }
+}
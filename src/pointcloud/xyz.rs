@@ -0,0 +1,88 @@
+use std::io::BufRead;
+
+use crate::{id::coordinates::Point, pointcloud::PointLayout};
+
+/// Reads points from an ASCII XYZ file.
+///
+/// Each line holds one point. The first three numbers are the coordinates, read
+/// according to the [`PointLayout`]; further columns such as intensity or color are
+/// ignored. Columns may be separated by whitespace, commas or semicolons.
+///
+/// Blank lines and lines starting with `#` or `//` are skipped, as is a header line
+/// before the first point whose first column is not a number (such as `x,y,z`).
+///
+/// A line that cannot be read is yielded as `Err` with its line number and reading
+/// continues with the next line. An I/O error is yielded once and ends the iteration.
+pub struct XyzReader<R: BufRead> {
+    inner: R,
+    layout: PointLayout,
+    line: String,
+    line_number: usize,
+    seen_point: bool,
+    done: bool,
+}
+
+impl<R: BufRead> XyzReader<R> {
+    /// Creates a reader over `inner`.
+    pub fn new(inner: R, layout: PointLayout) -> Self {
+        Self {
+            inner,
+            layout,
+            line: String::new(),
+            line_number: 0,
+            seen_point: false,
+            done: false,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for XyzReader<R> {
+    type Item = Result<Point, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            self.line.clear();
+            match self.inner.read_line(&mut self.line) {
+                Ok(0) => self.done = true,
+                Ok(_) => {
+                    self.line_number += 1;
+                    let content = self.line.trim();
+                    if content.is_empty() || content.starts_with('#') || content.starts_with("//") {
+                        continue;
+                    }
+
+                    let mut columns = content
+                        .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+                        .filter(|token| !token.is_empty());
+                    let mut values = [0.0; 3];
+                    let mut parsed = 0;
+                    for (value, token) in values.iter_mut().zip(columns.by_ref()) {
+                        match token.parse::<f64>() {
+                            Ok(v) => *value = v,
+                            Err(_) => break,
+                        }
+                        parsed += 1;
+                    }
+
+                    if parsed == 0 && !self.seen_point {
+                        // 最初の点より前の見出し行
+                        continue;
+                    }
+                    self.seen_point = true;
+                    if parsed < 3 {
+                        return Some(Err(format!(
+                            "line {}: expected three numeric columns in '{}'",
+                            self.line_number, content
+                        )));
+                    }
+                    return Some(Ok(self.layout.point(values)));
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(format!("line {}: {}", self.line_number + 1, e)));
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,279 @@
+//! Recurring time schedules expanded into space-time ID sets.
+//!
+//! A [`Schedule`] describes when something recurs, such as "weekdays 09:00–17:00 JST"
+//! or "every hour for 10 minutes". It can be built directly or parsed from a subset
+//! of iCalendar RRULE with [`Schedule::from_rrule`], and is expanded over a bounded
+//! horizon with [`Schedule::to_set`]. The result is combined with a spatial set through
+//! [`SpaceTimeIdSet::product`].
+
+pub mod rrule;
+
+use chrono::{
+    DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Timelike, Utc,
+    Weekday,
+};
+
+use crate::{
+    id::{
+        DimensionRange::{Any, LimitRange},
+        SpaceTimeId,
+    },
+    set::SpaceTimeIdSet,
+};
+
+/// 時間帯 [start, end)
+type Window = (DateTime<Utc>, DateTime<Utc>);
+
+/// How often a [`Schedule`] recurs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    /// Every `interval` hours.
+    Hourly,
+    /// Every `interval` days.
+    Daily,
+    /// Every `interval` weeks (weeks start on Monday).
+    Weekly,
+}
+
+/// A recurring time window.
+///
+/// Each occurrence starts at a time generated from `start` and lasts `duration`.
+/// The rules follow RRULE:
+///
+/// - `Hourly`: every `interval` hours from `start`. `by_day` and `by_hour` keep only
+///   occurrences on those local weekdays and hours.
+/// - `Daily`: every `interval` days from the date of `start`, at the local hours of
+///   `by_hour` (or the hour of `start`). `by_day` keeps only those weekdays.
+/// - `Weekly`: every `interval` weeks from the week of `start`, on the weekdays of
+///   `by_day` (or the weekday of `start`) and the local hours of `by_hour` (or the hour
+///   of `start`).
+///
+/// Minutes and seconds always come from `start`. Weekdays and hours are evaluated in
+/// the offset of `start`. Occurrences before `start` or after `until` are dropped.
+///
+/// # Japanese Note
+///
+/// 繰り返す時間帯。RRULE の一部 (FREQ, INTERVAL, BYDAY, BYHOUR, UNTIL) に対応する
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    /// Recurrence frequency.
+    pub frequency: Frequency,
+    /// Number of frequency units between recurrences. Must be at least 1.
+    pub interval: u32,
+    /// Local weekdays of the occurrences. Empty means no restriction.
+    pub by_day: Vec<Weekday>,
+    /// Local hours (0 to 23) of the occurrences. Empty means no restriction.
+    pub by_hour: Vec<u32>,
+    /// First occurrence; its offset is the time zone of the schedule.
+    pub start: DateTime<FixedOffset>,
+    /// Length of each occurrence.
+    pub duration: TimeDelta,
+    /// Last instant an occurrence may start at (inclusive).
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl Schedule {
+    /// Creates a schedule recurring every frequency unit from `start`, with no
+    /// `by_day`, `by_hour` or `until` restriction.
+    pub fn new(frequency: Frequency, start: DateTime<FixedOffset>, duration: TimeDelta) -> Self {
+        Self {
+            frequency,
+            interval: 1,
+            by_day: Vec::new(),
+            by_hour: Vec::new(),
+            start,
+            duration,
+            until: None,
+        }
+    }
+
+    /// Expands the schedule over the horizon `[from, to)` into merged time windows.
+    ///
+    /// Overlapping or touching occurrences are merged, and windows are clipped to the
+    /// horizon. The windows are sorted and disjoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the schedule is invalid (`interval == 0`, a non-positive
+    /// `duration`, an hour above 23) or the horizon is empty.
+    pub fn windows(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<Window>, String> {
+        self.validate()?;
+        if to <= from {
+            return Err(format!("horizon {}..{} is empty", from, to));
+        }
+
+        let mut windows: Vec<Window> = self
+            .occurrences(from - self.duration, to)
+            .into_iter()
+            .map(|start| (start, start + self.duration))
+            .filter(|&(start, end)| end > from && start < to)
+            .map(|(start, end)| (start.max(from), end.min(to)))
+            .collect();
+        windows.sort();
+
+        let mut merged: Vec<Window> = Vec::with_capacity(windows.len());
+        for (start, end) in windows {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Expands the schedule over the horizon `[from, to)` into a set of temporal IDs.
+    ///
+    /// Each merged window of [`windows`](Self::windows) becomes one ID covering the
+    /// whole space (`z = 0`, F, X and Y `Any`). All IDs share the coarsest interval `i`
+    /// that aligns with every window boundary, counted from the UNIX epoch, so the set
+    /// is as small as possible and can be combined with a spatial set by
+    /// [`SpaceTimeIdSet::product`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`windows`](Self::windows), and an error if a window
+    /// starts before the UNIX epoch or does not fit in the `u32` time index.
+    ///
+    /// # Japanese Note
+    ///
+    /// 期間内の時間帯を、すべての境界に揃う最大の i で表した時間 ID の集合にする
+    pub fn to_set(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<SpaceTimeIdSet, String> {
+        let windows = self.windows(from, to)?;
+
+        let mut bounds = Vec::with_capacity(windows.len());
+        let mut i: u64 = 0;
+        for &(start, end) in &windows {
+            let seconds = |time: DateTime<Utc>| {
+                u64::try_from(time.timestamp())
+                    .map_err(|_| format!("time {} is before the UNIX epoch", time))
+            };
+            let (s, e) = (seconds(start)?, seconds(end)?);
+            i = gcd(gcd(i, s), e);
+            bounds.push((s, e));
+        }
+        if bounds.is_empty() {
+            return Ok(SpaceTimeIdSet::new());
+        }
+        let i = u32::try_from(i).map_err(|_| format!("time interval {} is out of u32 range", i))?;
+
+        let index = |v: u64| {
+            u32::try_from(v / i as u64).map_err(|_| format!("time index {} is out of u32 range", v))
+        };
+        let ids = bounds
+            .into_iter()
+            .map(|(s, e)| {
+                SpaceTimeId::new(0, Any, Any, Any, i, LimitRange(index(s)?, index(e)? - 1))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(SpaceTimeIdSet::from_disjoint(ids))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.interval == 0 {
+            return Err("INTERVAL must be at least 1".into());
+        }
+        if self.duration <= TimeDelta::zero() {
+            return Err(format!("duration {} must be positive", self.duration));
+        }
+        if let Some(hour) = self.by_hour.iter().find(|&&hour| hour > 23) {
+            return Err(format!("BYHOUR value {} is out of 0..=23", hour));
+        }
+        Ok(())
+    }
+
+    /// [from, to) の間に始まる発生時刻
+    fn occurrences(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let offset = *self.start.offset();
+        let local = |time: DateTime<Utc>| time.with_timezone(&offset).naive_local();
+        let first = local(self.start.to_utc());
+        let (local_from, local_to) = (local(from), local(to));
+
+        let mut out = Vec::new();
+        let mut push = |naive: NaiveDateTime| {
+            let Some(time) = offset.from_local_datetime(&naive).single() else {
+                return;
+            };
+            let time = time.to_utc();
+            let in_range = time >= self.start && time >= from && time < to;
+            if in_range && self.until.is_none_or(|until| time <= until) {
+                out.push(time);
+            }
+        };
+
+        match self.frequency {
+            Frequency::Hourly => {
+                let step = TimeDelta::hours(self.interval as i64);
+                let skip = (local_from - first).num_hours().max(0) / self.interval as i64;
+                let mut time = first + step * skip as i32;
+                while time < local_to {
+                    if self.day_matches(time.date()) && self.hour_matches(time.hour()) {
+                        push(time);
+                    }
+                    time += step;
+                }
+            }
+            Frequency::Daily => {
+                let step = self.interval as i64;
+                let skip = (local_from.date() - first.date()).num_days().max(0) / step;
+                let mut day = first.date() + TimeDelta::days(skip * step);
+                while day <= local_to.date() {
+                    if self.day_matches(day) {
+                        self.at_hours(day, first, &mut push);
+                    }
+                    day += TimeDelta::days(step);
+                }
+            }
+            Frequency::Weekly => {
+                let step = 7 * self.interval as i64;
+                let monday =
+                    first.date() - TimeDelta::days(first.weekday().num_days_from_monday() as i64);
+                let skip = (local_from.date() - monday).num_days().max(0) / step;
+                let mut week = monday + TimeDelta::days(skip * step);
+                while week <= local_to.date() {
+                    for offset_days in 0..7 {
+                        let day = week + TimeDelta::days(offset_days);
+                        let matches = if self.by_day.is_empty() {
+                            day.weekday() == first.weekday()
+                        } else {
+                            self.by_day.contains(&day.weekday())
+                        };
+                        if matches {
+                            self.at_hours(day, first, &mut push);
+                        }
+                    }
+                    week += TimeDelta::days(step);
+                }
+            }
+        }
+        out
+    }
+
+    /// day の BYHOUR (なければ開始時刻の時) の各時刻を push する
+    fn at_hours(&self, day: NaiveDate, first: NaiveDateTime, push: &mut impl FnMut(NaiveDateTime)) {
+        let hours = if self.by_hour.is_empty() {
+            vec![first.hour()]
+        } else {
+            self.by_hour.clone()
+        };
+        for hour in hours {
+            if let Some(time) = day.and_hms_opt(hour, first.minute(), first.second()) {
+                push(time);
+            }
+        }
+    }
+
+    fn day_matches(&self, day: NaiveDate) -> bool {
+        self.by_day.is_empty() || self.by_day.contains(&day.weekday())
+    }
+
+    fn hour_matches(&self, hour: u32) -> bool {
+        self.by_hour.is_empty() || self.by_hour.contains(&hour)
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
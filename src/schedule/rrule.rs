@@ -0,0 +1,108 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Utc, Weekday};
+
+use crate::schedule::{Frequency, Schedule};
+
+impl Schedule {
+    /// Parses a subset of an iCalendar RRULE (RFC 5545), such as
+    /// `FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR;UNTIL=20251231T150000Z`.
+    ///
+    /// Supported parts are `FREQ` (`HOURLY`, `DAILY`, `WEEKLY`), `INTERVAL`, `BYDAY`
+    /// (two-letter weekdays), `BYHOUR` and `UNTIL`. `UNTIL` may be a UTC date-time
+    /// (`20251231T150000Z`), a local date-time in the offset of `start`
+    /// (`20251231T235959`) or a date (`20251231`, up to the end of that local day).
+    /// A leading `RRULE:` is accepted. `start` plays the role of DTSTART and `duration`
+    /// is the length of each occurrence.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `FREQ` is missing, if a part is unknown or repeated, or if a
+    /// value cannot be parsed.
+    ///
+    /// # Japanese Note
+    ///
+    /// RRULE の文字列から Schedule を作る
+    pub fn from_rrule(
+        rule: &str,
+        start: DateTime<FixedOffset>,
+        duration: TimeDelta,
+    ) -> Result<Schedule, String> {
+        let rule = rule.trim();
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+
+        let mut frequency = None;
+        let mut schedule = Schedule::new(Frequency::Daily, start, duration);
+        let mut seen: Vec<&str> = Vec::new();
+
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("RRULE part '{}' has no value", part))?;
+            if seen.contains(&name) {
+                return Err(format!("RRULE part {} is repeated", name));
+            }
+            seen.push(name);
+
+            match name {
+                "FREQ" => {
+                    frequency = Some(match value {
+                        "HOURLY" => Frequency::Hourly,
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        _ => return Err(format!("unsupported FREQ {}", value)),
+                    })
+                }
+                "INTERVAL" => {
+                    schedule.interval = value
+                        .parse()
+                        .map_err(|_| format!("invalid INTERVAL {}", value))?;
+                }
+                "BYDAY" => {
+                    schedule.by_day = value.split(',').map(weekday).collect::<Result<_, _>>()?;
+                }
+                "BYHOUR" => {
+                    schedule.by_hour = value
+                        .split(',')
+                        .map(|hour| hour.parse().map_err(|_| format!("invalid BYHOUR {}", hour)))
+                        .collect::<Result<_, _>>()?;
+                }
+                "UNTIL" => schedule.until = Some(until(value, *start.offset())?),
+                _ => return Err(format!("unsupported RRULE part {}", name)),
+            }
+        }
+
+        schedule.frequency = frequency.ok_or("RRULE has no FREQ")?;
+        schedule.validate()?;
+        Ok(schedule)
+    }
+}
+
+fn weekday(value: &str) -> Result<Weekday, String> {
+    Ok(match value {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return Err(format!("invalid BYDAY {}", value)),
+    })
+}
+
+fn until(value: &str, offset: FixedOffset) -> Result<DateTime<Utc>, String> {
+    let invalid = || format!("invalid UNTIL {}", value);
+    let local = if let Some(utc) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+        return Ok(Utc.from_utc_datetime(&time));
+    } else if value.contains('T') {
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(|_| invalid())?
+    } else {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|_| invalid())?;
+        date.and_hms_opt(23, 59, 59).ok_or_else(invalid)?
+    };
+    offset
+        .from_local_datetime(&local)
+        .single()
+        .map(|time| time.to_utc())
+        .ok_or_else(invalid)
+}
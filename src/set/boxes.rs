@@ -0,0 +1,303 @@
+//! Index-space boxes used to build exact, disjoint sets directly.
+//!
+//! Every `SpaceTimeId` is a box in (F, X, Y, T) index space. Once all IDs of a set are
+//! rescaled to a common zoom level and time interval, unions and differences reduce to
+//! splitting axis-aligned boxes, which is what the helpers in this module do.
+
+use std::collections::HashMap;
+
+use crate::{
+    id::{
+        DimensionRange::{AfterUnLimitRange, Any, LimitRange},
+        SpaceTimeId,
+        z_range::{F_MAX, F_MIN, XY_MAX},
+    },
+    set::SpaceTimeIdSet,
+};
+
+/// 時間軸の上限がないことを表す番兵値
+pub(crate) const T_UNBOUNDED: i64 = i64::MAX / 4;
+
+/// 共通の z と i に揃えた index 空間上の閉区間の直方体
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct IndexBox {
+    pub(crate) f: (i64, i64),
+    pub(crate) x: (i64, i64),
+    pub(crate) y: (i64, i64),
+    pub(crate) t: (i64, i64),
+}
+
+/// 集合の要素を揃えるためのズームレベルと時間間隔
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Grid {
+    pub(crate) z: u8,
+    pub(crate) i: u32,
+}
+
+impl Grid {
+    /// 最も細かい z と、0 でない i の最大公約数を選ぶ
+    pub(crate) fn of<'a, I>(ids: I) -> Option<Grid>
+    where
+        I: IntoIterator<Item = &'a SpaceTimeId>,
+    {
+        let mut grid: Option<Grid> = None;
+        for id in ids {
+            let current = grid.get_or_insert(Grid { z: id.z(), i: 0 });
+            current.z = current.z.max(id.z());
+            if id.i() != 0 {
+                current.i = SpaceTimeId::gcd(current.i, id.i());
+            }
+        }
+        grid
+    }
+
+    pub(crate) fn xy_max(&self) -> i64 {
+        XY_MAX[self.z as usize] as i64
+    }
+
+    pub(crate) fn f_min(&self) -> i64 {
+        F_MIN[self.z as usize] as i64
+    }
+
+    pub(crate) fn f_max(&self) -> i64 {
+        F_MAX[self.z as usize] as i64
+    }
+
+    /// `SpaceTimeId` をこのグリッド上の直方体に変換する
+    pub(crate) fn to_box(self, id: &SpaceTimeId) -> IndexBox {
+        let k = 1_i64 << (self.z - id.z());
+        let scale = |(s, e): (i64, i64)| (s * k, (e + 1) * k - 1);
+
+        let (f0, f1) = id.f_bounds();
+        let (x0, x1) = id.x_bounds();
+        let (y0, y1) = id.y_bounds();
+
+        let t = if id.i() == 0 || self.i == 0 {
+            (0, T_UNBOUNDED)
+        } else {
+            let k = (id.i() / self.i) as i64;
+            let (t0, t1) = id.t_bounds();
+            if t1 == u32::MAX {
+                (t0 as i64 * k, T_UNBOUNDED)
+            } else {
+                (t0 as i64 * k, (t1 as i64 + 1) * k - 1)
+            }
+        };
+
+        IndexBox {
+            f: scale((f0 as i64, f1 as i64)),
+            x: scale((x0 as i64, x1 as i64)),
+            y: scale((y0 as i64, y1 as i64)),
+            t,
+        }
+    }
+
+    /// このグリッド上の直方体を `SpaceTimeId` に戻す
+    pub(crate) fn to_id(self, b: &IndexBox) -> Result<SpaceTimeId, String> {
+        let t = if self.i == 0 {
+            Any
+        } else if b.t.1 >= T_UNBOUNDED {
+            AfterUnLimitRange(
+                u32::try_from(b.t.0).map_err(|_| format!("t {} is out of u32 range", b.t.0))?,
+            )
+        } else {
+            let s = u32::try_from(b.t.0).map_err(|_| format!("t {} is out of u32 range", b.t.0))?;
+            let e = u32::try_from(b.t.1).map_err(|_| format!("t {} is out of u32 range", b.t.1))?;
+            LimitRange(s, e)
+        };
+
+        let xy = |(s, e): (i64, i64)| -> Result<_, String> {
+            Ok(LimitRange(
+                u32::try_from(s).map_err(|_| format!("XY value {} is out of range", s))?,
+                u32::try_from(e).map_err(|_| format!("XY value {} is out of range", e))?,
+            ))
+        };
+        let f = LimitRange(
+            i32::try_from(b.f.0).map_err(|_| format!("F value {} is out of range", b.f.0))?,
+            i32::try_from(b.f.1).map_err(|_| format!("F value {} is out of range", b.f.1))?,
+        );
+
+        SpaceTimeId::new(self.z, f, xy(b.x)?, xy(b.y)?, self.i, t)
+    }
+}
+
+impl IndexBox {
+    pub(crate) fn intersects(&self, other: &IndexBox) -> bool {
+        let overlap = |a: (i64, i64), b: (i64, i64)| a.0 <= b.1 && b.0 <= a.1;
+        overlap(self.f, other.f)
+            && overlap(self.x, other.x)
+            && overlap(self.y, other.y)
+            && overlap(self.t, other.t)
+    }
+
+    /// self から other を取り除いた残りを、互いに重ならない直方体で返す
+    pub(crate) fn subtract(&self, other: &IndexBox) -> Vec<IndexBox> {
+        if !self.intersects(other) {
+            return vec![*self];
+        }
+
+        let mut result = Vec::new();
+        let mut rest = *self;
+
+        for dim in 0..4 {
+            let (s, e) = rest.dim(dim);
+            let (os, oe) = other.dim(dim);
+            if s < os {
+                let mut piece = rest;
+                *piece.dim_mut(dim) = (s, os - 1);
+                result.push(piece);
+            }
+            if oe < e {
+                let mut piece = rest;
+                *piece.dim_mut(dim) = (oe + 1, e);
+                result.push(piece);
+            }
+            *rest.dim_mut(dim) = (s.max(os), e.min(oe));
+        }
+
+        result
+    }
+
+    /// x が 0..=xy_max の外にはみ出している場合に、経度方向へ回り込ませて分割する
+    pub(crate) fn wrap_x(&self, xy_max: i64) -> Vec<IndexBox> {
+        let n = xy_max + 1;
+        let (s, e) = self.x;
+        if e - s + 1 >= n {
+            return vec![IndexBox {
+                x: (0, xy_max),
+                ..*self
+            }];
+        }
+
+        let s = s.rem_euclid(n);
+        let e = e.rem_euclid(n);
+        if s <= e {
+            vec![IndexBox { x: (s, e), ..*self }]
+        } else {
+            vec![
+                IndexBox {
+                    x: (s, xy_max),
+                    ..*self
+                },
+                IndexBox { x: (0, e), ..*self },
+            ]
+        }
+    }
+
+    pub(crate) fn dim(&self, dim: usize) -> (i64, i64) {
+        match dim {
+            0 => self.f,
+            1 => self.x,
+            2 => self.y,
+            _ => self.t,
+        }
+    }
+
+    pub(crate) fn dim_mut(&mut self, dim: usize) -> &mut (i64, i64) {
+        match dim {
+            0 => &mut self.f,
+            1 => &mut self.x,
+            2 => &mut self.y,
+            _ => &mut self.t,
+        }
+    }
+}
+
+/// 重なりを含む直方体の列を、同じ領域を表す互いに素な直方体の列に変換する
+pub(crate) fn disjoint_union<I>(boxes: I) -> Vec<IndexBox>
+where
+    I: IntoIterator<Item = IndexBox>,
+{
+    let mut result: Vec<IndexBox> = Vec::new();
+    for b in boxes {
+        let mut pieces = vec![b];
+        for existing in &result {
+            pieces = pieces
+                .into_iter()
+                .flat_map(|piece| piece.subtract(existing))
+                .collect();
+            if pieces.is_empty() {
+                break;
+            }
+        }
+        result.extend(pieces);
+    }
+    result
+}
+
+/// a の各直方体から b のすべての直方体を取り除く
+pub(crate) fn subtract_all(a: Vec<IndexBox>, b: &[IndexBox]) -> Vec<IndexBox> {
+    let mut result = a;
+    for other in b {
+        result = result
+            .into_iter()
+            .flat_map(|piece| piece.subtract(other))
+            .collect();
+    }
+    result
+}
+
+/// 1 次元を除いた残り 3 次元の区間
+type OtherDims = [(i64, i64); 3];
+
+/// 3 次元が一致し残り 1 次元で隣接する直方体を繋げて数を減らす
+pub(crate) fn coalesce(mut boxes: Vec<IndexBox>) -> Vec<IndexBox> {
+    loop {
+        let before = boxes.len();
+        for dim in 0..4 {
+            let mut groups: HashMap<OtherDims, Vec<(i64, i64)>> = HashMap::new();
+            for b in &boxes {
+                let mut key = [(0, 0); 3];
+                for (k, other) in (0..4).filter(|&d| d != dim).enumerate() {
+                    key[k] = b.dim(other);
+                }
+                groups.entry(key).or_default().push(b.dim(dim));
+            }
+
+            boxes.clear();
+            for (key, mut ranges) in groups {
+                ranges.sort_unstable();
+                let mut merged: Vec<(i64, i64)> = Vec::new();
+                for range in ranges {
+                    match merged.last_mut() {
+                        Some(last) if last.1 + 1 == range.0 => last.1 = range.1,
+                        _ => merged.push(range),
+                    }
+                }
+                for range in merged {
+                    let mut b = IndexBox {
+                        f: range,
+                        x: range,
+                        y: range,
+                        t: range,
+                    };
+                    for (k, other) in (0..4).filter(|&d| d != dim).enumerate() {
+                        *b.dim_mut(other) = key[k];
+                    }
+                    boxes.push(b);
+                }
+            }
+        }
+        if boxes.len() == before {
+            return boxes;
+        }
+    }
+}
+
+impl SpaceTimeIdSet {
+    /// 集合の要素を共通のグリッド上の直方体として取り出す
+    pub(crate) fn to_boxes(&self) -> Option<(Grid, Vec<IndexBox>)> {
+        let grid = Grid::of(&self.inner)?;
+        let boxes = self.inner.iter().map(|id| grid.to_box(id)).collect();
+        Some((grid, boxes))
+    }
+
+    /// 互いに素な直方体の列から集合を作る
+    pub(crate) fn from_boxes(grid: Grid, boxes: Vec<IndexBox>) -> Result<Self, String> {
+        let inner = coalesce(boxes)
+            .iter()
+            .map(|b| grid.to_id(b))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { inner })
+    }
+}
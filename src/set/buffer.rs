@@ -0,0 +1,184 @@
+use std::f64::consts::PI;
+
+use crate::{
+    id::SpaceTimeId,
+    set::{
+        SpaceTimeIdSet,
+        boxes::{Grid, IndexBox, T_UNBOUNDED, disjoint_union, subtract_all},
+    },
+};
+
+/// WGS84 の長半径 [m]
+const EQUATORIAL_RADIUS: f64 = 6_378_137.0;
+
+/// 膨張・収縮の量をボクセル数ではなく物理量で表したもの
+#[derive(Debug, Clone, Copy)]
+struct Margin {
+    horizontal: f64,
+    vertical: f64,
+    seconds: u32,
+}
+
+impl SpaceTimeIdSet {
+    /// Grows the region covered by the set by a metric distance (dilation).
+    ///
+    /// Every ID is extended by `meters_horizontal` in the X and Y directions,
+    /// by `meters_vertical` in the F direction and by `seconds` along the time axis.
+    /// The result is the union of all grown IDs, returned as a disjoint set.
+    ///
+    /// The structuring element is a box, so corners grow by the same amount as faces.
+    /// The operation works on range IDs directly: each ID is treated as one box in
+    /// index space and is never expanded into single voxels.
+    ///
+    /// # Notes
+    ///
+    /// Under Web Mercator the ground size of a tile shrinks with `cos(latitude)`.
+    /// The number of tiles added horizontally is therefore computed per ID from
+    /// the latitude of the grown ID's edge closest to a pole, so the margin is never
+    /// smaller than requested, even in the grown part poleward of the ID. X wraps
+    /// around the antimeridian; Y and F are clamped to the valid domain of the zoom
+    /// level. Spatial IDs (`i == 0`) are valid for all time and are not changed by
+    /// `seconds`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a distance is negative or not finite, or if the grown
+    /// time range no longer fits into `u32`.
+    pub fn buffer(
+        &self,
+        meters_horizontal: f64,
+        meters_vertical: f64,
+        seconds: u32,
+    ) -> Result<SpaceTimeIdSet, String> {
+        let margin = Margin::new(meters_horizontal, meters_vertical, seconds)?;
+        let Some((grid, boxes)) = self.to_boxes() else {
+            return Ok(SpaceTimeIdSet::new());
+        };
+
+        let grown = disjoint_union(Self::dilate_boxes(grid, &boxes, margin));
+        Self::from_boxes(grid, grown)
+    }
+
+    /// Shrinks the region covered by the set by a metric distance (erosion).
+    ///
+    /// A voxel remains in the result only if every voxel within `meters_horizontal`,
+    /// `meters_vertical` and `seconds` of it also belongs to the set. This is the dual
+    /// of [`buffer`](Self::buffer) and uses the same box-shaped, latitude-aware margin.
+    ///
+    /// The erosion is computed on range IDs as `A - buffer(buffer(A) - A)`: only the
+    /// thin shell around the set is dilated, never its complement in the whole space.
+    /// The edges of the valid domain (the poles and the top and bottom of F) are not
+    /// treated as empty space, so regions touching them are not eroded from that side.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`buffer`](Self::buffer).
+    pub fn erode(
+        &self,
+        meters_horizontal: f64,
+        meters_vertical: f64,
+        seconds: u32,
+    ) -> Result<SpaceTimeIdSet, String> {
+        let margin = Margin::new(meters_horizontal, meters_vertical, seconds)?;
+        let Some((grid, boxes)) = self.to_boxes() else {
+            return Ok(SpaceTimeIdSet::new());
+        };
+
+        let grown = disjoint_union(Self::dilate_boxes(grid, &boxes, margin));
+        let shell = subtract_all(grown, &boxes);
+        let shell_grown = Self::dilate_boxes(grid, &shell, margin);
+        let eroded = subtract_all(boxes, &shell_grown);
+
+        Self::from_boxes(grid, eroded)
+    }
+
+    /// 各直方体を margin だけ膨らませる (結果は重なりを含む)
+    fn dilate_boxes(grid: Grid, boxes: &[IndexBox], margin: Margin) -> Vec<IndexBox> {
+        let xy_max = grid.xy_max();
+
+        boxes
+            .iter()
+            .flat_map(|b| {
+                let dxy = Self::horizontal_cells(grid.z, b.y, margin.horizontal);
+                let df = Self::vertical_cells(grid.z, margin.vertical);
+                let dt = if grid.i == 0 {
+                    0
+                } else {
+                    (margin.seconds as i64 + grid.i as i64 - 1) / grid.i as i64
+                };
+
+                let grown = IndexBox {
+                    f: (
+                        (b.f.0 - df).max(grid.f_min()),
+                        (b.f.1 + df).min(grid.f_max()),
+                    ),
+                    x: (b.x.0 - dxy, b.x.1 + dxy),
+                    y: ((b.y.0 - dxy).max(0), (b.y.1 + dxy).min(xy_max)),
+                    t: (
+                        (b.t.0 - dt).max(0),
+                        if b.t.1 >= T_UNBOUNDED {
+                            T_UNBOUNDED
+                        } else {
+                            b.t.1 + dt
+                        },
+                    ),
+                };
+                grown.wrap_x(xy_max)
+            })
+            .collect()
+    }
+
+    /// 水平方向の距離をタイル数に変換する
+    ///
+    /// 広げた部分は元の直方体より極側にあってタイルが細いので、広げた後の極側の端の緯度で数え直し、
+    /// 数が増えなくなるまで繰り返す
+    fn horizontal_cells(z: u8, y: (i64, i64), meters: f64) -> i64 {
+        if meters == 0.0 {
+            return 0;
+        }
+        let n = 1_u64 << z;
+        let mut cells = 0;
+        loop {
+            let north = SpaceTimeId::latitude((y.0 - cells).max(0) as u32, n as u32);
+            let south = SpaceTimeId::latitude((y.1 + 1 + cells).min(n as i64) as u32, n as u32);
+            let latitude = north.abs().max(south.abs());
+
+            let tile = 2.0 * PI * EQUATORIAL_RADIUS * latitude.to_radians().cos() / n as f64;
+            let next = (meters / tile).ceil();
+            if !next.is_finite() || next >= n as f64 {
+                return n as i64;
+            }
+            if next as i64 <= cells {
+                return cells;
+            }
+            cells = next as i64;
+        }
+    }
+
+    /// 鉛直方向の距離を f の数に変換する
+    fn vertical_cells(z: u8, meters: f64) -> i64 {
+        let height = 2_f64.powi(25 - z as i32);
+        ((meters / height).ceil() as i64).min(1 << (z as i64 + 1))
+    }
+}
+
+impl Margin {
+    fn new(horizontal: f64, vertical: f64, seconds: u32) -> Result<Self, String> {
+        for (name, value) in [
+            ("meters_horizontal", horizontal),
+            ("meters_vertical", vertical),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(format!(
+                    "{} must be a finite, non-negative distance. Got {}",
+                    name, value
+                ));
+            }
+        }
+        Ok(Self {
+            horizontal,
+            vertical,
+            seconds,
+        })
+    }
+}
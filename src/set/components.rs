@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use crate::set::{
+    SpaceTimeIdSet,
+    boxes::{Grid, IndexBox},
+};
+
+/// Spatial neighborhood used to decide whether two voxels are connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// Voxels sharing a face (6 neighbors).
+    Six,
+    /// Voxels sharing a face or an edge (18 neighbors).
+    Eighteen,
+    /// Voxels sharing a face, an edge or a corner (26 neighbors).
+    TwentySix,
+}
+
+/// Connectivity rule used by [`SpaceTimeIdSet::connected_components`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Connectivity {
+    /// Which spatial neighbors are considered connected.
+    pub neighborhood: Neighborhood,
+    /// If `true`, IDs whose time ranges are consecutive (touching without overlapping)
+    /// are also connected, as long as they are spatial neighbors. If `false`,
+    /// connected IDs must share at least one time index.
+    pub temporal: bool,
+}
+
+impl Connectivity {
+    /// Creates a purely spatial connectivity rule.
+    pub fn spatial(neighborhood: Neighborhood) -> Self {
+        Self {
+            neighborhood,
+            temporal: false,
+        }
+    }
+}
+
+/// 2 つの区間の位置関係
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Contact {
+    Overlap,
+    Touch,
+    Apart,
+}
+
+impl SpaceTimeIdSet {
+    /// Splits the set into connected components.
+    ///
+    /// Two IDs belong to the same component if they are linked by a chain of IDs in
+    /// which each consecutive pair is adjacent under `connectivity`. Adjacency is
+    /// decided directly on the index ranges of the IDs after rescaling them to a
+    /// common zoom level and time interval, so range IDs are never expanded into
+    /// single voxels. The X axis wraps around the antimeridian.
+    ///
+    /// # Returns
+    ///
+    /// One `SpaceTimeIdSet` per component, holding the original IDs of the set.
+    /// Components are ordered by the position of their first ID in [`iter`](Self::iter).
+    pub fn connected_components(&self, connectivity: Connectivity) -> Vec<SpaceTimeIdSet> {
+        let labels = self.connected_component_labels(connectivity);
+        let count = labels.iter().map(|&label| label + 1).max().unwrap_or(0);
+
+        let mut components = vec![SpaceTimeIdSet::new(); count];
+        for (id, label) in self.inner.iter().zip(labels) {
+            components[label].inner.push(*id);
+        }
+        components
+    }
+
+    /// Labels every ID of the set with the index of its connected component.
+    ///
+    /// The returned vector is aligned with [`iter`](Self::iter): the `n`-th label
+    /// belongs to the `n`-th ID. Labels are consecutive numbers starting at `0`,
+    /// assigned in order of first appearance, and match the order used by
+    /// [`connected_components`](Self::connected_components).
+    pub fn connected_component_labels(&self, connectivity: Connectivity) -> Vec<usize> {
+        let Some((grid, boxes)) = self.to_boxes() else {
+            return Vec::new();
+        };
+
+        let mut parent: Vec<usize> = (0..boxes.len()).collect();
+
+        // x の開始位置で並べ、x 方向に接し得る範囲だけを比較する
+        let mut order: Vec<usize> = (0..boxes.len()).collect();
+        order.sort_by_key(|&k| boxes[k].x.0);
+
+        for (n, &a) in order.iter().enumerate() {
+            for &b in &order[n + 1..] {
+                if boxes[b].x.0 > boxes[a].x.1 + 1 {
+                    break;
+                }
+                if Self::is_adjacent(grid, &boxes[a], &boxes[b], connectivity) {
+                    Self::union(&mut parent, a, b);
+                }
+            }
+        }
+
+        // 経度 ±180° で接する組み合わせ
+        let west: Vec<usize> = (0..boxes.len()).filter(|&k| boxes[k].x.0 == 0).collect();
+        let east: Vec<usize> = (0..boxes.len())
+            .filter(|&k| boxes[k].x.1 == grid.xy_max())
+            .collect();
+        for &a in &west {
+            for &b in &east {
+                if a != b && Self::is_adjacent(grid, &boxes[a], &boxes[b], connectivity) {
+                    Self::union(&mut parent, a, b);
+                }
+            }
+        }
+
+        let mut labels = Vec::with_capacity(boxes.len());
+        let mut roots: HashMap<usize, usize> = HashMap::new();
+        for k in 0..boxes.len() {
+            let root = Self::find(&mut parent, k);
+            let next = roots.len();
+            labels.push(*roots.entry(root).or_insert(next));
+        }
+        labels
+    }
+
+    fn is_adjacent(grid: Grid, a: &IndexBox, b: &IndexBox, connectivity: Connectivity) -> bool {
+        let t = Self::contact(a.t, b.t);
+        let t_ok = match t {
+            Contact::Overlap => true,
+            Contact::Touch => connectivity.temporal,
+            Contact::Apart => false,
+        };
+        if !t_ok {
+            return false;
+        }
+
+        let n = grid.xy_max() + 1;
+        let x = match Self::contact(a.x, b.x) {
+            // x は経度方向に循環する
+            Contact::Apart if a.x.1 + 1 == b.x.0 + n || b.x.1 + 1 == a.x.0 + n => Contact::Touch,
+            contact => contact,
+        };
+
+        let mut touching = 0;
+        for contact in [Self::contact(a.f, b.f), x, Self::contact(a.y, b.y)] {
+            match contact {
+                Contact::Overlap => {}
+                Contact::Touch => touching += 1,
+                Contact::Apart => return false,
+            }
+        }
+
+        let limit = match connectivity.neighborhood {
+            Neighborhood::Six => 1,
+            Neighborhood::Eighteen => 2,
+            Neighborhood::TwentySix => 3,
+        };
+        touching <= limit
+    }
+
+    fn contact(a: (i64, i64), b: (i64, i64)) -> Contact {
+        if a.0 <= b.1 && b.0 <= a.1 {
+            Contact::Overlap
+        } else if a.1 + 1 == b.0 || b.1 + 1 == a.0 {
+            Contact::Touch
+        } else {
+            Contact::Apart
+        }
+    }
+
+    fn find(parent: &mut [usize], mut k: usize) -> usize {
+        while parent[k] != k {
+            parent[k] = parent[parent[k]];
+            k = parent[k];
+        }
+        k
+    }
+
+    fn union(parent: &mut [usize], a: usize, b: usize) {
+        let ra = Self::find(parent, a);
+        let rb = Self::find(parent, b);
+        if ra != rb {
+            parent[ra.max(rb)] = ra.min(rb);
+        }
+    }
+}
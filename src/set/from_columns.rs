@@ -0,0 +1,60 @@
+use std::ops::RangeInclusive;
+
+use crate::{
+    id::{
+        DimensionRange::{Any, LimitRange, Single},
+        SpaceTimeId,
+    },
+    set::SpaceTimeIdSet,
+};
+
+impl SpaceTimeIdSet {
+    /// Builds a spatial set from voxel columns at a single zoom level.
+    ///
+    /// Each item is an `(x, y)` column and an inclusive range of `f` indices occupied in
+    /// it. Ranges of the same column that overlap or touch are merged, so a column
+    /// contributes one `LimitRange` ID per contiguous run. Because columns never overlap,
+    /// the resulting IDs are disjoint by construction and are stored without going
+    /// through `insert`.
+    ///
+    /// # Japanese Note
+    ///
+    /// (x, y) ごとの f の範囲を連続区間にまとめて集合を作る
+    pub(crate) fn from_columns<I>(z: u8, columns: I) -> Self
+    where
+        I: IntoIterator<Item = ((u32, u32), RangeInclusive<i32>)>,
+    {
+        let mut columns: Vec<((u32, u32), RangeInclusive<i32>)> = columns
+            .into_iter()
+            .filter(|(_, range)| !range.is_empty())
+            .collect();
+        columns.sort_unstable_by_key(|((x, y), range)| (*y, *x, *range.start()));
+
+        let mut inner = Vec::new();
+        let mut iter = columns.into_iter();
+        let Some((mut column, first)) = iter.next() else {
+            return Self { inner };
+        };
+        let (mut start, mut end) = first.into_inner();
+
+        for (next, range) in iter {
+            let (next_start, next_end) = range.into_inner();
+            if next == column && next_start as i64 <= end as i64 + 1 {
+                end = end.max(next_end);
+                continue;
+            }
+            inner.push(Self::column_run(z, column, start, end));
+            column = next;
+            start = next_start;
+            end = next_end;
+        }
+        inner.push(Self::column_run(z, column, start, end));
+
+        Self { inner }
+    }
+
+    fn column_run(z: u8, (x, y): (u32, u32), start: i32, end: i32) -> SpaceTimeId {
+        SpaceTimeId::new(z, LimitRange(start, end), Single(x), Single(y), 0, Any)
+            .expect("列から生成した SpaceTimeId が範囲外")
+    }
+}
@@ -1,11 +1,21 @@
 use crate::id::{DimensionRange, SpaceTimeId};
 pub mod and;
+pub(crate) mod boxes;
+pub mod buffer;
+pub mod components;
 pub mod equal;
+pub mod from_columns;
 pub mod from_hash;
 pub mod insert;
+pub mod nearest;
 pub mod not;
 pub mod or;
+pub mod product;
 pub mod pure;
+pub mod raycast;
+pub mod simplify;
+pub mod time;
+pub mod translate;
 pub mod xor;
 
 #[derive(Clone)]
@@ -33,6 +43,10 @@ impl SpaceTimeIdSet {
     pub fn new() -> Self {
         Self { inner: Vec::new() }
     }
+    /// 互いに重ならないことが分かっている ID の列から、結合せずにそのまま集合を作る
+    pub(crate) fn from_disjoint(inner: Vec<SpaceTimeId>) -> Self {
+        Self { inner }
+    }
     /// Returns an iterator over the `SpaceTimeId` elements contained in the set.
     /// This allows read-only access to each element in the set.
     pub fn iter(&self) -> impl Iterator<Item = &SpaceTimeId> {
@@ -0,0 +1,321 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::f64::consts::FRAC_PI_2;
+
+use crate::{
+    function::{
+        shape::distance,
+        tools::{ECEF, point_to_ecef::point_to_ecef},
+    },
+    id::{SpaceTimeId, coordinates::Point},
+    set::SpaceTimeIdSet,
+};
+
+/// WGS84 の長半径 [m]
+const EQUATORIAL_RADIUS: f64 = 6_378_137.0;
+
+/// WGS84 の第一離心率の 2 乗
+const E2: f64 = 6.694_379_990_141_317e-3;
+
+/// WGS84 の極での曲率半径 a / sqrt(1 - e^2) [m]。子午線・卯酉線の曲率半径の最大値
+const POLAR_CURVATURE_RADIUS: f64 = 6_399_593.625_8;
+
+/// 二分法の反復回数。f64 の精度で区間が縮まなくなるのに十分な回数
+const BISECTIONS: usize = 64;
+
+impl SpaceTimeIdSet {
+    /// Finds the ID of the set closest to `point`.
+    ///
+    /// The distance is the Euclidean distance in ECEF coordinates between `point` and
+    /// the nearest point of the region described by
+    /// [`coordinates`](SpaceTimeId::coordinates) of each ID. Range IDs are measured as a
+    /// whole and are never expanded. Longitude wraps around the antimeridian.
+    ///
+    /// The nearest point is computed exactly, up to floating-point rounding: the nearest
+    /// longitude is found in closed form, and within that meridian plane the candidates
+    /// are the two latitude faces and the latitudes whose ellipsoid normal passes through
+    /// `point`, which are bracketed where they are unique and found by bisection. This
+    /// holds for IDs above the smallest radius of curvature of the ellipsoid, about
+    /// 6,335 km below its surface.
+    ///
+    /// The IDs are visited in order of a cheap lower bound of their distance, and the
+    /// exact distance is only computed for IDs whose bound can still beat the closest
+    /// distance found so far.
+    ///
+    /// `time` is a point in time in seconds, compared against `t * i` of the temporal IDs.
+    /// Temporal IDs that are not active at `time` are skipped; spatial IDs (`i == 0`)
+    /// are always candidates. If `time` is `None`, every ID is a candidate.
+    ///
+    /// # Returns
+    ///
+    /// The closest ID and its distance in meters, or `None` if there is no candidate.
+    /// The distance is `0.0` if `point` lies inside an ID.
+    ///
+    /// # Japanese Note
+    ///
+    /// point に最も近い ID と、その ID までの距離 [m] を返す
+    pub fn nearest(&self, point: Point, time: Option<u64>) -> Option<(SpaceTimeId, f64)> {
+        let target = Target::new(point);
+        let candidates = target.candidates(self.inner.iter().filter(|id| match time {
+            Some(seconds) => Self::is_active(id, seconds),
+            None => true,
+        }));
+
+        let mut best: Option<(SpaceTimeId, f64)> = None;
+        for (bound, id) in candidates {
+            match best {
+                Some((_, distance)) if distance <= bound => break,
+                Some((_, distance)) => {
+                    let d = target.distance_to(&id);
+                    if d < distance {
+                        best = Some((id, d));
+                    }
+                }
+                None => best = Some((id, target.distance_to(&id))),
+            }
+        }
+        best
+    }
+
+    /// Finds the `k` IDs of the set closest to `point`.
+    ///
+    /// Distances are computed as in [`nearest`](Self::nearest), ignoring time.
+    ///
+    /// # Returns
+    ///
+    /// Up to `k` pairs of ID and distance in meters, sorted from the closest.
+    ///
+    /// # Japanese Note
+    ///
+    /// point に近い順に k 個の ID と距離 [m] を返す
+    pub fn k_nearest(&self, point: Point, k: usize) -> Vec<(SpaceTimeId, f64)> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let target = Target::new(point);
+        let mut result: Vec<(SpaceTimeId, f64)> = Vec::with_capacity(k + 1);
+        for (bound, id) in target.candidates(self.inner.iter()) {
+            // 下限が k 番目の距離以上なら、残りはどれも k 番以内に入らない
+            if result.len() == k && result[k - 1].1 <= bound {
+                break;
+            }
+            let d = target.distance_to(&id);
+            let position = result.partition_point(|&(_, other)| other <= d);
+            if position < k {
+                result.insert(position, (id, d));
+                result.truncate(k);
+            }
+        }
+        result
+    }
+}
+
+/// 距離を測る基準点
+struct Target {
+    point: Point,
+    ecef: ECEF,
+}
+
+/// 距離の下限が小さいものから取り出す候補
+struct Candidate {
+    bound: f64,
+    id: SpaceTimeId,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap は最大を取り出すので逆順にする
+        other.bound.total_cmp(&self.bound)
+    }
+}
+
+impl Target {
+    fn new(point: Point) -> Self {
+        Self {
+            point,
+            ecef: point_to_ecef(point),
+        }
+    }
+
+    /// 距離の下限が小さい順に候補を返す。途中で打ち切られることが多いので、全体は並べ替えずにヒープから取り出す
+    fn candidates<'a, I>(&self, ids: I) -> impl Iterator<Item = (f64, SpaceTimeId)> + use<I>
+    where
+        I: IntoIterator<Item = &'a SpaceTimeId>,
+    {
+        let mut heap: BinaryHeap<Candidate> = ids
+            .into_iter()
+            .map(|id| Candidate {
+                bound: self.lower_bound(id),
+                id: *id,
+            })
+            .collect();
+        std::iter::from_fn(move || heap.pop().map(|c| (c.bound, c.id)))
+    }
+
+    /// ID までの距離の下限 [m]
+    ///
+    /// ID の中心から高度・緯度・経度の順に動いて任意の点へ至る経路の長さで、中心からの距離を
+    /// 上から抑え、中心までの距離から引く
+    fn lower_bound(&self, id: &SpaceTimeId) -> f64 {
+        let c = id.coordinates();
+        let center = Point {
+            latitude: (c.latitude.0 + c.latitude.1) / 2.0,
+            longitude: (c.longitude.0 + c.longitude.1) / 2.0,
+            altitude: (c.altitude.0 + c.altitude.1) / 2.0,
+        };
+        let half = |(a, b): (f64, f64)| (b - a).abs() / 2.0;
+
+        // 楕円体の曲率半径は極での値 a / sqrt(1 - e^2) を超えない
+        let top = c.altitude.0.max(c.altitude.1);
+        let radius = (POLAR_CURVATURE_RADIUS + top).max(0.0);
+        let (north, south) = (c.latitude.0.abs(), c.latitude.1.abs());
+        let cos_max = if c.latitude.0.signum() != c.latitude.1.signum() {
+            1.0
+        } else {
+            north.min(south).to_radians().cos()
+        };
+
+        let reach = half(c.altitude)
+            + radius * half(c.latitude).to_radians()
+            + radius * cos_max * half(c.longitude).to_radians();
+        (distance(self.ecef, point_to_ecef(center)) - reach).max(0.0)
+    }
+
+    /// ID が表す曲がった直方体までの距離 [m]
+    ///
+    /// 緯度・高度を決めた点の集まりは z 軸まわりの円弧なので、最も近い経度は緯度・高度によらず
+    /// 経度の範囲への射影で決まる。その子午面に移すと、調べるのは緯度の境界 2 本と、
+    /// point を通る法線の緯度だけでよい
+    fn distance_to(&self, id: &SpaceTimeId) -> f64 {
+        let c = id.coordinates();
+        let latitude = (
+            c.latitude.0.min(c.latitude.1).to_radians(),
+            c.latitude.0.max(c.latitude.1).to_radians(),
+        );
+        let altitude = (
+            c.altitude.0.min(c.altitude.1),
+            c.altitude.0.max(c.altitude.1),
+        );
+
+        // 経度は直方体の中心に最も近くなるように 360° 単位でずらす。
+        // ずらした範囲への射影が、角度で測っても最も近い経度になる
+        let center = (c.longitude.0 + c.longitude.1) / 2.0;
+        let shift = ((center - self.point.longitude) / 360.0).round() * 360.0;
+        let longitude = self
+            .point
+            .longitude
+            .clamp(c.longitude.0 - shift, c.longitude.1 - shift);
+        let (sin, cos) = (self.point.longitude - longitude).to_radians().sin_cos();
+
+        let radius = self.ecef.x.hypot(self.ecef.y);
+        let target = (radius * cos, self.ecef.z);
+        let in_plane = meridian_distance(target, latitude, altitude);
+        in_plane.hypot(radius * sin)
+    }
+}
+
+/// 子午面 (赤道からの距離, z) 上の、緯度 phi [rad]・高度 0 の点と外向きの法線
+fn surface(phi: f64) -> ((f64, f64), (f64, f64)) {
+    let (sin, cos) = phi.sin_cos();
+    let n = EQUATORIAL_RADIUS / (1.0 - E2 * sin * sin).sqrt();
+    ((n * cos, n * (1.0 - E2) * sin), (cos, sin))
+}
+
+/// 子午面上で、緯度・高度の範囲が作る領域から target までの距離
+fn meridian_distance(target: (f64, f64), latitude: (f64, f64), altitude: (f64, f64)) -> f64 {
+    let along = |phi: f64| {
+        let (q, up) = surface(phi);
+        let h = (target.0 - q.0) * up.0 + (target.1 - q.1) * up.1;
+        (q, up, h)
+    };
+
+    let mut best = f64::INFINITY;
+    // 緯度の境界は法線上の線分
+    for phi in [latitude.0, latitude.1] {
+        let (q, up, h) = along(phi);
+        let h = h.clamp(altitude.0, altitude.1);
+        let p = (q.0 + h * up.0, q.1 + h * up.1);
+        best = best.min((target.0 - p.0).hypot(target.1 - p.1));
+    }
+    // target を通る法線の上では、高度の差がそのまま距離になる (範囲内なら 0)
+    for phi in foot_latitudes(target, latitude) {
+        let (_, _, h) = along(phi);
+        best = best.min((h - h.clamp(altitude.0, altitude.1)).abs());
+    }
+    best
+}
+
+/// 法線が target を通る緯度 [rad] のうち、範囲内にあるもの
+///
+/// G(phi) = e^2 N sin(phi) - rho tan(phi) + z の根で、G の増減は rho と
+/// K(phi) = e^2 cos^2(phi) d(N sin(phi))/d(phi) の大小で決まる。K は |phi| について減少するので、
+/// G が単調な区間は高々 3 つで、それぞれで二分法を使う
+fn foot_latitudes(target: (f64, f64), latitude: (f64, f64)) -> Vec<f64> {
+    let (rho, z) = target;
+    let g = |phi: f64| {
+        let (sin, cos) = phi.sin_cos();
+        let n = EQUATORIAL_RADIUS / (1.0 - E2 * sin * sin).sqrt();
+        E2 * n * sin - rho * sin / cos + z
+    };
+    let k = |phi: f64| {
+        let (sin, cos) = phi.sin_cos();
+        let w = 1.0 - E2 * sin * sin;
+        let n = EQUATORIAL_RADIUS / w.sqrt();
+        E2 * cos * cos * (n * cos + n * E2 * sin * sin * cos / w)
+    };
+
+    let mut cuts = vec![latitude.0, latitude.1];
+    if rho > 0.0 && rho < E2 * EQUATORIAL_RADIUS {
+        // K(phi) = rho となる phi > 0 (K(0) = e^2 a > rho, K(90°) = 0 < rho)
+        let (mut low, mut high) = (0.0, FRAC_PI_2);
+        for _ in 0..BISECTIONS {
+            let middle = (low + high) / 2.0;
+            if k(middle) > rho {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        for cut in [-low, low] {
+            if latitude.0 < cut && cut < latitude.1 {
+                cuts.push(cut);
+            }
+        }
+    }
+    cuts.sort_by(f64::total_cmp);
+
+    let mut roots = Vec::new();
+    for window in cuts.windows(2) {
+        let (mut low, mut high) = (window[0], window[1]);
+        let (g_low, g_high) = (g(low), g(high));
+        if g_low.signum() == g_high.signum() && g_low != 0.0 && g_high != 0.0 {
+            continue;
+        }
+        let rising = g_low < g_high;
+        for _ in 0..BISECTIONS {
+            let middle = (low + high) / 2.0;
+            if (g(middle) < 0.0) == rising {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        roots.push((low + high) / 2.0);
+    }
+    roots
+}
@@ -0,0 +1,57 @@
+use crate::{id::SpaceTimeId, set::SpaceTimeIdSet};
+
+impl SpaceTimeIdSet {
+    /// Returns the Cartesian product of the spatial extent of this set and the
+    /// temporal extent of `times`.
+    ///
+    /// Every ID of the result has the F, X, Y of an ID of `self` and the `i`, `t` of an
+    /// ID of `times`. This is typically used with a spatial set and a set produced by
+    /// [`Schedule::to_set`](crate::schedule::Schedule::to_set).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `self` contains temporal IDs, if `times` contains spatial
+    /// IDs, or if the time windows of `times` overlap (the product would not be
+    /// disjoint).
+    ///
+    /// # Japanese Note
+    ///
+    /// 空間 ID の集合と、時間帯の集合の直積を作る
+    pub fn product(&self, times: &SpaceTimeIdSet) -> Result<SpaceTimeIdSet, String> {
+        if let Some(id) = self.iter().find(|id| id.i() != 0) {
+            return Err(format!("{} is not a spatial ID", id));
+        }
+        if let Some(id) = times.iter().find(|id| id.i() == 0) {
+            return Err(format!("{} has no time window", id));
+        }
+
+        // 時間帯 [秒] が重ならないことを確かめる
+        let mut windows: Vec<(u64, u64, &SpaceTimeId)> = times
+            .iter()
+            .map(|id| {
+                let (s, e) = id.t_bounds();
+                let i = id.i() as u64;
+                (s as u64 * i, (e as u64 + 1) * i, id)
+            })
+            .collect();
+        windows.sort();
+        if let Some(w) = windows.windows(2).find(|w| w[1].0 < w[0].1) {
+            return Err(format!("time windows of {} and {} overlap", w[0].2, w[1].2));
+        }
+
+        let mut ids = Vec::new();
+        for space in self.iter() {
+            for time in times.iter() {
+                ids.push(SpaceTimeId::new(
+                    space.z(),
+                    space.f(),
+                    space.x(),
+                    space.y(),
+                    time.i(),
+                    time.t(),
+                )?);
+            }
+        }
+        Ok(SpaceTimeIdSet::from_disjoint(ids))
+    }
+}
@@ -0,0 +1,391 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::f64::consts::PI;
+
+use crate::{
+    id::SpaceTimeId,
+    set::{
+        SpaceTimeIdSet,
+        boxes::{Grid, IndexBox, coalesce, disjoint_union},
+    },
+};
+
+const EQUATORIAL_RADIUS: f64 = 6_378_137.0;
+
+/// 候補を探すときに、並べ替えた列で前後いくつまでを見るか
+const WINDOW: usize = 8;
+
+/// How [`SpaceTimeIdSet::simplify`] may change the region of the set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimplifyMode {
+    /// The result covers the original set. IDs are merged into their bounding boxes,
+    /// which adds volume.
+    Superset,
+    /// The result is contained in the original set. Nearby IDs are replaced by the
+    /// largest box inside their union, which removes volume.
+    Subset,
+}
+
+/// Result of [`SpaceTimeIdSet::simplify`] and [`SpaceTimeIdSet::simplify_within`].
+#[derive(Debug, Clone)]
+pub struct Simplified {
+    /// The simplified set.
+    pub set: SpaceTimeIdSet,
+    /// Volume in cubic meters added ([`SimplifyMode::Superset`]) or removed
+    /// ([`SimplifyMode::Subset`]) compared to the original set.
+    pub volume: f64,
+}
+
+impl SpaceTimeIdSet {
+    /// Approximates the set with at most `max_ids` IDs.
+    ///
+    /// All IDs are rescaled to the finest zoom level of the set and adjacent IDs are
+    /// first joined exactly. If more than `max_ids` IDs remain:
+    ///
+    /// - With [`SimplifyMode::Superset`], pairs of nearby IDs are repeatedly replaced by
+    ///   their bounding box, always choosing the merge that adds the least volume. IDs
+    ///   are only merged with IDs of the same time range.
+    /// - With [`SimplifyMode::Subset`], pairs of nearby IDs are repeatedly replaced by the
+    ///   largest box contained in their union, always choosing the replacement that
+    ///   removes the least volume. Two IDs that touch along one axis become the box
+    ///   spanning both where they face each other; otherwise the smaller one is dropped.
+    ///   An ID is dropped on its own only when that removes less volume than any
+    ///   replacement.
+    ///
+    /// The reported volume is the spatial volume in cubic meters, computed on a sphere
+    /// with the equatorial radius; time is not weighted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `max_ids` is 0 in superset mode on a non-empty set, or if the
+    /// IDs cannot be merged down to `max_ids` because their time ranges differ.
+    ///
+    /// # Japanese Note
+    ///
+    /// Superset は体積の増分が最小の組から外接直方体にまとめ、Subset は減る体積が最小の組から
+    /// 2 つの和集合に含まれる最大の直方体に置き換える
+    pub fn simplify(&self, max_ids: usize, mode: SimplifyMode) -> Result<Simplified, String> {
+        let Some((grid, boxes)) = self.to_boxes() else {
+            return Ok(Simplified {
+                set: SpaceTimeIdSet::new(),
+                volume: 0.0,
+            });
+        };
+        let boxes = coalesce(disjoint_union(boxes));
+
+        let (boxes, volume) = match mode {
+            SimplifyMode::Superset => {
+                if max_ids == 0 {
+                    return Err("A non-empty superset needs at least one ID".into());
+                }
+                let (boxes, volume) = merge(grid, boxes, max_ids, f64::INFINITY);
+                if boxes.len() > max_ids {
+                    return Err(format!(
+                        "Cannot merge the set below {} IDs: IDs with different time ranges are not merged",
+                        boxes.len()
+                    ));
+                }
+                (boxes, volume)
+            }
+            SimplifyMode::Subset => shrink(grid, boxes, max_ids, f64::INFINITY),
+        };
+
+        Ok(Simplified {
+            set: Self::from_boxes(grid, boxes)?,
+            volume,
+        })
+    }
+
+    /// Approximates the set with as few IDs as possible while changing at most
+    /// `max_volume` cubic meters.
+    ///
+    /// Works like [`simplify`](Self::simplify), but merges (superset) or shrinks (subset)
+    /// IDs only as long as the total added or removed volume stays within `max_volume`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `max_volume` is negative or not a number.
+    pub fn simplify_within(
+        &self,
+        max_volume: f64,
+        mode: SimplifyMode,
+    ) -> Result<Simplified, String> {
+        if max_volume.is_nan() || max_volume < 0.0 {
+            return Err(format!(
+                "Maximum volume must be non-negative. Got {}",
+                max_volume
+            ));
+        }
+        let Some((grid, boxes)) = self.to_boxes() else {
+            return Ok(Simplified {
+                set: SpaceTimeIdSet::new(),
+                volume: 0.0,
+            });
+        };
+        let boxes = coalesce(disjoint_union(boxes));
+
+        let (boxes, volume) = match mode {
+            SimplifyMode::Superset => merge(grid, boxes, 1, max_volume),
+            SimplifyMode::Subset => shrink(grid, boxes, 0, max_volume),
+        };
+        Ok(Simplified {
+            set: Self::from_boxes(grid, boxes)?,
+            volume,
+        })
+    }
+}
+
+/// 直方体の空間的な体積 (m^3)。球面上の緯度帯の面積に高さを掛ける
+fn volume(grid: Grid, b: &IndexBox) -> f64 {
+    let n = 1_u64 << grid.z;
+    let north = SpaceTimeId::latitude(b.y.0 as u32, n as u32).to_radians();
+    let south = SpaceTimeId::latitude((b.y.1 + 1) as u32, n as u32).to_radians();
+    let width = 2.0 * PI * (b.x.1 - b.x.0 + 1) as f64 / n as f64;
+    let area = EQUATORIAL_RADIUS * EQUATORIAL_RADIUS * width * (north.sin() - south.sin());
+    let height = (b.f.1 - b.f.0 + 1) as f64 * 2_f64.powi(25 - grid.z as i32);
+    area * height
+}
+
+fn bounding(a: &IndexBox, b: &IndexBox) -> IndexBox {
+    let span = |p: (i64, i64), q: (i64, i64)| (p.0.min(q.0), p.1.max(q.1));
+    IndexBox {
+        f: span(a.f, b.f),
+        x: span(a.x, b.x),
+        y: span(a.y, b.y),
+        t: a.t,
+    }
+}
+
+/// 2 つの直方体の和集合に含まれる最大の直方体
+///
+/// 1 つの軸で接していて他の軸で重なるなら、その軸に両方をつないだ直方体も候補になる
+fn largest_within(grid: Grid, a: &IndexBox, b: &IndexBox) -> IndexBox {
+    let mut best = if volume(grid, a) >= volume(grid, b) {
+        *a
+    } else {
+        *b
+    };
+    for dim in 0..3 {
+        let (p, q) = (a.dim(dim), b.dim(dim));
+        if p.1 + 1 != q.0 && q.1 + 1 != p.0 {
+            continue;
+        }
+        let mut joined = *a;
+        *joined.dim_mut(dim) = (p.0.min(q.0), p.1.max(q.1));
+        let mut overlaps = true;
+        for other in (0..3).filter(|&other| other != dim) {
+            let (p, q) = (a.dim(other), b.dim(other));
+            let common = (p.0.max(q.0), p.1.min(q.1));
+            overlaps &= common.0 <= common.1;
+            *joined.dim_mut(other) = common;
+        }
+        if overlaps && volume(grid, &joined) > volume(grid, &best) {
+            best = joined;
+        }
+    }
+    best
+}
+
+/// 2 つの直方体を、和集合に含まれる最大の直方体に置き換えることを繰り返す
+///
+/// a == b の候補は直方体を 1 つだけ捨てる。数が max_ids 以下になるか、
+/// 取り除いた体積が budget を超える置き換えしか残らなくなるまで続ける
+fn shrink(grid: Grid, boxes: Vec<IndexBox>, max_ids: usize, budget: f64) -> (Vec<IndexBox>, f64) {
+    // 取り除いた直方体は None にし、新しい直方体は末尾に足す
+    let mut live: Vec<Option<IndexBox>> = boxes.into_iter().map(Some).collect();
+    let mut count = live.len();
+    let mut removed = 0.0;
+
+    let estimate = |live: &[Option<IndexBox>], a: usize, b: usize| -> Option<Candidate> {
+        let (p, q) = (live[a]?, live[b]?);
+        if a == b {
+            return Some(Candidate {
+                cost: volume(grid, &p),
+                a,
+                b,
+            });
+        }
+        if p.t != q.t {
+            return None;
+        }
+        let kept = largest_within(grid, &p, &q);
+        let cost = volume(grid, &p) + volume(grid, &q) - volume(grid, &kept);
+        Some(Candidate { cost, a, b })
+    };
+
+    let mut heap = candidates(&live, &estimate);
+    heap.extend((0..live.len()).filter_map(|k| estimate(&live, k, k)));
+
+    while count > max_ids {
+        let Some(Candidate { cost, a, b }) = heap.pop() else {
+            break;
+        };
+        let (Some(p), Some(q)) = (live[a], live[b]) else {
+            continue;
+        };
+        if removed + cost > budget {
+            continue;
+        }
+
+        removed += cost;
+        count -= 1;
+        live[a] = None;
+        live[b] = None;
+        if a == b {
+            continue;
+        }
+        live.push(Some(largest_within(grid, &p, &q)));
+
+        // 新しい直方体と、まだ残っている直方体との候補を足す
+        let new = live.len() - 1;
+        let mut nearest: Vec<Candidate> =
+            (0..new).filter_map(|k| estimate(&live, k, new)).collect();
+        nearest.sort_by(|x, y| y.cmp(x));
+        heap.extend(nearest.into_iter().take(WINDOW));
+        heap.extend(estimate(&live, new, new));
+    }
+
+    (live.into_iter().flatten().collect(), removed)
+}
+
+/// 併合の候補。推定の増分体積が小さいものから取り出す
+struct Candidate {
+    cost: f64,
+    a: usize,
+    b: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap は最大を取り出すので逆順にする
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| (other.a, other.b).cmp(&(self.a, self.b)))
+    }
+}
+
+/// 外接直方体への併合を繰り返す
+///
+/// 数が max_ids 以下になるか、加えた体積が budget を超える併合しか残らなくなるまで続ける
+fn merge(grid: Grid, boxes: Vec<IndexBox>, max_ids: usize, budget: f64) -> (Vec<IndexBox>, f64) {
+    // 取り除いた直方体は None にし、新しい直方体は末尾に足す
+    let mut live: Vec<Option<IndexBox>> = boxes.into_iter().map(Some).collect();
+    let mut count = live.len();
+    let mut added = 0.0;
+
+    let estimate = |live: &[Option<IndexBox>], a: usize, b: usize| -> Option<Candidate> {
+        let (p, q) = (live[a]?, live[b]?);
+        if p.t != q.t {
+            return None;
+        }
+        let cost = volume(grid, &bounding(&p, &q)) - volume(grid, &p) - volume(grid, &q);
+        Some(Candidate { cost, a, b })
+    };
+
+    while count > max_ids {
+        let mut heap = candidates(&live, &estimate);
+        let mut merged_any = false;
+
+        while count > max_ids {
+            let Some(Candidate { a, b, .. }) = heap.pop() else {
+                break;
+            };
+            let (Some(p), Some(q)) = (live[a], live[b]) else {
+                continue;
+            };
+            let merged = bounding(&p, &q);
+
+            // 外接直方体に重なる直方体を調べる。時間範囲が違うものがあれば併合しない
+            let mut inside = 0.0;
+            let mut touched = Vec::new();
+            let mut pieces = Vec::new();
+            let mut conflict = false;
+            for (k, other) in live.iter().enumerate() {
+                let Some(other) = other else {
+                    continue;
+                };
+                if !other.intersects(&merged) {
+                    continue;
+                }
+                if other.t != merged.t {
+                    conflict = true;
+                    break;
+                }
+                let rest = other.subtract(&merged);
+                inside += volume(grid, other) - rest.iter().map(|r| volume(grid, r)).sum::<f64>();
+                touched.push(k);
+                pieces.extend(rest);
+            }
+            if conflict || touched.len() <= pieces.len() {
+                continue;
+            }
+            let delta = volume(grid, &merged) - inside;
+            if added + delta > budget {
+                continue;
+            }
+
+            added += delta;
+            count = count - touched.len() + pieces.len() + 1;
+            for k in touched {
+                live[k] = None;
+            }
+            let start = live.len();
+            live.extend(pieces.into_iter().map(Some));
+            live.push(Some(merged));
+            merged_any = true;
+
+            // 新しい直方体と、まだ残っている直方体との候補を足す
+            for new in start..live.len() {
+                let mut nearest: Vec<Candidate> = (0..live.len())
+                    .filter(|&k| k != new)
+                    .filter_map(|k| estimate(&live, k.min(new), k.max(new)))
+                    .collect();
+                nearest.sort_by(|x, y| y.cmp(x));
+                heap.extend(nearest.into_iter().take(WINDOW));
+            }
+        }
+
+        if !merged_any {
+            break;
+        }
+    }
+
+    (live.into_iter().flatten().collect(), added)
+}
+
+/// 各軸で並べ替えた列の前後 WINDOW 個を候補にする
+fn candidates<E>(live: &[Option<IndexBox>], estimate: &E) -> BinaryHeap<Candidate>
+where
+    E: Fn(&[Option<IndexBox>], usize, usize) -> Option<Candidate>,
+{
+    let alive: Vec<usize> = (0..live.len()).filter(|&k| live[k].is_some()).collect();
+    let mut heap = BinaryHeap::new();
+    for dim in 0..3 {
+        let mut order = alive.clone();
+        order.sort_by_key(|&k| {
+            let b = live[k].expect("alive");
+            (b.t, b.dim(dim), b.dim((dim + 1) % 3), b.dim((dim + 2) % 3))
+        });
+        for (position, &a) in order.iter().enumerate() {
+            for &b in order.iter().skip(position + 1).take(WINDOW) {
+                heap.extend(estimate(live, a.min(b), a.max(b)));
+            }
+        }
+    }
+    heap
+}
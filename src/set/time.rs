@@ -0,0 +1,192 @@
+use crate::{
+    id::{
+        DimensionRange::{self, AfterUnLimitRange, Any, BeforeUnLimitRange, LimitRange},
+        SpaceTimeId,
+        time::TimePoint,
+    },
+    set::SpaceTimeIdSet,
+};
+
+impl SpaceTimeIdSet {
+    /// Returns the spatial snapshot of the set at the instant `time`.
+    ///
+    /// Every ID active at `time` is kept with its spatial extent only (`i == 0`,
+    /// `t == Any`). Spatial IDs are active at every instant.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `time` is before the UNIX epoch.
+    ///
+    /// # Japanese Note
+    ///
+    /// ある時刻に存在する部分だけを空間 ID として取り出す
+    pub fn at_time(&self, time: TimePoint) -> Result<SpaceTimeIdSet, String> {
+        let seconds = time.seconds()?;
+        let ids = self
+            .iter()
+            .filter(|id| Self::is_active(id, seconds))
+            .map(|id| SpaceTimeId::new(id.z(), id.f(), id.x(), id.y(), 0, Any))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(SpaceTimeIdSet::from_disjoint(ids))
+    }
+
+    /// Returns the part of the set inside the time window `[start, end)`.
+    ///
+    /// `end == None` keeps everything from `start` on. When the window does not align
+    /// with the interval of an ID, the ID is re-expressed with [`SpaceTimeId::scale`]
+    /// at the greatest common divisor of its interval and the window bounds. Spatial
+    /// IDs become temporal IDs covering the window.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `start` is before the UNIX epoch, if the window is empty,
+    /// or if a time index does not fit in `u32` after re-expression.
+    ///
+    /// # Japanese Note
+    ///
+    /// 時間帯で切り出す。区切りが i と揃わないときは、最大公約数の i で表し直す
+    pub fn slice_time(
+        &self,
+        start: TimePoint,
+        end: Option<TimePoint>,
+    ) -> Result<SpaceTimeIdSet, String> {
+        let start = start.seconds()?;
+        let end = end.map(|end| end.seconds()).transpose()?;
+        if end.is_some_and(|end| end <= start) {
+            return Err(format!("time window {}..{:?} is empty", start, end));
+        }
+
+        let mut ids = Vec::new();
+        for id in self.iter() {
+            if id.i() == 0 {
+                if start == 0 && end.is_none() {
+                    ids.push(*id);
+                    continue;
+                }
+                let i = gcd(start, end.unwrap_or(start))?;
+                ids.push(
+                    id.scale(None, Some(i))?
+                        .with_t(window_range(i, start, end)?)?,
+                );
+                continue;
+            }
+
+            let i = gcd(gcd(id.i() as u64, start)? as u64, end.unwrap_or(0))?;
+            let scaled = rescale_time(id, i)?;
+            let (s, e) = scaled.t_bounds();
+            let open = matches!(scaled.t(), AfterUnLimitRange(_) | Any);
+
+            let (w0, w1) = window_bounds(i, start, end);
+            let s = (s as u64).max(w0);
+            let e = match (open, w1) {
+                (true, None) => None,
+                (true, Some(w1)) => Some(w1),
+                (false, None) => Some(e as u64),
+                (false, Some(w1)) => Some((e as u64).min(w1)),
+            };
+            if e.is_some_and(|e| e < s) {
+                continue;
+            }
+            ids.push(scaled.with_t(index_range(s, e)?)?);
+        }
+        Ok(SpaceTimeIdSet::from_disjoint(ids))
+    }
+
+    /// Moves the set `seconds` later in time (earlier if negative).
+    ///
+    /// When `seconds` is not a multiple of the interval of an ID, the ID is first
+    /// re-expressed with [`SpaceTimeId::scale`] at the greatest common divisor of the
+    /// two. Spatial IDs and temporal IDs with `t == Any` exist at every instant and are
+    /// kept as they are. A `BeforeUnLimitRange` starts at the UNIX epoch, so only its
+    /// end is moved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a bounded ID would start before the UNIX epoch, if a
+    /// `BeforeUnLimitRange` would end before it, or if a time index does not fit in
+    /// `u32`. Negative shifts are never clamped at the epoch.
+    ///
+    /// # Japanese Note
+    ///
+    /// 時間方向に平行移動する。ずらす量が i の倍数でないときは、最大公約数の i で表し直す
+    pub fn shift_time(&self, seconds: i64) -> Result<SpaceTimeIdSet, String> {
+        let mut ids = Vec::new();
+        for id in self.iter() {
+            // 全時間にわたる ID はずらしても変わらない
+            if id.i() == 0 || seconds == 0 || matches!(id.t(), Any) {
+                ids.push(*id);
+                continue;
+            }
+
+            let i = gcd(id.i() as u64, seconds.unsigned_abs())?;
+            let scaled = rescale_time(id, i)?;
+            let delta = seconds / i as i64;
+            let (s, e) = scaled.t_bounds();
+            let shift = |v: u32, what: &str| {
+                u64::try_from(v as i64 + delta)
+                    .map_err(|_| format!("{} would {} before the UNIX epoch", id, what))
+            };
+
+            let t = match scaled.t() {
+                AfterUnLimitRange(_) => index_range(shift(s, "start")?, None)?,
+                // 始まりは UNIX 時刻から開いているので、終わりだけをずらす
+                BeforeUnLimitRange(_) => {
+                    let e = shift(e, "end")?;
+                    BeforeUnLimitRange(
+                        u32::try_from(e)
+                            .map_err(|_| format!("time index {} is out of u32 range", e))?,
+                    )
+                }
+                _ => index_range(shift(s, "start")?, Some(shift(e, "end")?))?,
+            };
+            ids.push(scaled.with_t(t)?);
+        }
+        Ok(SpaceTimeIdSet::from_disjoint(ids))
+    }
+}
+
+/// 0 でない最大公約数。u32 に収まらなければエラー
+fn gcd(mut a: u64, mut b: u64) -> Result<u32, String> {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    match u32::try_from(a) {
+        Ok(0) => Err("time interval would be 0".into()),
+        Ok(i) => Ok(i),
+        Err(_) => Err(format!("time interval {} is out of u32 range", a)),
+    }
+}
+
+/// ID の時間を i で表し直す。scale の掛け算があふれる場合は先にエラーにする
+fn rescale_time(id: &SpaceTimeId, i: u32) -> Result<SpaceTimeId, String> {
+    let k = (id.i() / i) as u64;
+    let (s, e) = id.t_bounds();
+    let last = match id.t() {
+        AfterUnLimitRange(_) | Any => s as u64 * k,
+        _ => (e as u64 + 1) * k - 1,
+    };
+    if last > u32::MAX as u64 {
+        return Err(format!("{} cannot be re-expressed with interval {}", id, i));
+    }
+    id.scale(None, Some(i))
+}
+
+/// 時間帯 [start, end) を間隔 i のインデックスの範囲 (終わりを含む) にする
+fn window_bounds(i: u32, start: u64, end: Option<u64>) -> (u64, Option<u64>) {
+    let i = i as u64;
+    (start / i, end.map(|end| end / i - 1))
+}
+
+fn window_range(i: u32, start: u64, end: Option<u64>) -> Result<DimensionRange<u32>, String> {
+    let (s, e) = window_bounds(i, start, end);
+    index_range(s, e)
+}
+
+fn index_range(s: u64, e: Option<u64>) -> Result<DimensionRange<u32>, String> {
+    let index =
+        |v: u64| u32::try_from(v).map_err(|_| format!("time index {} is out of u32 range", v));
+    Ok(match e {
+        None => AfterUnLimitRange(index(s)?),
+        Some(e) => LimitRange(index(s)?, index(e)?),
+    })
+}
@@ -0,0 +1,65 @@
+use crate::{
+    id::{
+        SpaceTimeId,
+        translate::{meters_to_voxels, translate_box},
+    },
+    set::SpaceTimeIdSet,
+};
+
+impl SpaceTimeIdSet {
+    /// Moves the whole set by `dx`, `dy` and `df` voxels of its finest zoom level.
+    ///
+    /// IDs of coarser zoom levels are rescaled to the finest one, so every ID moves by
+    /// the same physical distance. X wraps around the antimeridian and IDs that end up
+    /// crossing it are split. The T dimension is unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any ID would leave the valid Y or F domain.
+    ///
+    /// # Japanese Note
+    ///
+    /// 集合全体を最も細かいズームのインデックスで平行移動する
+    pub fn translate(&self, dx: i64, dy: i64, df: i64) -> Result<SpaceTimeIdSet, String> {
+        let Some((grid, boxes)) = self.to_boxes() else {
+            return Ok(SpaceTimeIdSet::new());
+        };
+        let mut moved = Vec::with_capacity(boxes.len());
+        for b in &boxes {
+            moved.extend(translate_box(grid, b, dx, dy, df)?);
+        }
+        Self::from_boxes(grid, moved)
+    }
+
+    /// Moves the whole set by a metric offset, `east`, `north` and `up` in meters.
+    ///
+    /// The offset is converted to whole voxels of the finest zoom level with the local
+    /// scale at the latitude of the center of the set, so the shape keeps its form and
+    /// the offset is exact at that latitude.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an offset is not finite, or the errors of
+    /// [`translate`](Self::translate).
+    pub fn translate_meters(
+        &self,
+        east: f64,
+        north: f64,
+        up: f64,
+    ) -> Result<SpaceTimeIdSet, String> {
+        let Some((grid, boxes)) = self.to_boxes() else {
+            return Ok(SpaceTimeIdSet::new());
+        };
+
+        // 集合の南北の中央の緯度
+        let y0 = boxes.iter().map(|b| b.y.0).min().unwrap_or(0);
+        let y1 = boxes.iter().map(|b| b.y.1).max().unwrap_or(0);
+        let n = 1_u64 << grid.z;
+        let north_edge = SpaceTimeId::latitude(y0 as u32, n as u32);
+        let south_edge = SpaceTimeId::latitude((y1 + 1) as u32, n as u32);
+        let latitude = (north_edge + south_edge) / 2.0;
+
+        let (dx, dy, df) = meters_to_voxels(grid.z, latitude, east, north, up)?;
+        self.translate(dx, dy, df)
+    }
+}
@@ -0,0 +1,222 @@
+use std::io::Read;
+
+/// A digital elevation model on a regular latitude/longitude grid.
+///
+/// # File format
+///
+/// [`Dem::read`] accepts the ESRI ASCII grid format with the grid defined in degrees
+/// of longitude and latitude:
+///
+/// ```text
+/// ncols        4
+/// nrows        3
+/// xllcorner    139.0
+/// yllcorner    35.0
+/// cellsize     0.001
+/// NODATA_value -9999
+/// 12.5 13.0 13.2 14.0
+/// ...
+/// ```
+///
+/// Header keys are case-insensitive. `xllcenter` and `yllcenter` may be used instead of
+/// the corner keys, and `NODATA_value` is optional. The values follow row by row from
+/// the northernmost row, each row from west to east.
+///
+/// # Japanese Note
+///
+/// ESRI ASCII グリッド形式の標高データ。最初の行が北端
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dem {
+    columns: usize,
+    rows: usize,
+    /// 南西端の経度・緯度
+    west: f64,
+    south: f64,
+    cell_size: f64,
+    no_data: Option<f64>,
+    values: Vec<f64>,
+}
+
+impl Dem {
+    /// Creates a DEM from its south-west corner, cell size and values.
+    ///
+    /// `values[row * columns + column]` is the height of the cell in row `row` counted
+    /// from the north and column `column` counted from the west. Cells equal to
+    /// `no_data` have no height.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the grid is empty, the cell size is not positive, or the
+    /// number of values is not `rows * columns`.
+    pub fn new(
+        columns: usize,
+        rows: usize,
+        west: f64,
+        south: f64,
+        cell_size: f64,
+        no_data: Option<f64>,
+        values: Vec<f64>,
+    ) -> Result<Self, String> {
+        if columns == 0 || rows == 0 {
+            return Err(format!("DEM must not be empty. Got {} x {}", columns, rows));
+        }
+        if cell_size.is_nan() || cell_size <= 0.0 {
+            return Err(format!("DEM cell size must be positive. Got {}", cell_size));
+        }
+        if values.len() != rows * columns {
+            return Err(format!(
+                "DEM of {} x {} needs {} values. Got {}",
+                columns,
+                rows,
+                rows * columns,
+                values.len()
+            ));
+        }
+        Ok(Self {
+            columns,
+            rows,
+            west,
+            south,
+            cell_size,
+            no_data,
+            values,
+        })
+    }
+
+    /// Reads a DEM in the ESRI ASCII grid format described above.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on I/O errors, if a header key is missing or repeated, if a
+    /// value is not a number, or if the number of values does not match the header.
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, String> {
+        let mut text = String::new();
+        reader
+            .read_to_string(&mut text)
+            .map_err(|e| e.to_string())?;
+
+        let mut tokens = text.split_whitespace().peekable();
+        let mut header: Vec<(String, f64)> = Vec::new();
+        while let Some(key) = tokens.next_if(|token| token.parse::<f64>().is_err()) {
+            let key = key.to_ascii_lowercase();
+            if header.iter().any(|(k, _)| *k == key) {
+                return Err(format!("DEM header key '{}' is repeated", key));
+            }
+            let value = tokens
+                .next()
+                .ok_or_else(|| format!("DEM header key '{}' has no value", key))?;
+            let value = value
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid value '{}' for DEM header key '{}'", value, key))?;
+            header.push((key, value));
+        }
+        let get = |key: &str| {
+            header
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|&(_, value)| value)
+        };
+        let require = |key: &str| get(key).ok_or_else(|| format!("DEM header has no '{}'", key));
+        let size = |key: &str| -> Result<usize, String> {
+            let value = require(key)?;
+            if value < 0.0 || value.fract() != 0.0 {
+                return Err(format!("Invalid DEM size {} for '{}'", value, key));
+            }
+            Ok(value as usize)
+        };
+
+        let columns = size("ncols")?;
+        let rows = size("nrows")?;
+        let cell_size = require("cellsize")?;
+        // 中心で与えられたときは角に直す
+        let corner = |axis: &str| match (
+            get(&format!("{}llcorner", axis)),
+            get(&format!("{}llcenter", axis)),
+        ) {
+            (Some(corner), None) => Ok(corner),
+            (None, Some(center)) => Ok(center - cell_size / 2.0),
+            _ => Err(format!(
+                "DEM header needs exactly one of '{0}llcorner' and '{0}llcenter'",
+                axis
+            )),
+        };
+        let west = corner("x")?;
+        let south = corner("y")?;
+
+        let values = tokens
+            .map(|token| {
+                token
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid number '{}' in DEM", token))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(
+            columns,
+            rows,
+            west,
+            south,
+            cell_size,
+            get("nodata_value"),
+            values,
+        )
+    }
+
+    /// Returns the longitude and latitude bounds of the grid as
+    /// `((west, east), (south, north))` in degrees.
+    pub fn bounds(&self) -> ((f64, f64), (f64, f64)) {
+        (
+            (self.west, self.west + self.columns as f64 * self.cell_size),
+            (self.south, self.south + self.rows as f64 * self.cell_size),
+        )
+    }
+
+    /// Returns the height of the cell that contains the position, or `None` outside the
+    /// grid or in a cell without data.
+    pub fn height(&self, latitude: f64, longitude: f64) -> Option<f64> {
+        let column = ((longitude - self.west) / self.cell_size).floor();
+        let row_from_south = ((latitude - self.south) / self.cell_size).floor();
+        if column < 0.0 || row_from_south < 0.0 {
+            return None;
+        }
+        let (column, row_from_south) = (column as usize, row_from_south as usize);
+        if column >= self.columns || row_from_south >= self.rows {
+            return None;
+        }
+        self.value(self.rows - 1 - row_from_south, column)
+    }
+
+    /// Returns the lowest and highest heights of the cells that overlap the area, or
+    /// `None` if no overlapping cell has data.
+    pub fn height_range(
+        &self,
+        (west, east): (f64, f64),
+        (south, north): (f64, f64),
+    ) -> Option<(f64, f64)> {
+        let index = |v: f64, origin: f64, count: usize| {
+            ((v - origin) / self.cell_size).clamp(0.0, count as f64)
+        };
+        let c0 = index(west, self.west, self.columns).floor() as usize;
+        let c1 = index(east, self.west, self.columns).ceil() as usize;
+        let r0 = index(south, self.south, self.rows).floor() as usize;
+        let r1 = index(north, self.south, self.rows).ceil() as usize;
+
+        let mut range: Option<(f64, f64)> = None;
+        for row_from_south in r0..r1 {
+            for column in c0..c1 {
+                if let Some(h) = self.value(self.rows - 1 - row_from_south, column) {
+                    range = Some(match range {
+                        Some((lo, hi)) => (lo.min(h), hi.max(h)),
+                        None => (h, h),
+                    });
+                }
+            }
+        }
+        range
+    }
+
+    fn value(&self, row: usize, column: usize) -> Option<f64> {
+        let value = self.values[row * self.columns + column];
+        (Some(value) != self.no_data && value.is_finite()).then_some(value)
+    }
+}
@@ -0,0 +1,169 @@
+//! Terrain volumes from elevation rasters.
+//!
+//! A [`dem::Dem`] is read from an ESRI ASCII grid and turned into sets of voxels at a
+//! zoom level: [`terrain_solid`] gives the voxels below the ground surface and
+//! [`agl_band`] the voxels within a height band above ground level (AGL). Both return
+//! one ID per voxel column, with the F range of the column merged into a single range.
+
+pub mod dem;
+
+use crate::{
+    function::tools::point_to_id::MAX_LATITUDE,
+    geoid::AltitudeReference,
+    id::{
+        DimensionRange::{Any, LimitRange, Single},
+        SpaceTimeId,
+        z_range::{F_MAX, F_MIN},
+    },
+    set::SpaceTimeIdSet,
+    terrain::dem::Dem,
+};
+
+/// Returns the voxels of zoom level `z` below the terrain surface of `dem`.
+///
+/// Each voxel column within the DEM extent is filled from `floor` (or from the bottom
+/// of the F domain if `floor` is `None`) up to the highest ground height of the DEM
+/// cells under the column. A voxel is included if any part of it is below the ground,
+/// so the result covers the terrain conservatively. Columns without DEM data or whose
+/// ground is below `floor` are left empty.
+///
+/// Heights of the DEM and `floor` are in `reference`; orthometric heights are converted
+/// with the geoid undulation at the center of each column.
+///
+/// # Errors
+///
+/// Returns an error if `z` is invalid or if the geoid model has no value for a column.
+///
+/// # Japanese Note
+///
+/// 列ごとに、足元の DEM セルの最大標高までを埋める。列は 1 つの ID にまとめる
+pub fn terrain_solid(
+    dem: &Dem,
+    z: u8,
+    floor: Option<f64>,
+    reference: AltitudeReference,
+) -> Result<SpaceTimeIdSet, String> {
+    columns(dem, z, reference, |_, ground_max, to_ellipsoidal| {
+        let bottom = match floor {
+            Some(floor) => Some(to_ellipsoidal(floor)?),
+            None => None,
+        };
+        Ok((bottom, to_ellipsoidal(ground_max)?))
+    })
+}
+
+/// Returns the voxels of zoom level `z` between `bottom` and `top` meters above the
+/// terrain surface of `dem`.
+///
+/// For each voxel column within the DEM extent, the band runs from `bottom` above the
+/// lowest ground height under the column to `top` above the highest one, so that the
+/// band is covered even where the ground slopes within the column. Columns without DEM
+/// data are left empty.
+///
+/// # Errors
+///
+/// Returns an error if `z` is invalid, if `bottom >= top`, or if the geoid model has no
+/// value for a column.
+pub fn agl_band(
+    dem: &Dem,
+    z: u8,
+    bottom: f64,
+    top: f64,
+    reference: AltitudeReference,
+) -> Result<SpaceTimeIdSet, String> {
+    if bottom.is_nan() || top.is_nan() || bottom >= top {
+        return Err(format!(
+            "AGL band bottom {} must be below top {}",
+            bottom, top
+        ));
+    }
+    columns(
+        dem,
+        z,
+        reference,
+        |ground_min, ground_max, to_ellipsoidal| {
+            Ok((
+                Some(to_ellipsoidal(ground_min + bottom)?),
+                to_ellipsoidal(ground_max + top)?,
+            ))
+        },
+    )
+}
+
+/// DEM の範囲にあるボクセル列ごとに高さの範囲 [bottom, top) を求め、列ごとの ID にする
+///
+/// range は (列の最低標高, 最高標高, 楕円体高への変換) から (下端, 上端) を返す。下端が None なら F_MIN から
+fn columns<F>(
+    dem: &Dem,
+    z: u8,
+    reference: AltitudeReference,
+    range: F,
+) -> Result<SpaceTimeIdSet, String>
+where
+    F: Fn(f64, f64, &dyn Fn(f64) -> Result<f64, String>) -> Result<(Option<f64>, f64), String>,
+{
+    if z >= 32 {
+        return Err(format!("Zoom level z must be 0..=31. Got {}", z));
+    }
+    let n = 1_u64 << z;
+    let (f_min, f_max) = (F_MIN[z as usize] as i64, F_MAX[z as usize] as i64);
+    let ((west, east), (south, north)) = dem.bounds();
+
+    // DEM の範囲に重なる x, y の範囲
+    let x_of = |lon: f64| SpaceTimeId::x_position(lon, n as u32).floor() as i64;
+    let y_of = |lat: f64| {
+        let lat = lat.clamp(-MAX_LATITUDE, MAX_LATITUDE);
+        SpaceTimeId::y_position(lat, n as u32).floor() as i64
+    };
+    let max = n as i64 - 1;
+    let (x0, x1) = (x_of(west).clamp(0, max), x_of(east).clamp(0, max));
+    let (y0, y1) = (y_of(north).clamp(0, max), y_of(south).clamp(0, max));
+
+    let scale = 2_f64.powi(z as i32 - 25);
+    let mut ids = Vec::new();
+    for y in y0..=y1 {
+        let lat = (
+            SpaceTimeId::latitude(y as u32 + 1, n as u32),
+            SpaceTimeId::latitude(y as u32, n as u32),
+        );
+        for x in x0..=x1 {
+            let lon = (
+                SpaceTimeId::longitude(x as u32, n as u32),
+                SpaceTimeId::longitude(x as u32 + 1, n as u32),
+            );
+            let Some((ground_min, ground_max)) = dem.height_range(lon, lat) else {
+                continue;
+            };
+            let (center_lat, center_lon) = ((lat.0 + lat.1) / 2.0, (lon.0 + lon.1) / 2.0);
+            let to_ellipsoidal = |h: f64| reference.ellipsoidal_height(center_lat, center_lon, h);
+            let (bottom, top) = range(ground_min, ground_max, &to_ellipsoidal)?;
+
+            // [bottom, top) に少しでもかかるボクセル
+            let lo = match bottom {
+                Some(bottom) => (bottom * scale).floor() as i64,
+                None => f_min,
+            };
+            let hi = (top * scale).ceil() as i64 - 1;
+            let (lo, hi) = (lo.max(f_min), hi.min(f_max));
+            if lo > hi {
+                continue;
+            }
+
+            let f = if lo == hi {
+                Single(lo as i32)
+            } else {
+                LimitRange(lo as i32, hi as i32)
+            };
+            ids.push(SpaceTimeId::new(
+                z,
+                f,
+                Single(x as u32),
+                Single(y as u32),
+                0,
+                Any,
+            )?);
+        }
+    }
+    // 列ごとに 1 つなので互いに素
+    Ok(SpaceTimeIdSet::from_disjoint(ids))
+}
@@ -1,9 +1,36 @@
+pub mod test_antimeridian;
+pub mod test_binary;
+pub mod test_buffer;
 pub mod test_complement;
+pub mod test_components;
 // pub mod test_containment;
 // pub mod test_coordinates;
 pub mod test_dimension_range;
+pub mod test_enu;
 pub mod test_equality;
+pub mod test_geoid;
+#[cfg(feature = "serde_support")]
+pub mod test_geojson;
+pub mod test_io;
+pub mod test_key;
+pub mod test_mesh;
+pub mod test_nearest;
+pub mod test_parse;
+pub mod test_pointcloud;
 pub mod test_points;
+pub mod test_points_to_ids;
+pub mod test_projection;
+pub mod test_raycast;
+pub mod test_schedule;
+#[cfg(feature = "serde_support")]
+pub mod test_serde_string;
 pub mod test_set_operations;
+pub mod test_shapes;
+pub mod test_simplify;
 pub mod test_spacetime_id;
 pub mod test_spacetime_id_set;
+pub mod test_terrain;
+pub mod test_tilehash;
+pub mod test_time;
+pub mod test_time_slice;
+pub mod test_translate;
@@ -0,0 +1,217 @@
+use crate::function::tools::{ECEF, ecef_to_id::ecef_to_id, point_to_id::point_to_id};
+use crate::id::DimensionRange::{AfterUnLimitRange, Any, BeforeUnLimitRange, LimitRange, Single};
+use crate::id::SpaceTimeId;
+use crate::id::coordinates::Point;
+use crate::id::relation::{Relation, relation};
+use crate::io::reader::LineReader;
+use crate::set::SpaceTimeIdSet;
+use std::collections::HashSet;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ID が覆う単一ボクセルの集合
+    fn voxels<'a, I: IntoIterator<Item = &'a SpaceTimeId>>(ids: I) -> HashSet<SpaceTimeId> {
+        ids.into_iter().flat_map(|id| id.pure()).collect()
+    }
+
+    #[test]
+    fn test_reversed_x_range_is_rejected() {
+        assert!(SpaceTimeId::new(3, Single(0), LimitRange(6, 1), Single(2), 0, Any).is_err());
+        // y は従来どおり入れ替える
+        let id = SpaceTimeId::new(3, Single(0), Single(1), LimitRange(5, 2), 0, Any).unwrap();
+        assert_eq!(id.y(), LimitRange(2, 5));
+    }
+
+    #[test]
+    fn test_new_wrapping() {
+        let ids =
+            SpaceTimeId::new_wrapping(3, Single(0), LimitRange(6, 1), Single(2), 0, Any).unwrap();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(ids[0].x(), AfterUnLimitRange(6));
+        assert_eq!(ids[1].x(), BeforeUnLimitRange(1));
+
+        // 経度は日付変更線で切れる
+        let east = ids[0].coordinates().longitude;
+        let west = ids[1].coordinates().longitude;
+        assert_eq!(east, (90.0, 180.0));
+        assert_eq!(west, (-180.0, -90.0));
+
+        let full =
+            SpaceTimeId::new_wrapping(3, Single(0), LimitRange(4, 3), Single(2), 0, Any).unwrap();
+        assert_eq!(full.len(), 1);
+        assert_eq!(full[0].x(), Any);
+
+        let plain =
+            SpaceTimeId::new_wrapping(3, Single(0), LimitRange(1, 6), Single(2), 0, Any).unwrap();
+        assert_eq!(
+            plain,
+            vec![SpaceTimeId::new(3, Single(0), LimitRange(1, 6), Single(2), 0, Any).unwrap()]
+        );
+
+        assert!(SpaceTimeId::new_wrapping(3, Single(0), LimitRange(8, 1), Any, 0, Any).is_err());
+        assert!(
+            SpaceTimeId::new_wrapping(3, Single(0), LimitRange(6, 1), Any, 0, Single(1)).is_err()
+        );
+    }
+
+    #[test]
+    fn test_complement_matches_wrapping() {
+        let id = SpaceTimeId::new(3, Any, LimitRange(2, 5), Any, 0, Any).unwrap();
+        let wrapped = SpaceTimeId::new_wrapping(3, Any, LimitRange(6, 1), Any, 0, Any).unwrap();
+        assert_eq!(voxels(&id.complement()), voxels(&wrapped));
+    }
+
+    #[test]
+    fn test_point_to_id_wraps_longitude() {
+        let at = |longitude: f64| {
+            point_to_id(
+                4,
+                Point {
+                    latitude: 35.0,
+                    longitude,
+                    altitude: 0.0,
+                },
+            )
+            .unwrap()
+        };
+        assert_eq!(at(180.0), at(-180.0));
+        assert_eq!(at(180.0).x(), Single(0));
+        assert_eq!(at(-179.9), at(180.1));
+        assert_eq!(at(179.9).x(), Single(15));
+        assert_eq!(at(190.0), at(-170.0));
+
+        // 負の x 軸上の点は経度 180° になる
+        let id = ecef_to_id(
+            4,
+            ECEF {
+                x: -6_378_137.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        )
+        .unwrap();
+        assert_eq!(id.x(), Single(0));
+    }
+
+    /// 日付変更線をまたぐ x = 6..=1 (z = 3) の集合
+    fn wrapped() -> SpaceTimeIdSet {
+        SpaceTimeIdSet::from_wrapping(3, Single(0), LimitRange(6, 1), Single(2), 0, Any).unwrap()
+    }
+
+    fn column(x: u32) -> SpaceTimeId {
+        SpaceTimeId::new(3, Single(0), Single(x), Single(2), 0, Any).unwrap()
+    }
+
+    #[test]
+    fn test_from_wrapping() {
+        let xs: HashSet<u32> = wrapped().pure().iter().map(|id| id.x_bounds().0).collect();
+        assert_eq!(xs, HashSet::from([6, 7, 0, 1]));
+
+        let plain =
+            SpaceTimeIdSet::from_wrapping(3, Single(0), LimitRange(1, 6), Single(2), 0, Any)
+                .unwrap();
+        assert_eq!(plain.iter().count(), 1);
+        assert!(
+            SpaceTimeIdSet::from_wrapping(3, Single(0), LimitRange(8, 1), Any, 0, Any).is_err()
+        );
+    }
+
+    #[test]
+    fn test_decoders_read_wrapping() {
+        let expected = voxels(wrapped().iter());
+
+        assert!("3/0/6:1/2".parse::<SpaceTimeId>().is_err());
+        let parsed = SpaceTimeIdSet::parse_wrapping("3/0/6:1/2").unwrap();
+        assert_eq!(voxels(parsed.iter()), expected);
+
+        // z = 3, f と y は Single, x は LimitRange(6, 6 - 5), t は Any
+        let bytes = [
+            b'K', b'S', b'I', b'D', 1, 0x00, 3, 0x10, 0x40, 0, 0, 12, 9, 4, 0xFF,
+        ];
+        assert!(SpaceTimeId::from_bytes(&bytes[6..14]).is_err());
+        let decoded = SpaceTimeIdSet::from_bytes(&bytes).unwrap();
+        assert_eq!(voxels(decoded.iter()), expected);
+
+        let text = "3/0/6:1/2\n3/0/4/2, bad\n";
+        let (set, errors) = LineReader::new(text.as_bytes()).into_set();
+        let mut with_four = expected.clone();
+        with_four.insert(column(4));
+        assert_eq!(voxels(set.iter()), with_four);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("line 2:"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn test_relation_across_antimeridian() {
+        let set = wrapped();
+        let relations = |id: SpaceTimeId| -> Vec<Relation> {
+            set.iter().map(|piece| relation(*piece, id)).collect()
+        };
+
+        // 180° の両側の列は、それぞれ片方の ID に含まれる
+        for x in [7, 0] {
+            let found = relations(column(x));
+            assert_eq!(
+                found
+                    .iter()
+                    .filter(|r| matches!(r, Relation::Superset(_)))
+                    .count(),
+                1
+            );
+            assert_eq!(
+                found
+                    .iter()
+                    .filter(|r| matches!(r, Relation::Disjoint))
+                    .count(),
+                1
+            );
+        }
+        // 間の列はどちらとも重ならない
+        assert!(
+            relations(column(4))
+                .iter()
+                .all(|r| matches!(r, Relation::Disjoint))
+        );
+        // 日付変更線の東側だけにかかる範囲は、東側の ID と部分的に重なる
+        let east = SpaceTimeId::new(3, Single(0), LimitRange(5, 6), Single(2), 0, Any).unwrap();
+        assert_eq!(
+            relations(east)
+                .iter()
+                .filter(|r| matches!(r, Relation::Overlap(_)))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_complement_across_antimeridian() {
+        // 補集合は粗いズームレベルの ID にまとまるので、z = 3 にそろえて比べる
+        let at_z3 = |set: &SpaceTimeIdSet| -> HashSet<SpaceTimeId> {
+            set.iter()
+                .flat_map(|id| id.scale(Some(3), None).unwrap().pure())
+                .collect()
+        };
+        let columns = |xs: std::ops::RangeInclusive<u32>| -> HashSet<SpaceTimeId> {
+            voxels(
+                xs.map(|x| SpaceTimeId::new(3, Any, Single(x), Any, 0, Any).unwrap())
+                    .collect::<Vec<_>>()
+                    .iter(),
+            )
+        };
+
+        // 180° で終わる ID の補集合は、日付変更線を越えずに西側だけになる
+        let east = SpaceTimeId::new(3, Any, AfterUnLimitRange(6), Any, 0, Any).unwrap();
+        assert_eq!(at_z3(&east.complement()), columns(0..=5));
+
+        // 日付変更線をまたぐ集合の補集合は、各 ID の補集合の共通部分で、またがない x = 2..=5
+        let set = SpaceTimeIdSet::from_wrapping(3, Any, LimitRange(6, 1), Any, 0, Any).unwrap();
+        let complement = set
+            .iter()
+            .map(|id| id.complement())
+            .reduce(|a, b| a & b)
+            .unwrap();
+        assert_eq!(at_z3(&complement), columns(2..=5));
+    }
+}
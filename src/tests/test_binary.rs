@@ -0,0 +1,270 @@
+use crate::binary::stream::{IdReader, IdWriter};
+use crate::id::DimensionRange::{
+    self, AfterUnLimitRange, Any, BeforeUnLimitRange, LimitRange, Single,
+};
+use crate::id::SpaceTimeId;
+use crate::set::SpaceTimeIdSet;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// 再現性のある xorshift 乱数
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+    }
+
+    fn range<T: Copy + PartialOrd>(rng: &mut Rng, a: T, b: T) -> DimensionRange<T> {
+        let (s, e) = if a <= b { (a, b) } else { (b, a) };
+        match rng.below(5) {
+            0 => Single(s),
+            1 => LimitRange(s, e),
+            2 => BeforeUnLimitRange(e),
+            3 => AfterUnLimitRange(s),
+            _ => Any,
+        }
+    }
+
+    /// `SpaceTimeId::new` が受け付けた ID だけを返す
+    fn random_id(rng: &mut Rng) -> Option<SpaceTimeId> {
+        let z = rng.below(32) as u8;
+        let n = 1_i64 << z;
+        let mut fv = || (rng.below(2 * n as u64) as i64 - n) as i32;
+        let (f0, f1) = (fv(), fv());
+        let mut xy = || rng.below(n as u64) as u32;
+        let (x0, x1, y0, y1) = (xy(), xy(), xy(), xy());
+        let i = match rng.below(3) {
+            0 => 0,
+            1 => rng.below(100) as u32 + 1,
+            _ => rng.next() as u32,
+        };
+        let (t0, t1) = (
+            rng.next() as u32 >> rng.below(32),
+            rng.next() as u32 >> rng.below(32),
+        );
+        let f = range(rng, f0, f1);
+        let x = range(rng, x0, x1);
+        let y = range(rng, y0, y1);
+        let t = if i == 0 { Any } else { range(rng, t0, t1) };
+        SpaceTimeId::new(z, f, x, y, i, t).ok()
+    }
+
+    fn random_ids(seed: u64, count: usize) -> Vec<SpaceTimeId> {
+        let mut rng = Rng(seed);
+        let mut ids = Vec::new();
+        while ids.len() < count {
+            if let Some(id) = random_id(&mut rng) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    fn as_set(set: &SpaceTimeIdSet) -> HashSet<SpaceTimeId> {
+        set.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_id_round_trip() {
+        for id in random_ids(0x9E37_79B9_7F4A_7C15, 5000) {
+            let bytes = id.to_bytes();
+            assert_eq!(SpaceTimeId::from_bytes(&bytes), Ok(id), "{}", id);
+        }
+    }
+
+    #[test]
+    fn test_id_encoding_is_compact() {
+        let id = SpaceTimeId::new(
+            20,
+            Single(10),
+            Single(931_000),
+            Single(412_000),
+            60,
+            Single(28_000_000),
+        )
+        .unwrap();
+        // z, タグ 2 バイト, i 1 バイト, 値 4 つ
+        assert!(id.to_bytes().len() <= 16, "{:?}", id.to_bytes());
+    }
+
+    #[test]
+    fn test_id_rejects_invalid_input() {
+        let id = SpaceTimeId::new(3, Single(2), LimitRange(1, 5), Single(7), 0, Any).unwrap();
+        let bytes = id.to_bytes();
+
+        // 末尾の余分なバイト
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert!(SpaceTimeId::from_bytes(&longer).is_err());
+
+        // 途中で切れた入力
+        for end in 0..bytes.len() {
+            assert!(SpaceTimeId::from_bytes(&bytes[..end]).is_err());
+        }
+
+        // 範囲外の X
+        let mut out_of_range = bytes.clone();
+        let last = out_of_range.len() - 1;
+        out_of_range[last] = 40;
+        assert!(SpaceTimeId::from_bytes(&out_of_range).is_err());
+
+        // 未知のタグ
+        let mut bad_tag = bytes;
+        bad_tag[1] = 0x55;
+        assert!(SpaceTimeId::from_bytes(&bad_tag).is_err());
+    }
+
+    #[test]
+    fn test_random_bytes_never_panic() {
+        let mut rng = Rng(0xD1B5_4A32_D192_ED03);
+        for _ in 0..20000 {
+            let len = rng.below(24) as usize;
+            let bytes: Vec<u8> = (0..len).map(|_| rng.next() as u8).collect();
+            let _ = SpaceTimeId::from_bytes(&bytes);
+
+            let mut framed = b"KSID\x01".to_vec();
+            framed.extend_from_slice(&bytes);
+            let _ = SpaceTimeIdSet::from_bytes(&framed);
+        }
+    }
+
+    #[test]
+    fn test_mutated_set_never_panics() {
+        let ids: HashSet<SpaceTimeId> = (0..40)
+            .map(|k| {
+                SpaceTimeId::new(
+                    12,
+                    Single(k % 3),
+                    Single(100 + k as u32 * 2),
+                    Single(50),
+                    0,
+                    Any,
+                )
+                .unwrap()
+            })
+            .collect();
+        let set = unsafe { SpaceTimeIdSet::from_hash(ids) };
+        let bytes = set.to_bytes();
+
+        let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+        for _ in 0..5000 {
+            let mut mutated = bytes.clone();
+            for _ in 0..=rng.below(3) {
+                let k = rng.below(mutated.len() as u64) as usize;
+                mutated[k] ^= 1 << rng.below(8);
+            }
+            let _ = SpaceTimeIdSet::from_bytes(&mutated);
+        }
+    }
+
+    #[test]
+    fn test_set_round_trip() {
+        // 互いに重ならないよう、x の位置をずらして並べる
+        let ids: HashSet<SpaceTimeId> = (0..300_u32)
+            .map(|k| {
+                let z = 10 + (k % 3) as u8;
+                let scale = 1 << (z - 10);
+                SpaceTimeId::new(
+                    z,
+                    Single(k as i32 % 5),
+                    Single(k * 3 * scale),
+                    LimitRange(10, 20),
+                    60,
+                    LimitRange(k, k + 10),
+                )
+                .unwrap()
+            })
+            .collect();
+        let set = unsafe { SpaceTimeIdSet::from_hash(ids.clone()) };
+
+        let bytes = set.to_bytes();
+        assert_eq!(&bytes[..5], b"KSID\x01");
+        let decoded = SpaceTimeIdSet::from_bytes(&bytes).unwrap();
+        assert_eq!(as_set(&decoded), ids);
+
+        // 並べ替えてから書くので、出力は順序によらない
+        assert_eq!(decoded.to_bytes(), bytes);
+
+        // 差分で書くので、1 件ずつ書くより短い
+        let separate: usize = ids.iter().map(|id| id.to_bytes().len()).sum();
+        assert!(bytes.len() < separate);
+    }
+
+    #[test]
+    fn test_set_rejects_invalid_input() {
+        let a = SpaceTimeId::new(5, Single(0), LimitRange(1, 4), Single(3), 0, Any).unwrap();
+        let b = SpaceTimeId::new(5, Single(0), Single(9), Single(3), 0, Any).unwrap();
+        let bytes = unsafe { SpaceTimeIdSet::from_hash([a, b].into_iter().collect()) }.to_bytes();
+        assert!(SpaceTimeIdSet::from_bytes(&bytes).is_ok());
+
+        // マジックバイトとバージョン
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(SpaceTimeIdSet::from_bytes(&bad_magic).is_err());
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 2;
+        assert!(SpaceTimeIdSet::from_bytes(&bad_version).is_err());
+
+        // 終端の欠落と余分なバイト
+        assert!(SpaceTimeIdSet::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert!(SpaceTimeIdSet::from_bytes(&longer).is_err());
+
+        // 空の集合
+        let empty = SpaceTimeIdSet::new().to_bytes();
+        assert!(SpaceTimeIdSet::from_bytes(&empty).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_set_rejects_overlap() {
+        // 親と子の ID は重なる
+        let parent = SpaceTimeId::new(4, Single(1), Single(3), Single(5), 0, Any).unwrap();
+        let child = SpaceTimeId::new(6, Single(5), Single(13), Single(22), 0, Any).unwrap();
+        let mut writer = IdWriter::new(Vec::new()).unwrap();
+        writer.write(&parent).unwrap();
+        writer.write(&child).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        assert!(SpaceTimeIdSet::from_bytes(&bytes).is_err());
+        // ストリームとしては読める
+        let read: Vec<_> = IdReader::new(bytes.as_slice())
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(read, vec![parent, child]);
+    }
+
+    #[test]
+    fn test_stream_round_trip() {
+        let ids = random_ids(0xA076_1D64_78BD_642F, 2000);
+        let mut writer = IdWriter::new(Vec::new()).unwrap();
+        for id in &ids {
+            writer.write(id).unwrap();
+        }
+        let bytes = writer.finish().unwrap();
+
+        let read: Vec<_> = IdReader::new(bytes.as_slice())
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(read, ids);
+
+        // 途中で切れたストリームはエラーで終わる
+        let truncated: Vec<_> = IdReader::new(&bytes[..bytes.len() / 2]).unwrap().collect();
+        assert!(truncated.last().unwrap().is_err());
+        assert!(truncated[..truncated.len() - 1].iter().all(|r| r.is_ok()));
+    }
+}
@@ -0,0 +1,157 @@
+use crate::id::DimensionRange::{Any, LimitRange, Single};
+use crate::id::SpaceTimeId;
+use crate::id::z_range::XY_MAX;
+use crate::set::SpaceTimeIdSet;
+use std::collections::HashSet;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voxels(set: &SpaceTimeIdSet) -> HashSet<SpaceTimeId> {
+        set.pure().into_iter().collect()
+    }
+
+    fn block(z: u8, f: (i32, i32), x: (u32, u32), y: (u32, u32)) -> SpaceTimeIdSet {
+        SpaceTimeIdSet::from(
+            SpaceTimeId::new(
+                z,
+                LimitRange(f.0, f.1),
+                LimitRange(x.0, x.1),
+                LimitRange(y.0, y.1),
+                0,
+                Any,
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_buffer_single_voxel() {
+        let id =
+            SpaceTimeId::new(20, Single(10), Single(931_000), Single(412_000), 0, Any).unwrap();
+        let grown = SpaceTimeIdSet::from(id).buffer(1.0, 1.0, 0).unwrap();
+
+        let result = voxels(&grown);
+        assert_eq!(result.len(), 27);
+        assert!(result.contains(&id));
+        assert!(result.contains(
+            &SpaceTimeId::new(20, Single(11), Single(931_001), Single(411_999), 0, Any).unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_buffer_zero_is_identity() {
+        let set = block(18, (0, 3), (100, 104), (200, 202));
+        assert_eq!(voxels(&set.buffer(0.0, 0.0, 0).unwrap()), voxels(&set));
+    }
+
+    #[test]
+    fn test_buffer_merges_overlapping_ids() {
+        let mut set = block(18, (0, 0), (100, 101), (200, 200));
+        set.insert(
+            SpaceTimeId::new(18, Single(0), LimitRange(104, 105), Single(200), 0, Any).unwrap(),
+        );
+
+        let grown = set.buffer(1.0, 0.0, 0).unwrap();
+        let result = voxels(&grown);
+
+        // x: 99..=106, y: 199..=201 の範囲が重複なく得られる
+        assert_eq!(result.len(), 8 * 3);
+        assert_eq!(grown.pure().len(), result.len());
+    }
+
+    #[test]
+    fn test_buffer_horizontal_distance_in_tiles() {
+        let set = block(18, (0, 0), (232_800, 232_800), (103_000, 103_000));
+        let coordinates = set.iter().next().unwrap().coordinates();
+        let width = (coordinates.longitude.1 - coordinates.longitude.0).to_radians()
+            * 6_378_137.0
+            * coordinates.latitude.0.to_radians().cos();
+
+        // 1.5 タイル分の距離は 2 タイルに切り上げられる
+        let grown = set.buffer(width * 1.5, 0.0, 0).unwrap();
+        assert_eq!(voxels(&grown).len(), 5 * 5);
+    }
+
+    #[test]
+    fn test_buffer_margin_holds_poleward() {
+        // 高緯度の低ズームでは、広げた先のタイルが元のタイルより目に見えて細い
+        let set = block(6, (0, 0), (20, 20), (10, 10));
+        let width_at = |y: u32| {
+            let c = SpaceTimeId::new(6, Single(0), Single(20), Single(y), 0, Any)
+                .unwrap()
+                .coordinates();
+            (c.longitude.1 - c.longitude.0).to_radians()
+                * 6_378_137.0
+                * c.latitude.0.to_radians().cos()
+        };
+        let meters = width_at(10) * 2.5;
+
+        let result = voxels(&set.buffer(meters, 0.0, 0).unwrap());
+        let y_min = result.iter().map(|id| id.y_bounds().0).min().unwrap();
+        let cells = 10 - y_min;
+        assert!(cells > 3);
+        // 広げた後の北端の緯度でも、足したタイルの幅は要求以上
+        assert!(cells as f64 * width_at(y_min) >= meters);
+        assert_eq!(result.len(), (2 * cells as usize + 1).pow(2));
+    }
+
+    #[test]
+    fn test_buffer_time() {
+        let id = SpaceTimeId::new(10, Single(1), Single(2), Single(3), 60, Single(10)).unwrap();
+        let grown = SpaceTimeIdSet::from(id).buffer(0.0, 0.0, 90).unwrap();
+
+        let ids: Vec<_> = grown.iter().collect();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(ids[0].t(), LimitRange(8, 12));
+        assert_eq!(ids[0].i(), 60);
+    }
+
+    #[test]
+    fn test_buffer_wraps_antimeridian() {
+        let z = 10;
+        let set = block(z, (0, 0), (0, 0), (300, 300));
+        let result = voxels(&set.buffer(1.0, 0.0, 0).unwrap());
+
+        let max = XY_MAX[z as usize];
+        assert!(
+            result.contains(
+                &SpaceTimeId::new(z, Single(0), Single(max), Single(300), 0, Any).unwrap()
+            )
+        );
+        assert_eq!(result.len(), 9);
+    }
+
+    #[test]
+    fn test_buffer_rejects_negative_distance() {
+        let set = block(18, (0, 0), (100, 100), (200, 200));
+        assert!(set.buffer(-1.0, 0.0, 0).is_err());
+        assert!(set.erode(0.0, f64::NAN, 0).is_err());
+    }
+
+    #[test]
+    fn test_erode_block() {
+        let set = block(18, (0, 4), (100, 104), (200, 204));
+        let eroded = set.erode(1.0, 1.0, 0).unwrap();
+
+        assert_eq!(
+            voxels(&eroded),
+            voxels(&block(18, (1, 3), (101, 103), (201, 203)))
+        );
+    }
+
+    #[test]
+    fn test_erode_removes_thin_parts() {
+        let set = block(18, (0, 0), (100, 104), (200, 204));
+        assert!(set.erode(0.0, 1.0, 0).unwrap().is_empty());
+        assert_eq!(set.erode(0.0, 0.0, 0).unwrap().pure().len(), 25);
+    }
+
+    #[test]
+    fn test_erode_of_buffer_contains_original() {
+        let set = block(16, (0, 1), (5000, 5003), (3000, 3002));
+        let opened = set.buffer(1.0, 1.0, 0).unwrap().erode(1.0, 1.0, 0).unwrap();
+        assert_eq!(voxels(&opened), voxels(&set));
+    }
+}
@@ -0,0 +1,107 @@
+use crate::id::DimensionRange::{Any, LimitRange, Single};
+use crate::id::SpaceTimeId;
+use crate::id::z_range::XY_MAX;
+use crate::set::SpaceTimeIdSet;
+use crate::set::components::{Connectivity, Neighborhood};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voxel(z: u8, f: i32, x: u32, y: u32) -> SpaceTimeId {
+        SpaceTimeId::new(z, Single(f), Single(x), Single(y), 0, Any).unwrap()
+    }
+
+    fn set_of(ids: &[SpaceTimeId]) -> SpaceTimeIdSet {
+        let mut set = SpaceTimeIdSet::new();
+        for id in ids {
+            set.insert(*id);
+        }
+        set
+    }
+
+    fn count(set: &SpaceTimeIdSet, neighborhood: Neighborhood) -> usize {
+        set.connected_components(Connectivity::spatial(neighborhood))
+            .len()
+    }
+
+    #[test]
+    fn test_components_empty_set() {
+        let set = SpaceTimeIdSet::new();
+        assert!(
+            set.connected_components(Connectivity::spatial(Neighborhood::Six))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_components_separated_voxels() {
+        let set = set_of(&[voxel(10, 0, 5, 5), voxel(10, 0, 8, 5)]);
+        assert_eq!(count(&set, Neighborhood::TwentySix), 2);
+    }
+
+    #[test]
+    fn test_components_face_edge_corner() {
+        let face = set_of(&[voxel(10, 0, 5, 5), voxel(10, 0, 6, 5)]);
+        assert_eq!(count(&face, Neighborhood::Six), 1);
+
+        let edge = set_of(&[voxel(10, 0, 5, 5), voxel(10, 0, 6, 6)]);
+        assert_eq!(count(&edge, Neighborhood::Six), 2);
+        assert_eq!(count(&edge, Neighborhood::Eighteen), 1);
+
+        let corner = set_of(&[voxel(10, 0, 5, 5), voxel(10, 1, 6, 6)]);
+        assert_eq!(count(&corner, Neighborhood::Eighteen), 2);
+        assert_eq!(count(&corner, Neighborhood::TwentySix), 1);
+    }
+
+    #[test]
+    fn test_components_chain_of_ranges() {
+        let a = SpaceTimeId::new(10, Single(0), LimitRange(0, 9), Single(5), 0, Any).unwrap();
+        let b = SpaceTimeId::new(10, Single(0), Single(9), LimitRange(6, 20), 0, Any).unwrap();
+        let c = SpaceTimeId::new(10, LimitRange(1, 4), Single(9), Single(20), 0, Any).unwrap();
+        let d = voxel(10, 0, 30, 30);
+        let set = set_of(&[a, b, c, d]);
+
+        let components = set.connected_components(Connectivity::spatial(Neighborhood::Six));
+        assert_eq!(components.len(), 2);
+
+        let labels = set.connected_component_labels(Connectivity::spatial(Neighborhood::Six));
+        assert_eq!(labels.len(), set.iter().count());
+        for (id, label) in set.iter().zip(&labels) {
+            assert!(components[*label].iter().any(|other| other == id));
+        }
+    }
+
+    #[test]
+    fn test_components_mixed_zoom_levels() {
+        // z=10 の (5, 5) は z=12 の x=20..=23 に相当する
+        let coarse = voxel(10, 0, 5, 5);
+        let fine = voxel(12, 0, 24, 21);
+        let set = set_of(&[coarse, fine]);
+        assert_eq!(count(&set, Neighborhood::Six), 1);
+
+        let far = voxel(12, 0, 25, 21);
+        assert_eq!(count(&set_of(&[coarse, far]), Neighborhood::TwentySix), 2);
+    }
+
+    #[test]
+    fn test_components_time_connectivity() {
+        let a = SpaceTimeId::new(10, Single(0), Single(5), Single(5), 60, Single(1)).unwrap();
+        let b = SpaceTimeId::new(10, Single(0), Single(6), Single(5), 60, Single(2)).unwrap();
+        let set = set_of(&[a, b]);
+
+        assert_eq!(count(&set, Neighborhood::TwentySix), 2);
+        let temporal = Connectivity {
+            neighborhood: Neighborhood::Six,
+            temporal: true,
+        };
+        assert_eq!(set.connected_components(temporal).len(), 1);
+    }
+
+    #[test]
+    fn test_components_wrap_antimeridian() {
+        let max = XY_MAX[10];
+        let set = set_of(&[voxel(10, 0, 0, 5), voxel(10, 0, max, 5)]);
+        assert_eq!(count(&set, Neighborhood::Six), 1);
+    }
+}
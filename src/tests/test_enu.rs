@@ -0,0 +1,97 @@
+use crate::function::tools::{
+    ENU, ecef_to_enu::ecef_to_enu, enu_to_id::enu_to_id, enu_to_point::enu_to_point,
+    point_to_ecef::point_to_ecef, point_to_enu::point_to_enu, point_to_id::point_to_id,
+};
+use crate::id::coordinates::Point;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGIN: Point = Point {
+        latitude: 35.681,
+        longitude: 139.767,
+        altitude: 40.0,
+    };
+
+    #[test]
+    fn test_origin_is_zero() {
+        let enu = point_to_enu(ORIGIN, ORIGIN);
+        assert!(enu.east.abs() < 1e-6 && enu.north.abs() < 1e-6 && enu.up.abs() < 1e-6);
+
+        let up = point_to_enu(
+            ORIGIN,
+            Point {
+                altitude: 140.0,
+                ..ORIGIN
+            },
+        );
+        assert!(up.east.abs() < 1e-6 && up.north.abs() < 1e-6);
+        assert!((up.up - 100.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_axes_directions() {
+        let east = point_to_enu(
+            ORIGIN,
+            Point {
+                longitude: ORIGIN.longitude + 0.001,
+                ..ORIGIN
+            },
+        );
+        assert!(east.east > 90.0 && east.east < 91.0);
+        assert!(east.north.abs() < 0.01);
+
+        let north = point_to_enu(
+            ORIGIN,
+            Point {
+                latitude: ORIGIN.latitude + 0.001,
+                ..ORIGIN
+            },
+        );
+        assert!(north.north > 110.0 && north.north < 112.0);
+        assert!(north.east.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let enu = ENU {
+            east: 1234.5,
+            north: -678.9,
+            up: 321.0,
+        };
+        let point = enu_to_point(ORIGIN, enu);
+        let back = point_to_enu(ORIGIN, point);
+        assert!((back.east - enu.east).abs() < 1e-4);
+        assert!((back.north - enu.north).abs() < 1e-4);
+        assert!((back.up - enu.up).abs() < 1e-4);
+
+        let same = ecef_to_enu(ORIGIN, point_to_ecef(point));
+        assert_eq!(same, back);
+        assert_eq!(
+            enu_to_id(20, ORIGIN, enu).unwrap(),
+            point_to_id(20, point).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_vertex_enu() {
+        let id = point_to_id(20, ORIGIN).unwrap();
+        let vertices = id.vertex_enu(ORIGIN);
+        for (enu, point) in vertices.iter().zip(id.vertex()) {
+            assert_eq!(*enu, point_to_enu(ORIGIN, point));
+        }
+        // ボクセルは原点を含む
+        let (min, max) = vertices
+            .iter()
+            .fold(([f64::MAX; 3], [f64::MIN; 3]), |(min, max), v| {
+                (
+                    [min[0].min(v.east), min[1].min(v.north), min[2].min(v.up)],
+                    [max[0].max(v.east), max[1].max(v.north), max[2].max(v.up)],
+                )
+            });
+        for k in 0..3 {
+            assert!(min[k] <= 1e-6 && max[k] >= -1e-6);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use crate::function::tools::point_to_id::{point_to_id, point_to_id_with_reference};
+use crate::geoid::grid::GridGeoid;
+use crate::geoid::{AltitudeReference, ConstantGeoid, GeoidModel};
+use crate::id::DimensionRange::Single;
+use crate::id::coordinates::Point;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRID: &str = "35.00000 139.00000 1.000000 1.000000 2 3 1 ver2.1
+  36.0000  38.0000 999.0000
+  40.0000  42.0000  44.0000
+";
+
+    #[test]
+    fn test_grid_geoid_bilinear() {
+        let grid = GridGeoid::read(GRID.as_bytes()).unwrap();
+        assert_eq!(grid.undulation(35.0, 139.0).unwrap(), 36.0);
+        assert_eq!(grid.undulation(36.0, 140.0).unwrap(), 42.0);
+        assert!((grid.undulation(35.5, 139.5).unwrap() - 39.0).abs() < 1e-9);
+        assert!((grid.undulation(35.25, 139.0).unwrap() - 37.0).abs() < 1e-9);
+
+        // 欠測点を含むセルと格子の外は誤り
+        assert!(grid.undulation(35.5, 140.5).is_err());
+        assert!(grid.undulation(34.9, 139.5).is_err());
+        assert!(grid.undulation(35.5, 141.1).is_err());
+    }
+
+    #[test]
+    fn test_grid_geoid_invalid() {
+        assert!(GridGeoid::read("".as_bytes()).is_err());
+        assert!(GridGeoid::read("35 139 1 1 2".as_bytes()).is_err());
+        assert!(GridGeoid::read("35 139 1 1 2 2\n1 2 3".as_bytes()).is_err());
+        assert!(GridGeoid::read("35 139 1 1 2 2\n1 2 x 4".as_bytes()).is_err());
+        assert!(GridGeoid::read("35 139 0 1 2 2\n1 2 3 4".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_point_to_id_with_reference() {
+        let geoid = ConstantGeoid::new(40.0);
+        let point = Point {
+            latitude: 35.68,
+            longitude: 139.76,
+            altitude: 100.0,
+        };
+
+        let ellipsoidal =
+            point_to_id_with_reference(25, point, AltitudeReference::Ellipsoidal).unwrap();
+        assert_eq!(ellipsoidal, point_to_id(25, point).unwrap());
+
+        let orthometric =
+            point_to_id_with_reference(25, point, AltitudeReference::Orthometric(&geoid)).unwrap();
+        assert_eq!(orthometric.f(), Single(140));
+        assert_eq!(orthometric.x(), ellipsoidal.x());
+        assert_eq!(orthometric.y(), ellipsoidal.y());
+
+        let grid = GridGeoid::read(GRID.as_bytes()).unwrap();
+        let outside = Point {
+            latitude: 0.0,
+            ..point
+        };
+        assert!(
+            point_to_id_with_reference(25, outside, AltitudeReference::Orthometric(&grid)).is_err()
+        );
+    }
+
+    #[test]
+    fn test_coordinates_with_reference() {
+        let geoid = ConstantGeoid::new(40.0);
+        let point = Point {
+            latitude: 35.68,
+            longitude: 139.76,
+            altitude: 100.0,
+        };
+        let id =
+            point_to_id_with_reference(25, point, AltitudeReference::Orthometric(&geoid)).unwrap();
+
+        let ellipsoidal = id.coordinates();
+        let orthometric = id
+            .coordinates_with_reference(AltitudeReference::Orthometric(&geoid))
+            .unwrap();
+        assert_eq!(orthometric.altitude, (100.0, 101.0));
+        assert_eq!(ellipsoidal.altitude, (140.0, 141.0));
+        assert_eq!(orthometric.latitude, ellipsoidal.latitude);
+        assert_eq!(orthometric.longitude, ellipsoidal.longitude);
+    }
+}
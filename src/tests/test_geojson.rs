@@ -0,0 +1,355 @@
+use crate::function::{tools::point_to_id::point_to_id, traversal::traverse};
+use crate::geojson::{export::ExportOptions, import::ImportOptions};
+use crate::id::DimensionRange::{AfterUnLimitRange, Any, LimitRange, Single};
+use crate::id::{SpaceTimeId, coordinates::Point};
+use crate::set::SpaceTimeIdSet;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voxel(f: i32, x: u32, y: u32) -> SpaceTimeId {
+        SpaceTimeId::new(10, Single(f), Single(x), Single(y), 0, Any).unwrap()
+    }
+
+    fn set_of(ids: &[SpaceTimeId]) -> SpaceTimeIdSet {
+        let mut set = SpaceTimeIdSet::new();
+        for id in ids {
+            set.insert(*id);
+        }
+        set
+    }
+
+    fn merged(set: &SpaceTimeIdSet) -> Vec<Value> {
+        let collection = set.to_geojson(ExportOptions { merge: true });
+        assert_eq!(collection["type"], "FeatureCollection");
+        collection["features"].as_array().unwrap().clone()
+    }
+
+    /// 経度・緯度で測った符号付き面積 (反時計回りが正)
+    fn signed_area(ring: &Value) -> f64 {
+        let points: Vec<(f64, f64)> = ring
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| (p[0].as_f64().unwrap(), p[1].as_f64().unwrap()))
+            .collect();
+        points
+            .windows(2)
+            .map(|w| w[0].0 * w[1].1 - w[1].0 * w[0].1)
+            .sum::<f64>()
+            / 2.0
+    }
+
+    #[test]
+    fn test_geojson_single_feature() {
+        let id = SpaceTimeId::new(10, LimitRange(2, 3), Single(900), Single(400), 0, Any).unwrap();
+        let feature = id.to_geojson();
+
+        assert_eq!(feature["type"], "Feature");
+        assert_eq!(feature["geometry"]["type"], "Polygon");
+        let ring = &feature["geometry"]["coordinates"][0];
+        assert_eq!(ring.as_array().unwrap().len(), 5);
+        assert_eq!(ring[0], ring[4]);
+        assert!(signed_area(ring) > 0.0);
+
+        let c = id.coordinates();
+        assert_eq!(ring[0][0].as_f64().unwrap(), c.longitude.0);
+
+        let properties = &feature["properties"];
+        assert_eq!(properties["id"], id.to_string());
+        assert_eq!(properties["z"], 10);
+        assert_eq!(properties["f"], "2:3");
+        assert_eq!(properties["x"], "900");
+        assert_eq!(properties["t"], "-");
+        assert_eq!(properties["altitude_min"].as_f64().unwrap(), 2.0 * 32_768.0);
+        assert_eq!(properties["altitude_max"].as_f64().unwrap(), 4.0 * 32_768.0);
+        assert!(properties.get("time_start").is_none());
+    }
+
+    #[test]
+    fn test_geojson_time_window() {
+        let id =
+            SpaceTimeId::new(10, Single(0), Single(1), Single(1), 60, LimitRange(1, 2)).unwrap();
+        let properties = id.to_geojson()["properties"].clone();
+        assert_eq!(properties["time_start"], "1970-01-01T00:01:00Z");
+        assert_eq!(properties["time_end"], "1970-01-01T00:03:00Z");
+
+        let open = SpaceTimeId::new(
+            10,
+            Single(0),
+            Single(1),
+            Single(1),
+            3600,
+            AfterUnLimitRange(24),
+        )
+        .unwrap();
+        let properties = open.to_geojson()["properties"].clone();
+        assert_eq!(properties["time_start"], "1970-01-02T00:00:00Z");
+        assert!(properties["time_end"].is_null());
+    }
+
+    #[test]
+    fn test_geojson_one_feature_per_id() {
+        let set = set_of(&[voxel(0, 1, 1), voxel(0, 4, 1), voxel(3, 5, 5)]);
+        let collection = set.to_geojson(ExportOptions::default());
+        assert_eq!(collection["features"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_geojson_merge_square() {
+        let set = set_of(&[
+            voxel(0, 1, 1),
+            voxel(0, 2, 1),
+            voxel(0, 1, 2),
+            voxel(0, 2, 2),
+        ]);
+        let features = merged(&set);
+        assert_eq!(features.len(), 1);
+
+        let geometry = &features[0]["geometry"];
+        assert_eq!(geometry["type"], "MultiPolygon");
+        let polygons = geometry["coordinates"].as_array().unwrap();
+        assert_eq!(polygons.len(), 1);
+        let ring = &polygons[0][0];
+        assert_eq!(ring.as_array().unwrap().len(), 5);
+        assert!(signed_area(ring) > 0.0);
+        assert_eq!(features[0]["properties"]["f"], "0");
+    }
+
+    #[test]
+    fn test_geojson_merge_with_hole() {
+        let mut ids = Vec::new();
+        for x in 0..3 {
+            for y in 0..3 {
+                if (x, y) != (1, 1) {
+                    ids.push(voxel(0, 10 + x, 10 + y));
+                }
+            }
+        }
+        let features = merged(&set_of(&ids));
+        assert_eq!(features.len(), 1);
+
+        let polygons = features[0]["geometry"]["coordinates"].as_array().unwrap();
+        assert_eq!(polygons.len(), 1);
+        let rings = polygons[0].as_array().unwrap();
+        assert_eq!(rings.len(), 2);
+        assert!(signed_area(&rings[0]) > 0.0);
+        assert!(signed_area(&rings[1]) < 0.0);
+    }
+
+    #[test]
+    fn test_geojson_merge_diagonal_cells() {
+        let features = merged(&set_of(&[voxel(0, 1, 1), voxel(0, 2, 2)]));
+        assert_eq!(features.len(), 1);
+        let polygons = features[0]["geometry"]["coordinates"].as_array().unwrap();
+        assert_eq!(polygons.len(), 2);
+        for polygon in polygons {
+            assert_eq!(polygon[0].as_array().unwrap().len(), 5);
+        }
+    }
+
+    #[test]
+    fn test_geojson_merge_altitude_bands() {
+        let same = set_of(&[voxel(0, 1, 1), voxel(1, 1, 1)]);
+        let features = merged(&same);
+        assert_eq!(features.len(), 1);
+        assert_eq!(features[0]["properties"]["f"], "0:1");
+
+        let different = set_of(&[voxel(0, 1, 1), voxel(1, 2, 1)]);
+        assert_eq!(merged(&different).len(), 2);
+    }
+
+    #[test]
+    fn test_geojson_merge_empty() {
+        assert!(merged(&SpaceTimeIdSet::new()).is_empty());
+    }
+
+    fn voxels(set: &SpaceTimeIdSet) -> HashSet<SpaceTimeId> {
+        set.pure().into_iter().collect()
+    }
+
+    fn feature(geometry: Value, properties: Value) -> Value {
+        json!({ "type": "Feature", "geometry": geometry, "properties": properties })
+    }
+
+    #[test]
+    fn test_geojson_import_point() {
+        let point = Point {
+            latitude: 35.68,
+            longitude: 139.76,
+            altitude: 40.0,
+        };
+        let geometry = json!({ "type": "Point", "coordinates": [139.76, 35.68, 40.0] });
+        let report = SpaceTimeIdSet::from_geojson(&geometry, &ImportOptions::new(20)).unwrap();
+
+        assert!(report.errors.is_empty());
+        assert_eq!(report.converted, 1);
+        assert_eq!(
+            voxels(&report.set),
+            HashSet::from([point_to_id(20, point).unwrap()])
+        );
+    }
+
+    #[test]
+    fn test_geojson_import_line_string() {
+        let a = Point {
+            latitude: 35.68,
+            longitude: 139.76,
+            altitude: 10.0,
+        };
+        let b = Point {
+            latitude: 35.681,
+            longitude: 139.762,
+            altitude: 10.0,
+        };
+        let value = feature(
+            json!({ "type": "LineString", "coordinates": [[139.76, 35.68], [139.762, 35.681]] }),
+            json!({ "altitude_min": 10.0 }),
+        );
+        let report = SpaceTimeIdSet::from_geojson(&value, &ImportOptions::new(20)).unwrap();
+
+        let expected: HashSet<SpaceTimeId> = traverse(20, a, b).map(|step| step.id).collect();
+        assert_eq!(voxels(&report.set), expected);
+    }
+
+    #[test]
+    fn test_geojson_import_long_line_string_keeps_altitude() {
+        // 約 100 km の線分。ECEF の弦のままだと中央で 200 m ほど地下に潜る
+        let value = feature(
+            json!({ "type": "LineString", "coordinates": [[139.0, 35.0], [140.1, 35.0]] }),
+            json!({ "altitude_min": 100.0 }),
+        );
+        let report = SpaceTimeIdSet::from_geojson(&value, &ImportOptions::new(20)).unwrap();
+        let ids = voxels(&report.set);
+
+        // z = 20 のボクセルの高さは 32 m なので、高度 100 m はすべて f = 3
+        assert!(ids.iter().all(|id| id.f() == Single(3)), "{:?}", ids);
+
+        // 途中の列も欠けずにつながっている
+        let xs: HashSet<u32> = ids.iter().map(|id| id.x_bounds().0).collect();
+        let (start, end) = (*xs.iter().min().unwrap(), *xs.iter().max().unwrap());
+        assert!(end - start > 2_000);
+        assert_eq!(xs.len() as u32, end - start + 1);
+    }
+
+    #[test]
+    fn test_geojson_import_polygon() {
+        let corner = SpaceTimeId::new(
+            20,
+            Single(0),
+            LimitRange(10, 12),
+            LimitRange(20, 21),
+            0,
+            Any,
+        )
+        .unwrap()
+        .coordinates();
+        let (west, east) = corner.longitude;
+        let (north, south) = corner.latitude;
+        let value = feature(
+            json!({
+                "type": "Polygon",
+                "coordinates": [[[west, south], [east, south], [east, north], [west, north], [west, south]]],
+            }),
+            json!({ "altitude_min": 0.0, "altitude_max": 64.0 }),
+        );
+        let report = SpaceTimeIdSet::from_geojson(&value, &ImportOptions::new(20)).unwrap();
+
+        let expected = SpaceTimeId::new(
+            20,
+            LimitRange(0, 1),
+            LimitRange(10, 12),
+            LimitRange(20, 21),
+            0,
+            Any,
+        )
+        .unwrap();
+        assert_eq!(voxels(&report.set), voxels(&SpaceTimeIdSet::from(expected)));
+    }
+
+    #[test]
+    fn test_geojson_import_polygon_with_hole() {
+        let c = |x: u32, y: u32| {
+            let coordinates =
+                SpaceTimeId::new(20, Single(0), Single(1000 + x), Single(1000 + y), 0, Any)
+                    .unwrap()
+                    .coordinates();
+            [coordinates.longitude.0, coordinates.latitude.0]
+        };
+        let value = feature(
+            json!({
+                "type": "Polygon",
+                "coordinates": [
+                    [c(0, 3), c(3, 3), c(3, 0), c(0, 0), c(0, 3)],
+                    [c(1, 1), c(2, 1), c(2, 2), c(1, 2), c(1, 1)],
+                ],
+            }),
+            json!({ "altitude_min": 0.0, "altitude_max": 1.0 }),
+        );
+        let report = SpaceTimeIdSet::from_geojson(&value, &ImportOptions::new(20)).unwrap();
+
+        let result = voxels(&report.set);
+        assert_eq!(result.len(), 8);
+        assert!(!result.contains(
+            &SpaceTimeId::new(20, Single(0), Single(1001), Single(1001), 0, Any).unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_geojson_round_trip() {
+        let set = set_of(&[voxel(0, 100, 200), voxel(2, 300, 400)]);
+        let exported = set.to_geojson(ExportOptions::default());
+        let report = SpaceTimeIdSet::from_geojson(&exported, &ImportOptions::new(10)).unwrap();
+
+        assert!(report.errors.is_empty());
+        assert_eq!(voxels(&report.set), voxels(&set));
+    }
+
+    #[test]
+    fn test_geojson_import_time_window() {
+        let value = feature(
+            json!({ "type": "Point", "coordinates": [139.76, 35.68] }),
+            json!({ "begin": "1970-01-01T00:01:30Z", "end": 240 }),
+        );
+        let mut options = ImportOptions::new(20);
+        options.time_start = "begin".into();
+        options.time_end = "end".into();
+
+        let report = SpaceTimeIdSet::from_geojson(&value, &options).unwrap();
+        assert_eq!(report.errors.len(), 1);
+
+        options.interval = 60;
+        let report = SpaceTimeIdSet::from_geojson(&value, &options).unwrap();
+        let ids: Vec<_> = report.set.iter().collect();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(ids[0].i(), 60);
+        assert_eq!(ids[0].t(), LimitRange(1, 3));
+    }
+
+    #[test]
+    fn test_geojson_import_reports_errors() {
+        let value = json!({
+            "type": "FeatureCollection",
+            "features": [
+                feature(json!({ "type": "Point", "coordinates": [139.76, 35.68] }), json!({})),
+                { "type": "Feature", "id": "empty", "geometry": null, "properties": {} },
+                feature(json!({ "type": "Polygon", "coordinates": [[[0, 0], [1, 0], [1, 1], [0, 0]]] }), json!({})),
+                feature(json!({ "type": "Point", "coordinates": [0.0, 89.0] }), json!({})),
+                feature(json!({ "type": "GeometryCollection", "geometries": [] }), json!({})),
+            ],
+        });
+        let report = SpaceTimeIdSet::from_geojson(&value, &ImportOptions::new(20)).unwrap();
+
+        assert_eq!(report.converted, 1);
+        assert_eq!(voxels(&report.set).len(), 1);
+        let indices: Vec<usize> = report.errors.iter().map(|e| e.index).collect();
+        assert_eq!(indices, vec![1, 2, 3, 4]);
+        assert_eq!(report.errors[0].id, Some(json!("empty")));
+
+        assert!(SpaceTimeIdSet::from_geojson_str("{", &ImportOptions::new(20)).is_err());
+        assert!(SpaceTimeIdSet::from_geojson(&json!([]), &ImportOptions::new(20)).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use crate::id::DimensionRange::{AfterUnLimitRange, Any, LimitRange, Single};
+use crate::id::SpaceTimeId;
+use crate::io::reader::LineReader;
+use crate::io::writer::{LineFormat, TextWriter};
+use std::io::{BufReader, Read};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids() -> Vec<SpaceTimeId> {
+        vec![
+            SpaceTimeId::new(
+                4,
+                AfterUnLimitRange(10),
+                Single(5),
+                Single(3),
+                60,
+                LimitRange(100, 200),
+            )
+            .unwrap(),
+            SpaceTimeId::new(
+                20,
+                Single(-7),
+                LimitRange(931_000, 931_010),
+                Single(412_000),
+                0,
+                Any,
+            )
+            .unwrap(),
+            SpaceTimeId::new(3, Any, Single(1), Any, 10, AfterUnLimitRange(7)).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_lines_round_trip() {
+        let mut writer = TextWriter::new(Vec::new(), LineFormat::Lines).unwrap();
+        writer.write_all(&ids()).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let read: Vec<_> = LineReader::new(bytes.as_slice())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(read, ids());
+    }
+
+    #[test]
+    fn test_reader_is_tolerant() {
+        let text = "\
+# voxels exported by hand
+4/10:-/5/3_60/100:200,
+
+  20/-7/931000:931010/412000_0/-,   // trailing comment
+3/-/1/-_10/7:-, 2/0/1/1,
+";
+        let read: Vec<_> = LineReader::new(text.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let mut expected = ids();
+        expected.push(SpaceTimeId::new(2, Single(0), Single(1), Single(1), 0, Any).unwrap());
+        assert_eq!(read, expected);
+    }
+
+    #[test]
+    fn test_reader_reports_bad_lines_and_continues() {
+        let text = "2/0/1/1\n2/0/9/1\nnot an id\n2/0/1/2,\n";
+        let results: Vec<_> = LineReader::new(text.as_bytes()).collect();
+        assert_eq!(results.len(), 4);
+        assert!(results[0].is_ok());
+        assert!(results[1].as_ref().unwrap_err().starts_with("line 2:"));
+        assert!(results[2].as_ref().unwrap_err().starts_with("line 3:"));
+        assert!(results[3].is_ok());
+    }
+
+    #[test]
+    fn test_reader_streams_lazily() {
+        // 読み込みが最初の行だけで済むことを確かめる
+        struct Counting<'a> {
+            inner: &'a [u8],
+            consumed: std::rc::Rc<std::cell::Cell<usize>>,
+        }
+        impl Read for Counting<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = self.inner.read(buf)?;
+                self.consumed.set(self.consumed.get() + n);
+                Ok(n)
+            }
+        }
+
+        let text = "2/0/1/1\n".repeat(100_000);
+        let consumed = std::rc::Rc::new(std::cell::Cell::new(0));
+        let source = BufReader::with_capacity(
+            64,
+            Counting {
+                inner: text.as_bytes(),
+                consumed: consumed.clone(),
+            },
+        );
+        let mut reader = LineReader::new(source);
+        assert!(reader.next().unwrap().is_ok());
+        assert!(consumed.get() < 1024);
+        assert_eq!(reader.count(), 99_999);
+    }
+
+    #[test]
+    fn test_csv_columns() {
+        let mut writer = TextWriter::new(Vec::new(), LineFormat::Csv).unwrap();
+        writer.write_all(&ids()).unwrap();
+        let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+        let rows: Vec<Vec<&str>> = text.lines().map(|l| l.split(',').collect()).collect();
+
+        assert_eq!(rows.len(), 4);
+        let header = &rows[0];
+        assert_eq!(header.len(), 17);
+        assert!(rows.iter().all(|row| row.len() == 17));
+        let column =
+            |row: usize, name: &str| rows[row][header.iter().position(|h| *h == name).unwrap()];
+
+        assert_eq!(column(1, "id"), "4/10:-/5/3_60/100:200");
+        assert_eq!(column(1, "f_min"), "10");
+        assert_eq!(column(1, "f_max"), "15");
+        assert_eq!(column(1, "t_min"), "100");
+        assert_eq!(column(1, "t_max"), "200");
+        assert_eq!(column(2, "t_min"), "");
+        assert_eq!(column(3, "t_max"), "");
+        assert_eq!(column(3, "y_max"), "7");
+
+        let min: f64 = column(2, "latitude_min").parse().unwrap();
+        let max: f64 = column(2, "latitude_max").parse().unwrap();
+        assert!(min < max);
+        assert_eq!(
+            column(1, "altitude_max").parse::<f64>().unwrap(),
+            33_554_432.0
+        );
+
+        // CSV の id 列は読み戻せる
+        let ids_column: String = rows[1..]
+            .iter()
+            .map(|row| format!("{}\n", row[0]))
+            .collect();
+        let read: Vec<_> = LineReader::new(ids_column.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(read, ids());
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let mut writer = TextWriter::new(Vec::new(), LineFormat::Csv).unwrap();
+        writer.write_all(&ids()).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let read: Vec<_> = LineReader::new(bytes.as_slice())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(read, ids());
+
+        // コメントと空行の後のヘッダーも見つける。ヘッダーがなければ従来どおり全列を読む
+        let text = format!("# exported\n\n{}", String::from_utf8(bytes).unwrap());
+        let read: Vec<_> = LineReader::new(text.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(read, ids());
+        let results: Vec<_> = LineReader::new("2/0/1/1,4,0\n".as_bytes()).collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[1].is_err());
+    }
+}
@@ -0,0 +1,198 @@
+use crate::id::DimensionRange::{AfterUnLimitRange, Any, BeforeUnLimitRange, LimitRange, Single};
+use crate::id::SpaceTimeId;
+use crate::id::key::Curve;
+use crate::id::z_range::XY_MAX;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    const CURVES: [Curve; 2] = [Curve::Morton, Curve::Hilbert];
+
+    fn key(id: &SpaceTimeId, curve: Curve) -> u128 {
+        match curve {
+            Curve::Morton => id.morton_key().unwrap(),
+            Curve::Hilbert => id.hilbert_key().unwrap(),
+        }
+    }
+
+    fn from_key(z: u8, key: u128, curve: Curve) -> Result<SpaceTimeId, String> {
+        match curve {
+            Curve::Morton => SpaceTimeId::from_morton_key(z, key),
+            Curve::Hilbert => SpaceTimeId::from_hilbert_key(z, key),
+        }
+    }
+
+    fn voxels(z: u8) -> Vec<SpaceTimeId> {
+        let n = 1_i32 << z;
+        let mut out = Vec::new();
+        for f in -n..n {
+            for x in 0..n as u32 {
+                for y in 0..n as u32 {
+                    out.push(SpaceTimeId::new(z, Single(f), Single(x), Single(y), 0, Any).unwrap());
+                }
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for curve in CURVES {
+            for z in 0..=3 {
+                let mut seen = HashSet::new();
+                for id in voxels(z) {
+                    let k = key(&id, curve);
+                    assert!(k < 1 << (3 * (z as u32 + 1)));
+                    assert!(seen.insert(k), "duplicate key {} for {}", k, id);
+                    assert_eq!(from_key(z, k, curve), Ok(id));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_at_max_zoom() {
+        let max = (1_u32 << 31) - 1;
+        for curve in CURVES {
+            for (f, x, y) in [
+                (i32::MIN, 0, 0),
+                (i32::MAX, max, max),
+                (-5, 123_456, max - 7),
+            ] {
+                let id = SpaceTimeId::new(31, Single(f), Single(x), Single(y), 0, Any).unwrap();
+                assert_eq!(from_key(31, key(&id, curve), curve), Ok(id));
+            }
+        }
+    }
+
+    #[test]
+    fn test_morton_interleaving() {
+        // z = 1: f は +2 され、(f, y, x) の順にビットを並べる
+        let id = SpaceTimeId::new(1, Single(-1), Single(1), Single(0), 0, Any).unwrap();
+        // f + 2 = 1 = 0b01, y = 0b00, x = 0b01 → 000 101
+        assert_eq!(id.morton_key(), Ok(0b000_101));
+    }
+
+    #[test]
+    fn test_hilbert_steps_are_adjacent() {
+        let z = 3;
+        let mut previous: Option<SpaceTimeId> = None;
+        for k in 0..1_u128 << (3 * (z as u32 + 1)) {
+            // x, y の範囲外のキーでは曲線が途切れる
+            let Ok(id) = SpaceTimeId::from_hilbert_key(z, k) else {
+                previous = None;
+                continue;
+            };
+            if let Some(p) = previous {
+                let d = (p.f_bounds().0 - id.f_bounds().0).abs()
+                    + (p.x_bounds().0 as i32 - id.x_bounds().0 as i32).abs()
+                    + (p.y_bounds().0 as i32 - id.y_bounds().0 as i32).abs();
+                assert_eq!(d, 1, "{} -> {}", p, id);
+            }
+            previous = Some(id);
+        }
+    }
+
+    #[test]
+    fn test_key_requires_single_voxel() {
+        let id = SpaceTimeId::new(4, LimitRange(0, 1), Single(2), Single(3), 0, Any).unwrap();
+        assert!(id.morton_key().is_err());
+        assert!(id.hilbert_key().is_err());
+        assert!(SpaceTimeId::from_morton_key(2, 1 << 9).is_err());
+        assert!(SpaceTimeId::from_hilbert_key(32, 0).is_err());
+    }
+
+    #[test]
+    fn test_key_ranges_cover_exactly() {
+        let ids = [
+            SpaceTimeId::new(
+                3,
+                LimitRange(-3, 5),
+                LimitRange(1, 6),
+                LimitRange(2, 7),
+                0,
+                Any,
+            )
+            .unwrap(),
+            SpaceTimeId::new(3, Any, Single(4), Any, 0, Any).unwrap(),
+            SpaceTimeId::new(
+                3,
+                BeforeUnLimitRange(-2),
+                AfterUnLimitRange(5),
+                LimitRange(0, 3),
+                0,
+                Any,
+            )
+            .unwrap(),
+            SpaceTimeId::new(2, Single(1), Single(2), Single(3), 10, Single(4)).unwrap(),
+            SpaceTimeId::new(3, Any, Any, Any, 0, Any).unwrap(),
+        ];
+        for curve in CURVES {
+            for id in &ids {
+                let ranges = id.key_ranges(curve, usize::MAX);
+                let expected: HashSet<u128> = id.pure().iter().map(|v| key(v, curve)).collect();
+                let covered: HashSet<u128> = ranges.iter().flat_map(|&(s, e)| s..=e).collect();
+                assert_eq!(covered, expected, "{} {:?}", id, curve);
+
+                // 区間は昇順で、隣り合う区間の間には必ず隙間がある
+                for w in ranges.windows(2) {
+                    assert!(w[0].1 + 1 < w[1].0, "{:?}", ranges);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_key_ranges_of_aligned_block() {
+        // 整列した 2x2x2 のブロックは 1 つの区間になる
+        let id = SpaceTimeId::new(
+            20,
+            LimitRange(8, 9),
+            LimitRange(100, 101),
+            LimitRange(6, 7),
+            0,
+            Any,
+        )
+        .unwrap();
+        for curve in CURVES {
+            let ranges = id.key_ranges(curve, usize::MAX);
+            assert_eq!(ranges.len(), 1);
+            assert_eq!(ranges[0].1 - ranges[0].0, 7);
+        }
+    }
+
+    #[test]
+    fn test_key_ranges_of_thin_slab() {
+        // 1 層だけの f で X/Y 全域を覆う ID は、正確に分けると列の数だけ区間が要る
+        let z = 20;
+        let f = 1234;
+        let id = SpaceTimeId::new(z, Single(f), Any, Any, 0, Any).unwrap();
+        let whole = 1_u128 << (3 * (z as u32 + 1));
+
+        for curve in CURVES {
+            let ranges = id.key_ranges(curve, 1000);
+            assert!(!ranges.is_empty() && ranges.len() <= 1000);
+            for w in ranges.windows(2) {
+                assert!(w[0].1 + 1 < w[1].0, "{:?}", &ranges[..2]);
+            }
+
+            // 上位集合なので、層のボクセルはどれもいずれかの区間に入る
+            let max = XY_MAX[z as usize];
+            for (x, y) in [(0, 0), (max, max), (12_345, 678_901), (max / 2, 3)] {
+                let voxel = SpaceTimeId::new(z, Single(f), Single(x), Single(y), 0, Any).unwrap();
+                let k = key(&voxel, curve);
+                assert!(ranges.iter().any(|&(s, e)| s <= k && k <= e));
+            }
+
+            // 八分木は区間数の上限まで分割されるので、全域よりずっと狭い
+            let covered: u128 = ranges.iter().map(|&(s, e)| e - s + 1).sum();
+            assert!(covered * 16 <= whole, "{} / {}", covered, whole);
+        }
+
+        // 上限を 1 にすると 1 つの区間にまとまる
+        assert_eq!(id.key_ranges(Curve::Morton, 1).len(), 1);
+        assert_eq!(id.key_ranges(Curve::Morton, 0).len(), 1);
+    }
+}
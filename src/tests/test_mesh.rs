@@ -0,0 +1,255 @@
+use crate::id::DimensionRange::{Any, LimitRange, Single};
+use crate::id::z_range::XY_MAX;
+use crate::id::{SpaceTimeId, coordinates::Point};
+use crate::map::SpaceTimeIdMap;
+use crate::mesh::{Frame, Mesh};
+use crate::set::SpaceTimeIdSet;
+use std::collections::{HashMap, HashSet};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const Z: u8 = 20;
+
+    fn voxel(f: i32, x: u32, y: u32) -> SpaceTimeId {
+        SpaceTimeId::new(Z, Single(f), Single(x), Single(y), 0, Any).unwrap()
+    }
+
+    /// 隣接する ID をまとめずにそのまま持つ集合
+    fn raw_set(ids: &[SpaceTimeId]) -> SpaceTimeIdSet {
+        unsafe { SpaceTimeIdSet::from_hash(ids.iter().copied().collect::<HashSet<_>>()) }
+    }
+
+    fn enu(id: &SpaceTimeId) -> Frame {
+        Frame::Enu {
+            origin: id.center(),
+        }
+    }
+
+    /// 発散定理で求めた符号付き体積 (外向きなら正)
+    fn signed_volume(mesh: &Mesh) -> f64 {
+        mesh.triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                let (a, b, c) = (
+                    mesh.positions[a as usize],
+                    mesh.positions[b as usize],
+                    mesh.positions[c as usize],
+                );
+                let cross = [
+                    b[1] * c[2] - b[2] * c[1],
+                    b[2] * c[0] - b[0] * c[2],
+                    b[0] * c[1] - b[1] * c[0],
+                ];
+                (a[0] * cross[0] + a[1] * cross[1] + a[2] * cross[2]) / 6.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_mesh_single_voxel() {
+        let id = voxel(0, 931_000, 412_000);
+        let mesh = SpaceTimeIdSet::from(id).to_mesh(enu(&id));
+
+        assert_eq!(mesh.positions.len(), 8);
+        assert_eq!(mesh.triangles.len(), 12);
+        assert!(mesh.colors.is_none());
+
+        // z=20 のボクセルは高さ 32 m、幅は緯度に応じて縮む
+        let volume = signed_volume(&mesh);
+        let c = id.coordinates();
+        let width = (c.longitude.1 - c.longitude.0).to_radians()
+            * 6_378_137.0
+            * c.latitude.0.to_radians().cos();
+        assert!(volume > 0.0);
+        assert!((volume / (width * width * 32.0) - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_mesh_range_id_is_closed() {
+        let id = SpaceTimeId::new(
+            Z,
+            LimitRange(0, 1),
+            LimitRange(100, 102),
+            Single(200),
+            0,
+            Any,
+        )
+        .unwrap();
+        let mesh = SpaceTimeIdSet::from(id).to_mesh(Frame::Ecef);
+
+        // 各辺は逆向きの辺とちょうど 1 回ずつ組になる
+        let mut edges: HashMap<(u32, u32), i32> = HashMap::new();
+        for &[a, b, c] in &mesh.triangles {
+            for (s, e) in [(a, b), (b, c), (c, a)] {
+                *edges.entry((s, e)).or_default() += 1;
+            }
+        }
+        for (&(s, e), &count) in &edges {
+            assert_eq!(count, 1);
+            assert_eq!(edges.get(&(e, s)), Some(&1));
+        }
+
+        // 面は格子に沿って分割される: 2 * (3*2 + 3*1 + 2*1) 個の四角形
+        assert_eq!(mesh.triangles.len(), 2 * 2 * (6 + 3 + 2));
+    }
+
+    #[test]
+    fn test_mesh_culls_internal_faces() {
+        let pair = raw_set(&[voxel(0, 100, 200), voxel(0, 101, 200)]);
+        assert_eq!(pair.to_mesh(Frame::Ecef).triangles.len(), 2 * 10);
+
+        let mut cube = Vec::new();
+        for f in 0..3 {
+            for x in 0..3 {
+                for y in 0..3 {
+                    cube.push(voxel(f, 100 + x, 200 + y));
+                }
+            }
+        }
+        let id = voxel(1, 101, 201);
+        let mesh = raw_set(&cube).to_mesh(enu(&id));
+        assert_eq!(mesh.triangles.len(), 2 * 6 * 9);
+        assert!(signed_volume(&mesh) > 0.0);
+
+        // 中心を抜くと内側の 6 面が現れる (体積は減る)
+        cube.retain(|v| *v != id);
+        let hollow = raw_set(&cube).to_mesh(enu(&id));
+        assert_eq!(hollow.triangles.len(), 2 * (6 * 9 + 6));
+        assert!(signed_volume(&hollow) < signed_volume(&mesh));
+    }
+
+    #[test]
+    fn test_mesh_culls_faces_of_range_ids() {
+        // 4 x 4 の範囲 ID の南の面に、1 ボクセルが接する
+        let block = SpaceTimeId::new(
+            Z,
+            Single(0),
+            LimitRange(100, 103),
+            LimitRange(200, 203),
+            0,
+            Any,
+        )
+        .unwrap();
+        let below = voxel(0, 101, 204);
+        let separate = raw_set(&[block]).to_mesh(Frame::Ecef).triangles.len()
+            + raw_set(&[below]).to_mesh(Frame::Ecef).triangles.len();
+
+        // ボクセルの北の面 (2 枚) と、範囲 ID の南の面のうち 1 セル分 (2 枚) が消える
+        let mesh = raw_set(&[block, below]).to_mesh(Frame::Ecef);
+        assert_eq!(mesh.triangles.len(), separate - 4);
+    }
+
+    #[test]
+    fn test_mesh_time_and_antimeridian() {
+        let a = SpaceTimeId::new(Z, Single(0), Single(100), Single(200), 60, Single(1)).unwrap();
+        let b = SpaceTimeId::new(Z, Single(0), Single(101), Single(200), 60, Single(2)).unwrap();
+        assert_eq!(
+            raw_set(&[a, b]).to_mesh(Frame::Ecef).triangles.len(),
+            2 * 12
+        );
+
+        let max = XY_MAX[Z as usize];
+        let wrap = raw_set(&[voxel(0, 0, 200), voxel(0, max, 200)]);
+        assert_eq!(wrap.to_mesh(Frame::Ecef).triangles.len(), 2 * 10);
+    }
+
+    #[test]
+    fn test_mesh_colors() {
+        let set = raw_set(&[voxel(0, 100, 200), voxel(5, 100, 200)]);
+        let mesh = set.to_mesh_with_colors(Frame::Ecef, |id| {
+            if id.f() == Single(0) {
+                [255, 0, 0, 255]
+            } else {
+                [0, 0, 255, 255]
+            }
+        });
+
+        let colors = mesh.colors.as_ref().unwrap();
+        assert_eq!(colors.len(), mesh.positions.len());
+        assert!(colors.contains(&[255, 0, 0, 255]));
+        assert!(colors.contains(&[0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn test_mesh_map_colors() {
+        let mut counts: SpaceTimeIdMap<u32> = SpaceTimeIdMap::new();
+        counts.insert(voxel(0, 100, 200), 7).unwrap();
+
+        let set = raw_set(&[voxel(0, 100, 200), voxel(5, 100, 200)]);
+        let mesh = set.to_mesh_with_map(Frame::Ecef, &counts, |count| match count {
+            Some(&count) => [count as u8, 0, 0, 255],
+            None => [0, 0, 0, 0],
+        });
+
+        let colors = mesh.colors.as_ref().unwrap();
+        assert_eq!(colors.len(), mesh.positions.len());
+        assert_eq!(colors.iter().filter(|&&c| c == [7, 0, 0, 255]).count(), 8);
+        assert_eq!(colors.iter().filter(|&&c| c == [0, 0, 0, 0]).count(), 8);
+    }
+
+    #[test]
+    fn test_mesh_text_formats() {
+        let id = voxel(0, 100, 200);
+        let mesh = SpaceTimeIdSet::from(id).to_mesh(enu(&id));
+
+        let obj = mesh.to_obj();
+        assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), 8);
+        assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 12);
+        // OBJ の頂点番号は 1 始まり
+        assert!(
+            obj.lines()
+                .filter(|l| l.starts_with("f "))
+                .all(|l| l.split_whitespace().skip(1).all(|v| v != "0"))
+        );
+
+        let ply = mesh.to_ply();
+        assert!(ply.starts_with("ply\nformat ascii 1.0\n"));
+        assert!(ply.contains("element vertex 8\n"));
+        assert!(ply.contains("element face 12\n"));
+        let body: Vec<&str> = ply.split("end_header\n").nth(1).unwrap().lines().collect();
+        assert_eq!(body.len(), 8 + 12);
+    }
+
+    #[test]
+    fn test_mesh_glb() {
+        let id = voxel(0, 100, 200);
+        let origin = Point {
+            latitude: 0.0,
+            longitude: 0.0,
+            altitude: 0.0,
+        };
+        let mesh =
+            SpaceTimeIdSet::from(id).to_mesh_with_colors(Frame::Enu { origin }, |_| [1, 2, 3, 4]);
+        let glb = mesh.to_glb();
+
+        let word = |k: usize| u32::from_le_bytes(glb[k..k + 4].try_into().unwrap());
+        assert_eq!(&glb[0..4], b"glTF");
+        assert_eq!(word(4), 2);
+        assert_eq!(word(8) as usize, glb.len());
+
+        let json_length = word(12) as usize;
+        assert_eq!(&glb[16..20], b"JSON");
+        let document: serde_json::Value =
+            serde_json::from_slice(&glb[20..20 + json_length]).unwrap();
+        assert_eq!(document["accessors"].as_array().unwrap().len(), 3);
+        assert_eq!(document["accessors"][0]["count"], 8);
+        assert_eq!(document["accessors"][2]["count"], 36);
+        assert!(document["nodes"][0]["rotation"].is_array());
+
+        let bin_start = 20 + json_length;
+        let bin_length = word(bin_start) as usize;
+        assert_eq!(&glb[bin_start + 4..bin_start + 8], b"BIN\0");
+        assert_eq!(bin_length, 8 * 12 + 8 * 4 + 36 * 4);
+        assert_eq!(document["buffers"][0]["byteLength"], bin_length);
+    }
+
+    #[test]
+    fn test_mesh_empty_set() {
+        let mesh = SpaceTimeIdSet::new().to_mesh(Frame::Ecef);
+        assert!(mesh.positions.is_empty());
+        assert!(mesh.triangles.is_empty());
+        assert!(mesh.to_glb().len() > 20);
+    }
+}
@@ -0,0 +1,216 @@
+use crate::function::tools::{point_to_ecef::point_to_ecef, point_to_id::point_to_id};
+use crate::id::DimensionRange::{Any, LimitRange, Single};
+use crate::id::{SpaceTimeId, coordinates::Point};
+use crate::set::SpaceTimeIdSet;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(latitude: f64, longitude: f64, altitude: f64) -> Point {
+        Point {
+            latitude,
+            longitude,
+            altitude,
+        }
+    }
+
+    fn distance(a: Point, b: Point) -> f64 {
+        let a = point_to_ecef(a);
+        let b = point_to_ecef(b);
+        ((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+    }
+
+    /// ID の表面を細かく標本化して求めた距離の最小値
+    fn sampled_distance(id: &SpaceTimeId, p: Point) -> f64 {
+        let c = id.coordinates();
+        let steps = 40;
+        let lerp = |(a, b): (f64, f64), k: usize| a + (b - a) * k as f64 / steps as f64;
+        let mut best = f64::MAX;
+        for i in 0..=steps {
+            for j in 0..=steps {
+                for k in 0..=steps {
+                    let q = point(
+                        lerp(c.latitude, i),
+                        lerp(c.longitude, j),
+                        lerp(c.altitude, k),
+                    );
+                    best = best.min(distance(p, q));
+                }
+            }
+        }
+        best
+    }
+
+    fn tokyo_block() -> SpaceTimeId {
+        let center = point_to_id(18, point(35.68, 139.76, 0.0)).unwrap();
+        let (x, _) = center.x_bounds();
+        let (y, _) = center.y_bounds();
+        SpaceTimeId::new(
+            18,
+            LimitRange(0, 2),
+            LimitRange(x, x + 3),
+            LimitRange(y, y + 1),
+            0,
+            Any,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_nearest_inside_is_zero() {
+        let id = tokyo_block();
+        let set = SpaceTimeIdSet::from(id);
+        let (found, d) = set.nearest(id.center(), None).unwrap();
+        assert_eq!(found, id);
+        assert_eq!(d, 0.0);
+    }
+
+    #[test]
+    fn test_nearest_matches_sampled_distance() {
+        let id = tokyo_block();
+        let set = SpaceTimeIdSet::from(id);
+        let c = id.center();
+
+        for p in [
+            point(c.latitude + 0.01, c.longitude, c.altitude),
+            point(c.latitude, c.longitude - 0.02, 500.0),
+            point(c.latitude - 0.003, c.longitude + 0.004, -200.0),
+            point(c.latitude, c.longitude, 1_000.0),
+        ] {
+            let (_, d) = set.nearest(p, None).unwrap();
+            let sampled = sampled_distance(&id, p);
+            // 標本点は表面上の点なので、正確な距離はそれ以下で、標本間隔ほどしか離れない
+            assert!(d <= sampled + 1e-6, "{} > {}", d, sampled);
+            assert!(sampled - d < 2.0, "{} vs {}", d, sampled);
+        }
+    }
+
+    #[test]
+    fn test_nearest_is_exact_for_large_ids() {
+        // 緯度 66.5°〜85°, 経度 -90°〜0°, 高度 0〜8 km の大きな ID
+        let id = SpaceTimeId::new(2, LimitRange(0, 0), Single(1), Single(0), 0, Any).unwrap();
+        let set = SpaceTimeIdSet::from(id);
+        let c = id.coordinates();
+
+        // 上面の法線上の点までの距離は、上面からの高さそのもの
+        let (_, d) = set
+            .nearest(point(75.0, -45.0, c.altitude.1 + 1_000_000.0), None)
+            .unwrap();
+        assert!((d - 1_000_000.0).abs() < 1e-6, "{}", d);
+        assert_eq!(set.nearest(point(75.0, -45.0, 10.0), None).unwrap().1, 0.0);
+
+        // 経度が 90° 以上離れた点や、地軸に近い点
+        for p in [
+            point(80.0, 100.0, 0.0),
+            point(89.0, 170.0, 5_000.0),
+            point(-30.0, -45.0, 10_000.0),
+            point(70.0, 30.0, -3_000.0),
+        ] {
+            let (_, d) = set.nearest(p, None).unwrap();
+            let sampled = sampled_distance(&id, p);
+            assert!(d <= sampled + 1e-6, "{} > {}", d, sampled);
+            // 標本の間隔は緯度方向で約 50 km
+            assert!(sampled - d < 60_000.0, "{} vs {}", d, sampled);
+        }
+    }
+
+    #[test]
+    fn test_nearest_above_column() {
+        let id = tokyo_block();
+        let set = SpaceTimeIdSet::from(id);
+        let c = id.center();
+        let top = id.coordinates().altitude.1;
+
+        let (_, d) = set
+            .nearest(point(c.latitude, c.longitude, top + 100.0), None)
+            .unwrap();
+        assert!((d - 100.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_nearest_wraps_antimeridian() {
+        let id = SpaceTimeId::new(16, Single(0), Single(0), Single(30_000), 0, Any).unwrap();
+        let set = SpaceTimeIdSet::from(id);
+        let c = id.center();
+
+        let (_, d) = set
+            .nearest(point(c.latitude, 179.9999, c.altitude), None)
+            .unwrap();
+        assert!(d < 20.0, "{}", d);
+    }
+
+    #[test]
+    fn test_nearest_respects_time() {
+        let near =
+            SpaceTimeId::new(18, Single(0), Single(100), Single(100), 60, Single(5)).unwrap();
+        let far = SpaceTimeId::new(18, Single(0), Single(110), Single(100), 0, Any).unwrap();
+        let set: SpaceTimeIdSet = [near, far].into_iter().collect();
+        let p = near.center();
+
+        assert_eq!(set.nearest(p, None).unwrap().0, near);
+        assert_eq!(set.nearest(p, Some(330)).unwrap().0, near);
+        assert_eq!(set.nearest(p, Some(0)).unwrap().0, far);
+        assert!(SpaceTimeIdSet::new().nearest(p, None).is_none());
+    }
+
+    #[test]
+    fn test_k_nearest_sorted() {
+        let ids: Vec<SpaceTimeId> = (0..10)
+            .map(|k| {
+                SpaceTimeId::new(18, Single(0), Single(100 + 3 * k), Single(100), 0, Any).unwrap()
+            })
+            .collect();
+        let set: SpaceTimeIdSet = ids.iter().copied().collect();
+        let p = ids[0].center();
+
+        let result = set.k_nearest(p, 4);
+        assert_eq!(result.len(), 4);
+        assert_eq!(
+            result.iter().map(|r| r.0).collect::<Vec<_>>(),
+            ids[..4].to_vec()
+        );
+        assert!(result.windows(2).all(|w| w[0].1 <= w[1].1));
+
+        assert_eq!(set.k_nearest(p, 100).len(), 10);
+        assert!(set.k_nearest(p, 0).is_empty());
+    }
+
+    #[test]
+    fn test_pruned_search_matches_full_scan() {
+        // 大きさも高さも異なる範囲 ID を並べる
+        let mut ids = Vec::new();
+        for i in 0..8_u32 {
+            for j in 0..8_u32 {
+                let x = 58_000 + 10 * i;
+                let y = 25_800 + 10 * j;
+                let size = (i + j) % 4;
+                let f = ((i * j) % 5) as i32;
+                ids.push(
+                    SpaceTimeId::new(
+                        16,
+                        LimitRange(f, f + size as i32),
+                        LimitRange(x, x + size),
+                        LimitRange(y, y + size),
+                        0,
+                        Any,
+                    )
+                    .unwrap(),
+                );
+            }
+        }
+        let set = SpaceTimeIdSet::from_disjoint(ids.clone());
+
+        for p in [
+            point(35.0, 139.0, 50.0),
+            point(35.3, 139.2, 3_000.0),
+            point(-10.0, 20.0, 0.0),
+        ] {
+            // k が全体の数なら枝刈りは起きない
+            let full = set.k_nearest(p, ids.len());
+            assert_eq!(full.len(), ids.len());
+            assert_eq!(set.k_nearest(p, 5), full[..5].to_vec());
+            assert_eq!(set.nearest(p, None), Some(full[0]));
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::id::DimensionRange::{
+    self, AfterUnLimitRange, Any, BeforeUnLimitRange, LimitRange, Single,
+};
+use crate::id::SpaceTimeId;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dimension_range() {
+        assert_eq!("5".parse::<DimensionRange<u32>>(), Ok(Single(5)));
+        assert_eq!("-3:7".parse::<DimensionRange<i32>>(), Ok(LimitRange(-3, 7)));
+        assert_eq!(
+            "-:-3".parse::<DimensionRange<i32>>(),
+            Ok(BeforeUnLimitRange(-3))
+        );
+        assert_eq!(
+            "-3:-".parse::<DimensionRange<i32>>(),
+            Ok(AfterUnLimitRange(-3))
+        );
+        assert_eq!("-".parse::<DimensionRange<u32>>(), Ok(Any));
+
+        assert!("-:-".parse::<DimensionRange<u32>>().is_err());
+        assert!("-1".parse::<DimensionRange<u32>>().is_err());
+        assert!("1:2:3".parse::<DimensionRange<u32>>().is_err());
+        assert!("".parse::<DimensionRange<u32>>().is_err());
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let ids = [
+            SpaceTimeId::new(
+                4,
+                AfterUnLimitRange(10),
+                Single(5),
+                Single(3),
+                60,
+                LimitRange(100, 200),
+            )
+            .unwrap(),
+            SpaceTimeId::new(
+                20,
+                Single(-7),
+                LimitRange(931_000, 931_010),
+                BeforeUnLimitRange(5),
+                0,
+                Any,
+            )
+            .unwrap(),
+            SpaceTimeId::new(0, Any, Any, Any, 1, AfterUnLimitRange(3)).unwrap(),
+        ];
+        for id in ids {
+            assert_eq!(id.to_string().parse::<SpaceTimeId>(), Ok(id));
+        }
+        assert_eq!(
+            "4/10:-/5/3_60/100:200"
+                .parse::<SpaceTimeId>()
+                .unwrap()
+                .to_string(),
+            "4/10:-/5/3_60/100:200"
+        );
+    }
+
+    #[test]
+    fn test_parse_spatial_shorthand() {
+        let id: SpaceTimeId = "4/10/5/3".parse().unwrap();
+        assert_eq!(
+            id,
+            SpaceTimeId::new(4, Single(10), Single(5), Single(3), 0, Any).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for s in [
+            "",
+            "4/10/5",
+            "4/10/5/3/1",
+            "x/1/1/1",
+            "4/10/5/3_60",
+            "4/10/5/3_a/1",
+            "4/10/16/3",
+            "4/1/1/1_0/5",
+            "32/0/0/0",
+        ] {
+            assert!(s.parse::<SpaceTimeId>().is_err(), "{}", s);
+        }
+    }
+}
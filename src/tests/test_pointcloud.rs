@@ -0,0 +1,188 @@
+use crate::function::tools::{point_to_ecef::point_to_ecef, point_to_id::point_to_id};
+use crate::id::DimensionRange::{Any, Single};
+use crate::id::SpaceTimeId;
+use crate::id::coordinates::Point;
+use crate::map::SpaceTimeIdMap;
+use crate::pointcloud::ply::PlyReader;
+use crate::pointcloud::xyz::XyzReader;
+use crate::pointcloud::{PointLayout, occupied_voxels, voxel_counts};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voxel(z: u8, f: i32, x: u32, y: u32) -> SpaceTimeId {
+        SpaceTimeId::new(z, Single(f), Single(x), Single(y), 0, Any).unwrap()
+    }
+
+    #[test]
+    fn test_map_insert_get() {
+        let mut map = SpaceTimeIdMap::<u32>::new();
+        assert!(map.is_empty());
+        assert_eq!(map.insert(voxel(3, 2, 5, 1), 7), Ok(None));
+        assert_eq!(map.insert(voxel(3, -3, 0, 7), 1), Ok(None));
+        assert_eq!(map.insert(voxel(3, 2, 5, 1), 8), Ok(Some(7)));
+
+        assert_eq!(map.get(&voxel(3, 2, 5, 1)), Some(&8));
+        assert_eq!(map.get(&voxel(3, -3, 0, 7)), Some(&1));
+        assert_eq!(map.get(&voxel(3, 2, 5, 0)), None);
+        assert_eq!(map.get(&voxel(2, 1, 2, 0)), None);
+        assert_eq!(map.len(), 2);
+
+        let entries: Vec<(SpaceTimeId, u32)> = map.iter().map(|(id, &v)| (id, v)).collect();
+        assert_eq!(
+            entries,
+            vec![(voxel(3, -3, 0, 7), 1), (voxel(3, 2, 5, 1), 8)]
+        );
+
+        // z の異なるボクセルとの重なり
+        assert!(map.insert(voxel(2, 1, 2, 0), 1).is_err());
+        assert!(map.insert(voxel(4, 4, 10, 2), 1).is_err());
+        assert!(map.insert(voxel(2, 1, 2, 1), 1).is_ok());
+        let range = SpaceTimeId::new(3, Single(0), Any, Single(0), 0, Any).unwrap();
+        assert!(map.insert(range, 1).is_err());
+    }
+
+    #[test]
+    fn test_xyz_reader() {
+        let text =
+            "x,y,z\n# comment\n35.0, 139.0, 10.0\n\n35.1 139.1 11 255\nbad line\n35.2;139.2;12\n";
+        let points: Vec<_> = XyzReader::new(text.as_bytes(), PointLayout::LatLonAlt).collect();
+        assert_eq!(points.len(), 4);
+        let p = points[1].as_ref().unwrap();
+        assert_eq!((p.latitude, p.longitude, p.altitude), (35.1, 139.1, 11.0));
+        assert!(points[2].as_ref().unwrap_err().starts_with("line 6:"));
+        assert_eq!(points[3].as_ref().unwrap().altitude, 12.0);
+
+        let swapped: Vec<_> = XyzReader::new("139.0 35.0 10.0".as_bytes(), PointLayout::LonLatAlt)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(swapped[0].latitude, 35.0);
+        assert_eq!(swapped[0].longitude, 139.0);
+    }
+
+    #[test]
+    fn test_ply_ascii() {
+        let text = "ply
+format ascii 1.0
+comment scan
+element camera 1
+property float focal
+element vertex 2
+property float x
+property float y
+property float z
+property uchar red
+element face 1
+property list uchar int vertex_indices
+end_header
+50.0
+35.5 139.5 3.0 255
+35.6 139.6 4.0 0
+3 0 1 1
+";
+        let points: Vec<Point> = PlyReader::new(text.as_bytes(), PointLayout::LatLonAlt)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[1].latitude, 35.6);
+        assert_eq!(points[1].altitude, 4.0);
+
+        assert!(PlyReader::new("obj\n".as_bytes(), PointLayout::LatLonAlt).is_err());
+        let missing_z = "ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nproperty float y\nend_header\n1 2\n";
+        assert!(PlyReader::new(missing_z.as_bytes(), PointLayout::LatLonAlt).is_err());
+    }
+
+    #[test]
+    fn test_ply_binary_ecef() {
+        let point = Point {
+            latitude: 35.68,
+            longitude: 139.76,
+            altitude: 40.0,
+        };
+        let ecef = point_to_ecef(point);
+
+        for (format, big_endian) in [("binary_little_endian", false), ("binary_big_endian", true)] {
+            let mut bytes = format!(
+                "ply\nformat {} 1.0\nelement vertex 2\nproperty uchar intensity\nproperty double x\nproperty double y\nproperty double z\nend_header\n",
+                format
+            )
+            .into_bytes();
+            for _ in 0..2 {
+                bytes.push(9);
+                for v in [ecef.x, ecef.y, ecef.z] {
+                    if big_endian {
+                        bytes.extend(v.to_be_bytes());
+                    } else {
+                        bytes.extend(v.to_le_bytes());
+                    }
+                }
+            }
+
+            let points: Vec<Point> = PlyReader::new(bytes.as_slice(), PointLayout::Ecef)
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(points.len(), 2);
+            assert!((points[0].latitude - point.latitude).abs() < 1e-9);
+            assert!((points[0].longitude - point.longitude).abs() < 1e-9);
+            assert!((points[0].altitude - point.altitude).abs() < 1e-3);
+
+            // 途中で切れたファイル
+            let truncated = &bytes[..bytes.len() - 4];
+            let result: Result<Vec<Point>, String> = PlyReader::new(truncated, PointLayout::Ecef)
+                .unwrap()
+                .collect();
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn test_voxel_counts() {
+        let text = "35.680 139.760 10\n35.680 139.760 10.5\n35.680 139.760 10.9\n40.0 140.0 0\n";
+        let points = || XyzReader::new(text.as_bytes(), PointLayout::LatLonAlt);
+        let dense = point_to_id(
+            20,
+            Point {
+                latitude: 35.68,
+                longitude: 139.76,
+                altitude: 10.0,
+            },
+        )
+        .unwrap();
+
+        let report = voxel_counts(20, points(), 1).unwrap();
+        assert_eq!(report.counts.len(), 2);
+        assert_eq!(report.counts.get(&dense), Some(&3));
+        assert_eq!(report.counted, 4);
+        assert!(report.errors.is_empty());
+
+        let filtered = voxel_counts(20, points(), 2).unwrap();
+        assert_eq!(filtered.counts.len(), 1);
+        assert_eq!(filtered.counted, 4);
+
+        let report = occupied_voxels(20, points(), 2).unwrap();
+        assert_eq!(report.set.iter().collect::<Vec<_>>(), vec![&dense]);
+
+        assert!(voxel_counts(32, points(), 1).is_err());
+    }
+
+    #[test]
+    fn test_voxel_counts_skips_bad_points() {
+        // 極域の点と読めない行は飛ばして、残りを数える
+        let text = "89.0 0.0 0.0\n35.680 139.760 10\nbad line\n35.680 139.760 10.5\n";
+        let report = occupied_voxels(
+            20,
+            XyzReader::new(text.as_bytes(), PointLayout::LatLonAlt),
+            2,
+        )
+        .unwrap();
+        assert_eq!(report.set.iter().count(), 1);
+        assert_eq!(report.counted, 2);
+
+        let indices: Vec<usize> = report.errors.iter().map(|e| e.index).collect();
+        assert_eq!(indices, vec![0, 2]);
+        assert!(report.errors[1].message.starts_with("line 3:"));
+    }
+}
@@ -0,0 +1,79 @@
+use crate::function::tools::point_to_id::{MAX_LATITUDE, point_to_id};
+use crate::function::tools::points_to_ids::{points_to_ids, points_to_set};
+use crate::id::DimensionRange::Single;
+use crate::id::coordinates::Point;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(latitude: f64, longitude: f64, altitude: f64) -> Point {
+        Point {
+            latitude,
+            longitude,
+            altitude,
+        }
+    }
+
+    #[test]
+    fn test_point_to_id_errors() {
+        let p = point(35.68, 139.76, 12.0);
+        assert!(point_to_id(20, p).is_ok());
+
+        assert!(point_to_id(20, point(86.0, 0.0, 0.0)).is_err());
+        assert!(point_to_id(20, point(-90.0, 0.0, 0.0)).is_err());
+        assert!(point_to_id(20, point(f64::NAN, 0.0, 0.0)).is_err());
+        assert!(point_to_id(20, point(0.0, f64::INFINITY, 0.0)).is_err());
+        assert!(point_to_id(32, p).is_err());
+        assert!(point_to_id(0, point(0.0, 0.0, 1e9)).is_err());
+
+        // 上限の緯度ちょうどは端のボクセルに入る
+        let north = point_to_id(10, point(MAX_LATITUDE, 0.0, 0.0)).unwrap();
+        assert_eq!(north.y(), Single(0));
+        let south = point_to_id(10, point(-MAX_LATITUDE, 0.0, 0.0)).unwrap();
+        assert_eq!(south.y(), Single(1023));
+    }
+
+    #[test]
+    fn test_points_to_ids() {
+        let points = [
+            point(35.68, 139.76, 12.0),
+            point(89.0, 139.76, 12.0),
+            point(-33.86, 151.21, 5.0),
+        ];
+        let ids = points_to_ids(18, &points);
+        assert_eq!(ids.len(), 3);
+        assert_eq!(ids[0], point_to_id(18, points[0]));
+        assert!(ids[1].is_err());
+        assert_eq!(ids[2], point_to_id(18, points[2]));
+    }
+
+    #[test]
+    fn test_points_to_set() {
+        let points: Vec<Point> = (0..1000)
+            .map(|k| point(35.68 + (k % 10) as f64 * 1e-7, 139.76, 12.0))
+            .collect();
+        let (set, errors) = points_to_set(18, &points);
+        assert!(errors.is_empty());
+        assert_eq!(set.iter().count(), 1);
+        assert_eq!(
+            set.iter().next(),
+            Some(&point_to_id(18, points[0]).unwrap())
+        );
+
+        // 範囲外の点は飛ばして、番号とエラーを入力の順に返す
+        let mixed = [
+            point(35.68, 139.76, 0.0),
+            point(-88.0, 0.0, 0.0),
+            point(35.0, 135.0, 0.0),
+            point(f64::NAN, 0.0, 0.0),
+        ];
+        let (set, errors) = points_to_set(18, &mixed);
+        assert_eq!(set.iter().count(), 2);
+        assert_eq!(
+            errors.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+        assert_eq!(Err(errors[0].1.clone()), point_to_id(18, mixed[1]));
+    }
+}
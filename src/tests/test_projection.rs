@@ -0,0 +1,91 @@
+use crate::function::tools::projection::{Hemisphere, Projected, TransverseMercator};
+use crate::id::coordinates::Point;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(latitude: f64, longitude: f64) -> Point {
+        Point {
+            latitude,
+            longitude,
+            altitude: 12.5,
+        }
+    }
+
+    #[test]
+    fn test_jgd2011_zone_ix() {
+        let zone = TransverseMercator::jgd2011(9).unwrap();
+
+        // 国土地理院の計算例 (つくば)
+        let p = zone.project(point(36.103774791666666, 140.08785504166664));
+        assert!((p.northing - 11543.6883).abs() < 1e-3, "{:?}", p);
+        assert!((p.easting - 22916.2436).abs() < 1e-3, "{:?}", p);
+        assert_eq!(p.altitude, 12.5);
+
+        // 原点は (0, 0)
+        let origin = zone.project(point(36.0, 139.0 + 50.0 / 60.0));
+        assert!(origin.northing.abs() < 1e-6 && origin.easting.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_utm() {
+        // CN Tower
+        let zone = TransverseMercator::utm_zone(-79.387139);
+        assert_eq!(zone, 17);
+        let utm = TransverseMercator::utm(zone, Hemisphere::North).unwrap();
+        let p = utm.project(point(43.642567, -79.387139));
+        assert!((p.easting - 630084.0).abs() < 1.0, "{:?}", p);
+        assert!((p.northing - 4833439.0).abs() < 1.0, "{:?}", p);
+
+        let south = TransverseMercator::utm(31, Hemisphere::South).unwrap();
+        let p = south.project(point(0.0, 3.0));
+        assert!((p.easting - 500000.0).abs() < 1e-6);
+        assert!((p.northing - 10_000_000.0).abs() < 1e-6);
+
+        assert_eq!(TransverseMercator::utm_zone(180.0), 1);
+        assert_eq!(TransverseMercator::utm_zone(179.9), 60);
+        assert!(TransverseMercator::utm(0, Hemisphere::North).is_err());
+        assert!(TransverseMercator::utm(61, Hemisphere::North).is_err());
+        assert!(TransverseMercator::jgd2011(0).is_err());
+        assert!(TransverseMercator::jgd2011(20).is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let cases = [
+            (TransverseMercator::jgd2011(1).unwrap(), point(33.5, 130.2)),
+            (
+                TransverseMercator::jgd2011(11).unwrap(),
+                point(43.06, 141.35),
+            ),
+            (
+                TransverseMercator::jgd2011(19).unwrap(),
+                point(24.28, 153.98),
+            ),
+            (
+                TransverseMercator::utm(54, Hemisphere::North).unwrap(),
+                point(35.681236, 139.767125),
+            ),
+            (
+                TransverseMercator::utm(56, Hemisphere::South).unwrap(),
+                point(-33.8568, 151.2153),
+            ),
+        ];
+        for (projection, p) in cases {
+            let back = projection.unproject(projection.project(p));
+            assert!((back.latitude - p.latitude).abs() < 1e-9, "{:?}", back);
+            assert!((back.longitude - p.longitude).abs() < 1e-9, "{:?}", back);
+            assert_eq!(back.altitude, p.altitude);
+        }
+
+        let zone = TransverseMercator::jgd2011(9).unwrap();
+        let back = zone.unproject(Projected {
+            easting: 22916.2436,
+            northing: 11543.6883,
+            altitude: 0.0,
+        });
+        assert!((back.latitude - 36.103774791666666).abs() < 1e-7);
+        assert!((back.longitude - 140.08785504166664).abs() < 1e-7);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::function::{
+    line::line,
+    tools::{point_to_ecef::point_to_ecef, point_to_id::point_to_id},
+    traversal::traverse,
+};
+use crate::id::DimensionRange::{Any, LimitRange, Single};
+use crate::id::{SpaceTimeId, coordinates::Point};
+use crate::set::SpaceTimeIdSet;
+use std::collections::HashSet;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const Z: u8 = 20;
+
+    fn point(latitude: f64, longitude: f64, altitude: f64) -> Point {
+        Point {
+            latitude,
+            longitude,
+            altitude,
+        }
+    }
+
+    fn distance(a: Point, b: Point) -> f64 {
+        let a = point_to_ecef(a);
+        let b = point_to_ecef(b);
+        ((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+    }
+
+    /// 東西方向に並んだ 2 点の間に南北に伸びる壁を置く
+    fn wall(t: Option<(u32, u32)>) -> (Point, Point, SpaceTimeId) {
+        let a = point(35.68, 139.760, 50.0);
+        let b = point(35.68, 139.770, 50.0);
+        let center = point_to_id(Z, point(35.68, 139.765, 50.0)).unwrap();
+        let (x, _) = center.x_bounds();
+        let (y, _) = center.y_bounds();
+        let (i, t) = match t {
+            Some((s, e)) => (60, LimitRange(s, e)),
+            None => (0, Any),
+        };
+        let id = SpaceTimeId::new(
+            Z,
+            LimitRange(0, 10),
+            Single(x),
+            LimitRange(y - 5, y + 5),
+            i,
+            t,
+        )
+        .unwrap();
+        (a, b, id)
+    }
+
+    #[test]
+    fn test_traverse_steps_are_contiguous() {
+        let a = point(35.68, 139.76, 10.0);
+        let b = point(35.681, 139.762, 40.0);
+        let steps: Vec<_> = traverse(Z, a, b).collect();
+
+        assert_eq!(steps.first().unwrap().id, point_to_id(Z, a).unwrap());
+        assert_eq!(steps.last().unwrap().id, point_to_id(Z, b).unwrap());
+        assert!(steps[0].enter == 0.0);
+        assert!((steps.last().unwrap().exit - distance(a, b)).abs() < 1e-6);
+
+        for pair in steps.windows(2) {
+            assert!((pair[0].exit - pair[1].enter).abs() < 1e-9);
+            assert!(pair[0].enter <= pair[0].exit);
+            assert_ne!(pair[0].id, pair[1].id);
+        }
+    }
+
+    #[test]
+    fn test_traverse_covers_sampled_line() {
+        let a = point(35.68, 139.76, 10.0);
+        let b = point(35.6805, 139.7607, 25.0);
+        let traversed: HashSet<SpaceTimeId> = traverse(Z, a, b).map(|step| step.id).collect();
+        let sampled: HashSet<SpaceTimeId> = line(Z, a, b).pure().into_iter().collect();
+
+        assert!(sampled.is_subset(&traversed));
+    }
+
+    #[test]
+    fn test_traverse_degenerate_segment() {
+        let a = point(35.68, 139.76, 10.0);
+        assert_eq!(traverse(Z, a, a).count(), 0);
+    }
+
+    #[test]
+    fn test_raycast_hits_wall() {
+        let (a, b, id) = wall(None);
+        let set = SpaceTimeIdSet::from(id);
+
+        let (hit, d) = set.raycast(a, b, 2_000.0).unwrap();
+        assert_eq!(hit, id);
+
+        // 壁の西側の面までの距離
+        let west = id.coordinates().longitude.0;
+        let expected = distance(a, point(35.68, west, 50.0));
+        assert!((d - expected).abs() < 0.1, "{} vs {}", d, expected);
+    }
+
+    #[test]
+    fn test_raycast_stops_at_max_distance() {
+        let (a, b, id) = wall(None);
+        let set = SpaceTimeIdSet::from(id);
+
+        assert!(set.raycast(a, b, 100.0).is_none());
+        assert!(set.raycast(b, a, 2_000.0).is_some());
+        assert!(set.raycast(a, b, -1.0).is_none());
+    }
+
+    #[test]
+    fn test_raycast_origin_inside_obstacle() {
+        let (_, b, id) = wall(None);
+        let inside = id.center();
+        let set = SpaceTimeIdSet::from(id);
+
+        let (hit, d) = set.raycast(inside, b, 1_000.0).unwrap();
+        assert_eq!(hit, id);
+        assert_eq!(d, 0.0);
+    }
+
+    #[test]
+    fn test_line_of_sight() {
+        let (a, b, id) = wall(None);
+        let set = SpaceTimeIdSet::from(id);
+
+        assert!(!set.has_line_of_sight(a, b, None));
+
+        // 壁 (高さ 11 × 32 m) より高い位置なら見通せる
+        let high_a = point(a.latitude, a.longitude, 500.0);
+        let high_b = point(b.latitude, b.longitude, 500.0);
+        assert!(set.has_line_of_sight(high_a, high_b, None));
+        assert!(SpaceTimeIdSet::new().has_line_of_sight(a, b, None));
+    }
+
+    #[test]
+    fn test_line_of_sight_at_time() {
+        let (a, b, id) = wall(Some((10, 19)));
+        let set = SpaceTimeIdSet::from(id);
+
+        assert!(!set.has_line_of_sight(a, b, None));
+        assert!(!set.has_line_of_sight(a, b, Some(600)));
+        assert!(!set.has_line_of_sight(a, b, Some(1_199)));
+        assert!(set.has_line_of_sight(a, b, Some(599)));
+        assert!(set.has_line_of_sight(a, b, Some(1_200)));
+    }
+}
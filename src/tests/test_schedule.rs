@@ -0,0 +1,187 @@
+use crate::id::DimensionRange::{Any, LimitRange, Single};
+use crate::id::SpaceTimeId;
+use crate::id::time::TimeAxis;
+use crate::schedule::{Frequency, Schedule};
+use crate::set::SpaceTimeIdSet;
+use chrono::{DateTime, FixedOffset, TimeDelta, TimeZone, Utc, Weekday};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jst(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(9 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(y, mo, d, h, mi, 0)
+            .unwrap()
+    }
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    #[test]
+    fn test_weekdays_office_hours() {
+        // 2025-01-06 は月曜日
+        let schedule = Schedule::from_rrule(
+            "RRULE:FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR",
+            jst(2025, 1, 6, 9, 0),
+            TimeDelta::hours(8),
+        )
+        .unwrap();
+        let windows = schedule
+            .windows(utc(2025, 1, 5, 0, 0), utc(2025, 1, 13, 0, 0))
+            .unwrap();
+        let expected: Vec<_> = (6..=10)
+            .map(|d| {
+                (
+                    jst(2025, 1, d, 9, 0).to_utc(),
+                    jst(2025, 1, d, 17, 0).to_utc(),
+                )
+            })
+            .collect();
+        assert_eq!(windows, expected);
+
+        // 09:00 JST = 00:00 UTC なので、8 時間の i で 1 日 1 つの ID になる
+        let set = schedule
+            .to_set(utc(2025, 1, 5, 0, 0), utc(2025, 1, 13, 0, 0))
+            .unwrap();
+        assert_eq!(set.iter().count(), 5);
+        for id in set.iter() {
+            assert_eq!(id.i(), 8 * 3600);
+            assert!(matches!(id.t(), Single(_)));
+            let (start, end) = id.time_window(&TimeAxis::default()).unwrap();
+            assert!(expected.contains(&(start, end.unwrap())));
+        }
+    }
+
+    #[test]
+    fn test_hourly_ten_minutes() {
+        let schedule = Schedule::new(
+            Frequency::Hourly,
+            jst(2025, 3, 1, 0, 0),
+            TimeDelta::minutes(10),
+        );
+        let set = schedule
+            .to_set(utc(2025, 3, 1, 0, 0), utc(2025, 3, 2, 0, 0))
+            .unwrap();
+        assert_eq!(set.iter().count(), 24);
+        assert!(set.iter().all(|id| id.i() == 600));
+    }
+
+    #[test]
+    fn test_interval_until_and_by_hour() {
+        let schedule = Schedule::from_rrule(
+            "FREQ=DAILY;INTERVAL=2;BYHOUR=9,13;UNTIL=20250105",
+            jst(2025, 1, 1, 9, 30),
+            TimeDelta::hours(1),
+        )
+        .unwrap();
+        let starts: Vec<_> = schedule
+            .windows(utc(2024, 12, 1, 0, 0), utc(2025, 2, 1, 0, 0))
+            .unwrap()
+            .into_iter()
+            .map(|(start, _)| start)
+            .collect();
+        let expected: Vec<_> = [(1, 9), (1, 13), (3, 9), (3, 13), (5, 9), (5, 13)]
+            .iter()
+            .map(|&(d, h)| jst(2025, 1, d, h, 30).to_utc())
+            .collect();
+        assert_eq!(starts, expected);
+
+        // BYDAY で曜日を絞る (2025-01-03 は金曜日)
+        let mut fridays = schedule.clone();
+        fridays.by_day = vec![Weekday::Fri];
+        let windows = fridays
+            .windows(utc(2024, 12, 1, 0, 0), utc(2025, 2, 1, 0, 0))
+            .unwrap();
+        assert_eq!(windows.len(), 2);
+    }
+
+    #[test]
+    fn test_overlapping_occurrences_merge_and_clip() {
+        let schedule = Schedule::new(
+            Frequency::Hourly,
+            jst(2025, 1, 1, 0, 0),
+            TimeDelta::minutes(90),
+        );
+        let from = utc(2025, 1, 1, 0, 15);
+        let to = utc(2025, 1, 1, 6, 0);
+        assert_eq!(schedule.windows(from, to).unwrap(), vec![(from, to)]);
+
+        let set = schedule.to_set(from, to).unwrap();
+        assert_eq!(set.iter().count(), 1);
+        let id = set.iter().next().unwrap();
+        assert_eq!(id.i(), 900);
+        assert_eq!(
+            id.time_window(&TimeAxis::default()).unwrap(),
+            (from, Some(to))
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let start = jst(2025, 1, 1, 0, 0);
+        let hour = TimeDelta::hours(1);
+        for rule in [
+            "BYDAY=MO",
+            "FREQ=MONTHLY",
+            "FREQ=DAILY;BYDAY=XX",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;BYHOUR=24",
+            "FREQ=DAILY;FREQ=WEEKLY",
+            "FREQ=DAILY;COUNT=3",
+            "FREQ=DAILY;UNTIL=2025",
+        ] {
+            assert!(Schedule::from_rrule(rule, start, hour).is_err(), "{}", rule);
+        }
+        assert!(Schedule::from_rrule("FREQ=DAILY", start, TimeDelta::zero()).is_err());
+
+        let schedule = Schedule::from_rrule("FREQ=DAILY", start, hour).unwrap();
+        assert!(
+            schedule
+                .windows(utc(2025, 1, 2, 0, 0), utc(2025, 1, 1, 0, 0))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_product_with_spatial_set() {
+        let a = SpaceTimeId::new(10, Single(0), Single(900), Single(400), 0, Any).unwrap();
+        let b = SpaceTimeId::new(10, LimitRange(0, 3), Single(902), Single(400), 0, Any).unwrap();
+        let area = unsafe { SpaceTimeIdSet::from_hash([a, b].into_iter().collect()) };
+
+        let schedule = Schedule::from_rrule(
+            "FREQ=DAILY;BYDAY=SA,SU",
+            jst(2025, 1, 4, 9, 0),
+            TimeDelta::hours(8),
+        )
+        .unwrap();
+        let times = schedule
+            .to_set(utc(2025, 1, 1, 0, 0), utc(2025, 1, 15, 0, 0))
+            .unwrap();
+        assert_eq!(times.iter().count(), 4);
+
+        let restricted = area.product(&times).unwrap();
+        assert_eq!(restricted.iter().count(), 8);
+        for id in restricted.iter() {
+            assert!(id.x() == a.x() || id.x() == b.x());
+            assert!(times.iter().any(|t| t.i() == id.i() && t.t() == id.t()));
+        }
+
+        // 時間 ID との直積や、重なる時間帯はエラー
+        assert!(restricted.product(&times).is_err());
+        assert!(area.product(&area).is_err());
+        let overlapping = unsafe {
+            SpaceTimeIdSet::from_hash(
+                [
+                    SpaceTimeId::new(0, Any, Any, Any, 60, LimitRange(0, 10)).unwrap(),
+                    SpaceTimeId::new(0, Any, Any, Any, 600, Single(1)).unwrap(),
+                ]
+                .into_iter()
+                .collect(),
+            )
+        };
+        assert!(area.product(&overlapping).is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use crate::id::DimensionRange::{AfterUnLimitRange, Any, LimitRange, Single};
+use crate::id::SpaceTimeId;
+use crate::id::serde_string::{self, PATTERN, SpaceTimeIdString};
+use crate::set::SpaceTimeIdSet;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+    struct Request {
+        #[serde(with = "serde_string")]
+        #[schemars(with = "SpaceTimeIdString")]
+        id: SpaceTimeId,
+        ids: Vec<SpaceTimeIdString>,
+    }
+
+    fn sample() -> SpaceTimeId {
+        SpaceTimeId::new(
+            4,
+            AfterUnLimitRange(10),
+            Single(5),
+            Single(3),
+            60,
+            LimitRange(100, 200),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_with_module_round_trip() {
+        let request = Request {
+            id: sample(),
+            ids: vec![SpaceTimeIdString(
+                SpaceTimeId::new(2, Single(0), Any, Single(1), 0, Any).unwrap(),
+            )],
+        };
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            value,
+            json!({ "id": "4/10:-/5/3_60/100:200", "ids": ["2/0/-/1_0/-"] })
+        );
+
+        let back: Request = serde_json::from_value(value).unwrap();
+        assert_eq!(back, request);
+    }
+
+    #[test]
+    fn test_newtype_round_trip() {
+        let text = serde_json::to_string(&SpaceTimeIdString(sample())).unwrap();
+        assert_eq!(text, "\"4/10:-/5/3_60/100:200\"");
+        let back: SpaceTimeIdString = serde_json::from_str(&text).unwrap();
+        assert_eq!(SpaceTimeId::from(back), sample());
+
+        // 時間の部分は省略できる
+        let spatial: SpaceTimeIdString = serde_json::from_str("\"4/10/5/3\"").unwrap();
+        assert_eq!(spatial.0.i(), 0);
+    }
+
+    #[test]
+    fn test_invalid_strings_are_rejected() {
+        assert!(serde_json::from_str::<SpaceTimeIdString>("\"4/10/99/3\"").is_err());
+        assert!(serde_json::from_str::<SpaceTimeIdString>("\"hello\"").is_err());
+        assert!(serde_json::from_str::<SpaceTimeIdString>("42").is_err());
+        assert!(serde_json::from_value::<Request>(json!({ "id": {"z": 1}, "ids": [] })).is_err());
+    }
+
+    #[test]
+    fn test_schema_has_pattern() {
+        let schema = serde_json::to_value(schemars::schema_for!(Request)).unwrap();
+        let definition = &schema["$defs"]["SpaceTimeIdString"];
+        assert_eq!(definition["type"], "string");
+        assert_eq!(definition["pattern"], PATTERN);
+        assert_eq!(
+            schema["properties"]["id"]["$ref"],
+            "#/$defs/SpaceTimeIdString"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_wrapping() {
+        #[derive(Deserialize)]
+        struct Area {
+            #[serde(deserialize_with = "serde_string::deserialize_wrapping")]
+            area: SpaceTimeIdSet,
+        }
+
+        let area: Area = serde_json::from_value(json!({ "area": "3/0/6:1/2" })).unwrap();
+        let mut ids: Vec<_> = area.area.iter().map(|id| id.to_string()).collect();
+        ids.sort();
+        assert_eq!(ids, ["3/0/-:1/2_0/-", "3/0/6:-/2_0/-"]);
+
+        // ID 1 つとして読むと誤り
+        assert!(serde_json::from_value::<SpaceTimeIdString>(json!("3/0/6:1/2")).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+use crate::function::{
+    cone::cone,
+    cylinder::cylinder,
+    shape::InclusionMode::{Inner, Outer},
+    sphere::sphere,
+    tools::{point_to_ecef::point_to_ecef, point_to_id::point_to_id},
+};
+use crate::id::{
+    DimensionRange::{LimitRange, Single},
+    SpaceTimeId,
+    coordinates::Point,
+};
+use crate::set::SpaceTimeIdSet;
+use std::collections::{HashMap, HashSet};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokyo(altitude: f64) -> Point {
+        Point {
+            latitude: 35.6809591,
+            longitude: 139.7673068,
+            altitude,
+        }
+    }
+
+    fn voxels(set: &SpaceTimeIdSet) -> HashSet<SpaceTimeId> {
+        set.pure().into_iter().collect()
+    }
+
+    /// 列ごとの f の範囲 (1 列 1 ID であることも確かめる)
+    fn columns(set: &SpaceTimeIdSet) -> HashMap<(u32, u32), (i32, i32)> {
+        let mut result = HashMap::new();
+        for id in set.iter() {
+            let (Single(x), Single(y)) = (id.x(), id.y()) else {
+                panic!("列ではない ID: {}", id);
+            };
+            let f = match id.f() {
+                Single(f) => (f, f),
+                LimitRange(start, end) => (start, end),
+                other => panic!("f の範囲が有限でない: {:?}", other),
+            };
+            assert!(result.insert((x, y), f).is_none());
+        }
+        result
+    }
+
+    fn distance(a: Point, b: Point) -> f64 {
+        let a = point_to_ecef(a);
+        let b = point_to_ecef(b);
+        ((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn test_sphere_inner_is_subset_of_outer() {
+        let inner = voxels(&sphere(20, tokyo(300.0), 200.0, Inner));
+        let outer = voxels(&sphere(20, tokyo(300.0), 200.0, Outer));
+
+        assert!(!inner.is_empty());
+        assert!(inner.len() < outer.len());
+        assert!(inner.is_subset(&outer));
+    }
+
+    #[test]
+    fn test_sphere_inner_vertices_within_radius() {
+        let center = tokyo(300.0);
+        for id in voxels(&sphere(20, center, 200.0, Inner)) {
+            for vertex in id.vertex() {
+                assert!(distance(vertex, center) <= 200.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sphere_outer_contains_center_voxel() {
+        let center = tokyo(300.0);
+        let outer = voxels(&sphere(20, center, 1.0, Outer));
+        assert!(outer.contains(&point_to_id(20, center).unwrap()));
+
+        // 半径がボクセルより小さいと内部判定のボクセルは存在しない
+        assert!(sphere(20, center, 1.0, Inner).is_empty());
+    }
+
+    #[test]
+    fn test_sphere_outer_voxels_touch_sphere() {
+        let center = tokyo(300.0);
+        for id in voxels(&sphere(18, center, 300.0, Outer)) {
+            let c = id.center();
+            // ボクセルの中心は半径 + ボクセルの対角線以内にある
+            assert!(distance(c, center) <= 300.0 + 250.0);
+        }
+    }
+
+    #[test]
+    fn test_sphere_invalid_radius_is_empty() {
+        assert!(sphere(20, tokyo(0.0), 0.0, Outer).is_empty());
+        assert!(sphere(20, tokyo(0.0), -5.0, Outer).is_empty());
+        assert!(sphere(20, tokyo(0.0), f64::NAN, Outer).is_empty());
+    }
+
+    #[test]
+    fn test_cylinder_contains_axis() {
+        let center = tokyo(0.0);
+        let inner = voxels(&cylinder(20, center, 150.0, 0.0, 400.0, Inner));
+        let outer = voxels(&cylinder(20, center, 150.0, 0.0, 400.0, Outer));
+
+        assert!(inner.is_subset(&outer));
+        assert!(inner.contains(&point_to_id(20, tokyo(200.0)).unwrap()));
+        assert!(!outer.contains(&point_to_id(20, tokyo(1000.0)).unwrap()));
+
+        // 外接判定では底面・上面に接するボクセルまでが含まれる
+        for id in &outer {
+            let coordinates = id.coordinates();
+            assert!(coordinates.altitude.1 >= 0.0);
+            assert!(coordinates.altitude.0 <= 400.0);
+        }
+    }
+
+    #[test]
+    fn test_cylinder_swapped_altitudes() {
+        let a = voxels(&cylinder(19, tokyo(0.0), 100.0, 0.0, 300.0, Outer));
+        let b = voxels(&cylinder(19, tokyo(0.0), 100.0, 300.0, 0.0, Outer));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_cone_contains_axis() {
+        let apex = tokyo(0.0);
+        let base = tokyo(600.0);
+        let outer = voxels(&cone(20, apex, base, 200.0, Outer));
+        let inner = voxels(&cone(20, apex, base, 200.0, Inner));
+
+        assert!(inner.is_subset(&outer));
+        assert!(outer.contains(&point_to_id(20, apex).unwrap()));
+        assert!(inner.contains(&point_to_id(20, tokyo(500.0)).unwrap()));
+        // 頂点付近は細いので内部判定にならない
+        assert!(!inner.contains(&point_to_id(20, apex).unwrap()));
+    }
+
+    #[test]
+    fn test_sphere_at_high_zoom() {
+        // z = 25 (約 1 m) で半径 500 m の球。列ごとに f の範囲を求めるので 1 列 1 ID になる
+        let center = tokyo(300.5);
+        let outer = columns(&sphere(25, center, 500.0, Outer));
+        let inner = columns(&sphere(25, center, 500.0, Inner));
+
+        // 中心の列は球の最下点から最上点まで
+        let id = point_to_id(25, center).unwrap();
+        let (Single(x), Single(y)) = (id.x(), id.y()) else {
+            unreachable!()
+        };
+        assert_eq!(outer[&(x, y)], (-200, 800));
+        assert_eq!(inner[&(x, y)], (-199, 799));
+
+        for (column, (start, end)) in &inner {
+            let (outer_start, outer_end) = outer[column];
+            assert!(outer_start <= *start && *end <= outer_end);
+        }
+
+        // 列の数はおおよそ球の断面積を列の面積で割った数
+        let cell = 2.0 * std::f64::consts::PI * 6_378_137.0 * center.latitude.to_radians().cos()
+            / 2_f64.powi(25);
+        let expected = std::f64::consts::PI * 500.0 * 500.0 / (cell * cell);
+        assert!((outer.len() as f64 / expected - 1.0).abs() < 0.01);
+        assert!((inner.len() as f64 / expected - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_tilted_cone_matches_points() {
+        // 進入経路のように傾いた円錐。内部の点は外接判定のボクセルに入り、
+        // 内部判定のボクセルの頂点はすべて円錐の中にある
+        let apex = tokyo(50.0);
+        let base = Point {
+            latitude: 35.6822,
+            longitude: 139.7690,
+            altitude: 180.0,
+        };
+        let outer = voxels(&cone(21, apex, base, 40.0, Outer));
+        let inner = voxels(&cone(21, apex, base, 40.0, Inner));
+        assert!(!inner.is_empty());
+
+        let a = point_to_ecef(apex);
+        let b = point_to_ecef(base);
+        let height = distance(apex, base);
+        let inside = |p: Point| {
+            let p = point_to_ecef(p);
+            let along =
+                ((p.x - a.x) * (b.x - a.x) + (p.y - a.y) * (b.y - a.y) + (p.z - a.z) * (b.z - a.z))
+                    / height;
+            let squared = (p.x - a.x).powi(2) + (p.y - a.y).powi(2) + (p.z - a.z).powi(2);
+            let across = (squared - along * along).max(0.0).sqrt();
+            (0.0..=height).contains(&along) && across <= 40.0 * along / height + 1e-6
+        };
+
+        for id in &inner {
+            for vertex in id.vertex() {
+                assert!(inside(vertex));
+            }
+        }
+        for i in 0..=40 {
+            for j in 0..=40 {
+                for k in 0..=40 {
+                    let t = |n: i32| n as f64 / 40.0;
+                    let point = Point {
+                        latitude: apex.latitude
+                            + (base.latitude - apex.latitude) * (1.4 * t(i) - 0.2),
+                        longitude: apex.longitude
+                            + (base.longitude - apex.longitude) * (1.4 * t(j) - 0.2),
+                        altitude: 40.0 + 160.0 * t(k),
+                    };
+                    if inside(point) {
+                        assert!(outer.contains(&point_to_id(21, point).unwrap()));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use crate::id::DimensionRange::{Any, LimitRange, Single};
+use crate::id::SpaceTimeId;
+use crate::set::SpaceTimeIdSet;
+use crate::set::simplify::SimplifyMode;
+use std::collections::HashSet;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voxels(set: &SpaceTimeIdSet) -> HashSet<SpaceTimeId> {
+        set.pure().into_iter().collect()
+    }
+
+    fn set_of(xs: &[(u32, u32)]) -> SpaceTimeIdSet {
+        let ids = xs
+            .iter()
+            .map(|&(x0, x1)| {
+                let x = if x0 == x1 {
+                    Single(x0)
+                } else {
+                    LimitRange(x0, x1)
+                };
+                SpaceTimeId::new(10, Single(0), x, Single(5), 0, Any).unwrap()
+            })
+            .collect();
+        SpaceTimeIdSet::from_disjoint(ids)
+    }
+
+    /// y = 5 の 1 ボクセルの体積。2 つから 1 つを捨てたときに減る体積
+    fn voxel_volume() -> f64 {
+        let two = set_of(&[(0, 0), (2, 2)]);
+        two.simplify(1, SimplifyMode::Subset).unwrap().volume
+    }
+
+    #[test]
+    fn test_simplify_superset() {
+        // 1 つおきに並んだ 6 ボクセル
+        let set = set_of(&[(0, 0), (2, 2), (4, 4), (6, 6), (8, 8), (10, 10)]);
+        let original = voxels(&set);
+
+        let simplified = set.simplify(3, SimplifyMode::Superset).unwrap();
+        assert!(simplified.set.iter().count() <= 3);
+        let covered = voxels(&simplified.set);
+        assert!(covered.is_superset(&original));
+
+        // 埋めたすき間の分だけ体積が増える
+        let gaps = (covered.len() - original.len()) as f64;
+        assert!(gaps > 0.0);
+        assert!((simplified.volume - gaps * voxel_volume()).abs() < 1e-6 * simplified.volume);
+
+        let one = set.simplify(1, SimplifyMode::Superset).unwrap();
+        assert_eq!(voxels(&one.set).len(), 11);
+        assert!(set.simplify(0, SimplifyMode::Superset).is_err());
+    }
+
+    #[test]
+    fn test_simplify_subset() {
+        let set = set_of(&[(0, 3), (10, 10), (20, 21)]);
+        let original = voxels(&set);
+
+        let simplified = set.simplify(1, SimplifyMode::Subset).unwrap();
+        let kept = voxels(&simplified.set);
+        assert!(kept.is_subset(&original));
+        assert_eq!(kept.len(), 4);
+        assert!((simplified.volume - 3.0 * voxel_volume()).abs() < 1e-6 * simplified.volume);
+
+        assert!(
+            set.simplify(0, SimplifyMode::Subset)
+                .unwrap()
+                .set
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_simplify_subset_shrinks_to_largest_box() {
+        // f = 0 に x = 0..=9、f = 1 に x = 0..=7。1 つの ID なら f = 0..=1, x = 0..=7 が最大
+        let set = SpaceTimeIdSet::from_disjoint(vec![
+            SpaceTimeId::new(10, Single(0), LimitRange(0, 9), Single(5), 0, Any).unwrap(),
+            SpaceTimeId::new(10, Single(1), LimitRange(0, 7), Single(5), 0, Any).unwrap(),
+        ]);
+        let original = voxels(&set);
+
+        let simplified = set.simplify(1, SimplifyMode::Subset).unwrap();
+        assert_eq!(simplified.set.iter().count(), 1);
+        let kept = voxels(&simplified.set);
+        assert!(kept.is_subset(&original));
+        assert_eq!(kept.len(), 16);
+
+        // 捨てたのは f = 0 の x = 8, 9 の 2 ボクセルだけ
+        assert!((simplified.volume - 2.0 * voxel_volume()).abs() < 1e-6 * simplified.volume);
+    }
+
+    #[test]
+    fn test_simplify_within() {
+        let set = set_of(&[(0, 0), (2, 2), (4, 4), (10, 10)]);
+        let original = voxels(&set);
+        let volume = voxel_volume();
+
+        // すき間 2 つまで埋めてよいなら、0〜4 だけがまとまる
+        let simplified = set
+            .simplify_within(2.5 * volume, SimplifyMode::Superset)
+            .unwrap();
+        assert_eq!(simplified.set.iter().count(), 2);
+        assert!(voxels(&simplified.set).is_superset(&original));
+        assert!((simplified.volume - 2.0 * volume).abs() < 1e-6 * volume);
+
+        let simplified = set
+            .simplify_within(1.5 * volume, SimplifyMode::Subset)
+            .unwrap();
+        assert_eq!(voxels(&simplified.set).len(), 3);
+
+        // 目標をすでに満たしていれば何もしない
+        let same = set.simplify(4, SimplifyMode::Superset).unwrap();
+        assert_eq!(voxels(&same.set), original);
+        assert_eq!(same.volume, 0.0);
+
+        assert!(set.simplify_within(-1.0, SimplifyMode::Subset).is_err());
+    }
+}
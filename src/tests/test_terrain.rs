@@ -0,0 +1,119 @@
+use crate::function::tools::point_to_id::point_to_id;
+use crate::geoid::{AltitudeReference, ConstantGeoid};
+use crate::id::DimensionRange::{BeforeUnLimitRange, LimitRange, Single};
+use crate::id::coordinates::Point;
+use crate::terrain::dem::Dem;
+use crate::terrain::{agl_band, terrain_solid};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 0.01° 四方のセル 2 x 2。北西 100 m, 北東 120 m, 南西 欠測, 南東 80 m
+    const DEM: &str = "ncols 2
+nrows 2
+xllcorner 139.70
+yllcorner 35.60
+cellsize 0.01
+NODATA_value -9999
+100 120
+-9999 80
+";
+
+    #[test]
+    fn test_dem_read() {
+        let dem = Dem::read(DEM.as_bytes()).unwrap();
+        let ((west, east), (south, north)) = dem.bounds();
+        assert!((west - 139.70).abs() < 1e-12 && (east - 139.72).abs() < 1e-12);
+        assert!((south - 35.60).abs() < 1e-12 && (north - 35.62).abs() < 1e-12);
+        assert_eq!(dem.height(35.615, 139.705), Some(100.0));
+        assert_eq!(dem.height(35.605, 139.715), Some(80.0));
+        assert_eq!(dem.height(35.605, 139.705), None);
+        assert_eq!(dem.height(35.0, 139.705), None);
+        assert_eq!(
+            dem.height_range((139.70, 139.72), (35.60, 35.62)),
+            Some((80.0, 120.0))
+        );
+
+        let centered = DEM
+            .replace("xllcorner 139.70", "XLLCENTER 139.705")
+            .replace("yllcorner", "YLLCORNER");
+        let ((w, e), (s, n)) = Dem::read(centered.as_bytes()).unwrap().bounds();
+        assert!((w - west).abs() < 1e-9 && (e - east).abs() < 1e-9);
+        assert!((s - south).abs() < 1e-9 && (n - north).abs() < 1e-9);
+
+        assert!(Dem::read("ncols 2\nnrows 2\ncellsize 1\n1 2 3 4".as_bytes()).is_err());
+        let short = DEM.replace("-9999 80", "-9999");
+        assert!(Dem::read(short.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_terrain_solid() {
+        let dem = Dem::read(DEM.as_bytes()).unwrap();
+        let z = 18;
+        let solid = terrain_solid(&dem, z, None, AltitudeReference::Ellipsoidal).unwrap();
+        assert!(!solid.is_empty());
+
+        // 北西のセルの上のボクセル列は 100 m まで埋まる (z = 18 では 1 ボクセル 128 m)
+        let inside = point_to_id(
+            z,
+            Point {
+                latitude: 35.618,
+                longitude: 139.702,
+                altitude: 50.0,
+            },
+        )
+        .unwrap();
+        let column = solid
+            .iter()
+            .find(|id| id.x() == inside.x() && id.y() == inside.y())
+            .unwrap();
+        assert_eq!(column.f(), BeforeUnLimitRange(0));
+
+        // 床を指定すると床から
+        let floored = terrain_solid(&dem, z, Some(-200.0), AltitudeReference::Ellipsoidal).unwrap();
+        for id in floored.iter() {
+            assert!(matches!(id.f(), LimitRange(-2, _)), "{}", id);
+        }
+    }
+
+    #[test]
+    fn test_agl_band() {
+        let dem = Dem::read(DEM.as_bytes()).unwrap();
+        let z = 22;
+        // 1 ボクセル 8 m。北西セル (100 m) の上 0〜150 m は f = 12 (96 m) から f = 31 (248〜256 m)
+        let band = agl_band(&dem, z, 0.0, 150.0, AltitudeReference::Ellipsoidal).unwrap();
+        let inside = point_to_id(
+            z,
+            Point {
+                latitude: 35.618,
+                longitude: 139.702,
+                altitude: 0.0,
+            },
+        )
+        .unwrap();
+        let column = band
+            .iter()
+            .find(|id| id.x() == inside.x() && id.y() == inside.y())
+            .unwrap();
+        assert_eq!(column.f(), LimitRange(12, 31));
+
+        // 標高 (ジオイド高 40 m) で与えると 40 m 上にずれる
+        let geoid = ConstantGeoid::new(40.0);
+        let band = agl_band(&dem, z, 0.0, 150.0, AltitudeReference::Orthometric(&geoid)).unwrap();
+        let column = band
+            .iter()
+            .find(|id| id.x() == inside.x() && id.y() == inside.y())
+            .unwrap();
+        assert_eq!(column.f(), LimitRange(17, 36));
+
+        let thin = agl_band(&dem, z, 0.0, 1.0, AltitudeReference::Ellipsoidal).unwrap();
+        let column = thin
+            .iter()
+            .find(|id| id.x() == inside.x() && id.y() == inside.y())
+            .unwrap();
+        assert_eq!(column.f(), Single(12));
+
+        assert!(agl_band(&dem, z, 150.0, 0.0, AltitudeReference::Ellipsoidal).is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use crate::id::DimensionRange::{AfterUnLimitRange, Any, LimitRange, Single};
+use crate::id::SpaceTimeId;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn id(z: u8, f: i32, x: u32, y: u32) -> SpaceTimeId {
+        SpaceTimeId::new(z, Single(f), Single(x), Single(y), 0, Any).unwrap()
+    }
+
+    /// ID を z ズームの単一ボクセルに展開する (T は無視)
+    fn voxels(id: &SpaceTimeId, z: u8) -> HashSet<(i32, u32, u32)> {
+        id.scale(Some(z), None)
+            .unwrap()
+            .pure()
+            .iter()
+            .map(|v| (v.f_bounds().0, v.x_bounds().0, v.y_bounds().0))
+            .collect()
+    }
+
+    #[test]
+    fn test_tilehash_digits() {
+        // |f| = 0b01, x = 0b10, y = 0b01
+        assert_eq!(id(2, -1, 2, 1).to_tilehash().unwrap(), "-27");
+        assert_eq!(id(2, 1, 2, 1).to_tilehash().unwrap(), "27");
+        assert_eq!(id(0, 0, 0, 0).to_tilehash().unwrap(), "");
+        assert_eq!(id(1, 1, 1, 1).to_tilehash().unwrap(), "8");
+    }
+
+    #[test]
+    fn test_tilehash_round_trip() {
+        for z in 0..=4_u8 {
+            let n = 1_i32 << z;
+            let mut seen = HashSet::new();
+            for f in -n + 1..n {
+                for x in 0..n as u32 {
+                    for y in 0..n as u32 {
+                        let v = id(z, f, x, y);
+                        let hash = v.to_tilehash().unwrap();
+                        assert!(seen.insert(hash.clone()));
+                        assert_eq!(SpaceTimeId::from_tilehash(&hash), Ok(v));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_tilehash_errors() {
+        // f = -2^z は z 桁では書けない
+        assert!(id(3, -8, 0, 0).to_tilehash().is_err());
+        let range = SpaceTimeId::new(3, LimitRange(0, 1), Single(0), Single(0), 0, Any).unwrap();
+        assert!(range.to_tilehash().is_err());
+
+        assert!(SpaceTimeId::from_tilehash("129").is_err());
+        assert!(SpaceTimeId::from_tilehash("1-2").is_err());
+        assert!(SpaceTimeId::from_tilehash("-").is_err());
+        assert!(SpaceTimeId::from_tilehash("-123").is_err());
+        assert!(SpaceTimeId::from_tilehash(&"1".repeat(32)).is_err());
+        assert!(SpaceTimeId::from_tilehash(&"8".repeat(31)).is_ok());
+    }
+
+    #[test]
+    fn test_tilehashes_cover_exactly() {
+        let ids = [
+            SpaceTimeId::new(
+                3,
+                LimitRange(-5, 6),
+                LimitRange(1, 6),
+                LimitRange(0, 7),
+                0,
+                Any,
+            )
+            .unwrap(),
+            SpaceTimeId::new(3, AfterUnLimitRange(0), Any, Any, 0, Any).unwrap(),
+            SpaceTimeId::new(4, Single(-3), LimitRange(4, 11), Single(9), 60, Single(3)).unwrap(),
+        ];
+        for v in &ids {
+            let hashes = v.to_tilehashes().unwrap();
+            let mut covered = HashSet::new();
+            for hash in &hashes {
+                let part = voxels(&SpaceTimeId::from_tilehash(hash).unwrap(), v.z());
+                assert!(covered.is_disjoint(&part), "{:?}", hashes);
+                covered.extend(part);
+            }
+            assert_eq!(covered, voxels(v, v.z()), "{}", v);
+        }
+
+        // f >= 0 の全体は z = 0 の 1 つの tilehash になる
+        assert_eq!(ids[1].to_tilehashes().unwrap(), vec![String::new()]);
+
+        // f = -2^z を含む ID は書けない
+        let all = SpaceTimeId::new(3, Any, Any, Any, 0, Any).unwrap();
+        assert!(all.to_tilehashes().is_err());
+    }
+
+    #[test]
+    fn test_quadkey() {
+        assert_eq!(id(3, 0, 3, 5).to_quadkey().unwrap(), "213");
+        assert_eq!(id(0, 0, 0, 0).to_quadkey().unwrap(), "");
+
+        let range = SpaceTimeId::new(3, Any, LimitRange(2, 5), LimitRange(0, 3), 0, Any).unwrap();
+        assert!(range.to_quadkey().is_err());
+        let mut quadkeys = range.to_quadkeys();
+        quadkeys.sort();
+        // x = 2..=3, 4..=5 と y = 0..=3 は z = 2 の 4 タイルになる
+        assert_eq!(quadkeys, vec!["01", "03", "10", "12"]);
+    }
+}
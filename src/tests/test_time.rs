@@ -0,0 +1,126 @@
+use crate::id::DimensionRange::{AfterUnLimitRange, Any, LimitRange, Single};
+use crate::id::SpaceTimeId;
+use crate::id::time::{TimeAxis, TimeRounding};
+use chrono::{DateTime, TimeZone, Utc};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(h: u32, m: u32, s: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, h, m, s).unwrap()
+    }
+
+    fn spatial() -> SpaceTimeId {
+        SpaceTimeId::new(10, Single(0), Single(900), Single(400), 0, Any).unwrap()
+    }
+
+    #[test]
+    fn test_aligned_window() {
+        let axis = TimeAxis::default();
+        let id = spatial()
+            .with_time(
+                3600,
+                at(1, 0, 0),
+                Some(at(4, 0, 0)),
+                &axis,
+                TimeRounding::Outward,
+            )
+            .unwrap();
+        let base = (at(0, 0, 0).timestamp() / 3600) as u32;
+        assert_eq!(id.i(), 3600);
+        assert_eq!(id.t(), LimitRange(base + 1, base + 3));
+        assert_eq!(id.time_window(&axis), Ok((at(1, 0, 0), Some(at(4, 0, 0)))));
+
+        // 揃っていれば丸め方によらない
+        let inward = spatial()
+            .with_time(
+                3600,
+                at(1, 0, 0),
+                Some(at(4, 0, 0)),
+                &axis,
+                TimeRounding::Inward,
+            )
+            .unwrap();
+        assert_eq!(inward, id);
+    }
+
+    #[test]
+    fn test_rounding() {
+        let axis = TimeAxis::new(at(0, 0, 0));
+        let window = (at(0, 10, 0), Some(at(2, 50, 0)));
+
+        let outward = axis
+            .t_range(3600, window.0, window.1, TimeRounding::Outward)
+            .unwrap();
+        assert_eq!(outward, LimitRange(0, 2));
+        let inward = axis
+            .t_range(3600, window.0, window.1, TimeRounding::Inward)
+            .unwrap();
+        assert_eq!(inward, Single(1));
+
+        // 1 区間も収まらない
+        assert!(
+            axis.t_range(3600, at(0, 10, 0), Some(at(0, 50, 0)), TimeRounding::Inward)
+                .is_err()
+        );
+        assert_eq!(
+            axis.t_range(
+                3600,
+                at(0, 10, 0),
+                Some(at(0, 50, 0)),
+                TimeRounding::Outward
+            ),
+            Ok(Single(0))
+        );
+
+        // 1 ナノ秒のずれも外側に丸める
+        let end = at(1, 0, 0) + chrono::TimeDelta::nanoseconds(1);
+        assert_eq!(
+            axis.t_range(3600, at(0, 0, 0), Some(end), TimeRounding::Outward),
+            Ok(LimitRange(0, 1))
+        );
+    }
+
+    #[test]
+    fn test_custom_epoch_and_open_end() {
+        let axis = TimeAxis::new(at(12, 0, 0));
+        let id = spatial()
+            .with_time(60, at(12, 30, 0), None, &axis, TimeRounding::Outward)
+            .unwrap();
+        assert_eq!(id.t(), AfterUnLimitRange(30));
+        assert_eq!(id.time_window(&axis), Ok((at(12, 30, 0), None)));
+
+        // 同じ ID でも基準時刻が違えば別の時刻になる
+        let (start, _) = id.time_window(&TimeAxis::default()).unwrap();
+        assert_eq!(
+            start,
+            DateTime::UNIX_EPOCH + chrono::TimeDelta::seconds(1800)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let axis = TimeAxis::new(at(12, 0, 0));
+        // 基準時刻より前
+        assert!(
+            axis.t_range(60, at(11, 0, 0), None, TimeRounding::Outward)
+                .is_err()
+        );
+        // 空の区間
+        assert!(
+            axis.t_range(60, at(13, 0, 0), Some(at(13, 0, 0)), TimeRounding::Outward)
+                .is_err()
+        );
+        // 間隔 0
+        assert!(
+            axis.t_range(0, at(13, 0, 0), None, TimeRounding::Outward)
+                .is_err()
+        );
+        // u32 に収まらない
+        let far = at(12, 0, 0) + chrono::TimeDelta::seconds(5_000_000_000);
+        assert!(axis.t_range(1, far, None, TimeRounding::Outward).is_err());
+        // 空間 ID には時間帯がない
+        assert!(spatial().time_window(&axis).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use crate::id::DimensionRange::{AfterUnLimitRange, Any, BeforeUnLimitRange, LimitRange, Single};
+use crate::id::SpaceTimeId;
+use crate::id::time::{TimeAxis, TimePoint};
+use crate::set::SpaceTimeIdSet;
+use chrono::{DateTime, TimeDelta, Utc};
+use std::collections::HashSet;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(x: u32, i: u32, t: crate::id::DimensionRange<u32>) -> SpaceTimeId {
+        SpaceTimeId::new(10, Single(0), Single(x), Single(400), i, t).unwrap()
+    }
+
+    fn set(ids: &[SpaceTimeId]) -> SpaceTimeIdSet {
+        unsafe { SpaceTimeIdSet::from_hash(ids.iter().copied().collect()) }
+    }
+
+    /// 時間 ID の (x, 開始 [秒], 終了 [秒]) の集合
+    fn windows(set: &SpaceTimeIdSet) -> HashSet<(u32, i64, Option<i64>)> {
+        set.iter()
+            .filter(|id| id.i() != 0)
+            .map(|id| {
+                let (start, end) = id.time_window(&TimeAxis::default()).unwrap();
+                (
+                    id.x_bounds().0,
+                    start.timestamp(),
+                    end.map(|e| e.timestamp()),
+                )
+            })
+            .collect()
+    }
+
+    fn sample() -> SpaceTimeIdSet {
+        set(&[
+            id(1, 60, LimitRange(10, 20)),
+            id(5, 0, Any),
+            id(9, 3600, Single(1)),
+            id(12, 60, AfterUnLimitRange(5)),
+        ])
+    }
+
+    #[test]
+    fn test_at_time() {
+        let snapshot = sample().at_time(TimePoint::Seconds(700)).unwrap();
+        let xs: HashSet<u32> = snapshot.iter().map(|id| id.x_bounds().0).collect();
+        assert_eq!(xs, HashSet::from([1, 5, 12]));
+        assert!(snapshot.iter().all(|id| id.i() == 0 && id.t() == Any));
+
+        let later = sample()
+            .at_time(TimePoint::Index { i: 3600, t: 1 })
+            .unwrap();
+        let xs: HashSet<u32> = later.iter().map(|id| id.x_bounds().0).collect();
+        assert_eq!(xs, HashSet::from([5, 9, 12]));
+
+        let time = DateTime::UNIX_EPOCH + TimeDelta::seconds(700);
+        let by_date: HashSet<SpaceTimeId> = sample()
+            .at_time(time.into())
+            .unwrap()
+            .iter()
+            .copied()
+            .collect();
+        assert_eq!(by_date, snapshot.iter().copied().collect());
+
+        let before: DateTime<Utc> = DateTime::UNIX_EPOCH - TimeDelta::seconds(1);
+        assert!(sample().at_time(before.into()).is_err());
+    }
+
+    #[test]
+    fn test_slice_aligned() {
+        let sliced = sample()
+            .slice_time(TimePoint::Seconds(900), Some(TimePoint::Seconds(1020)))
+            .unwrap();
+        assert_eq!(
+            windows(&sliced),
+            HashSet::from([
+                (1, 900, Some(1020)),
+                (5, 900, Some(1020)),
+                (12, 900, Some(1020))
+            ])
+        );
+        let x1 = sliced.iter().find(|id| id.x_bounds().0 == 1).unwrap();
+        assert_eq!((x1.i(), x1.t()), (60, LimitRange(15, 16)));
+    }
+
+    #[test]
+    fn test_slice_misaligned() {
+        let sliced = sample()
+            .slice_time(TimePoint::Seconds(630), Some(TimePoint::Seconds(700)))
+            .unwrap();
+        let x1 = sliced.iter().find(|id| id.x_bounds().0 == 1).unwrap();
+        // gcd(60, 630, 700) = 10
+        assert_eq!((x1.i(), x1.t()), (10, LimitRange(63, 69)));
+        assert_eq!(
+            windows(&sliced),
+            HashSet::from([
+                (1, 630, Some(700)),
+                (5, 630, Some(700)),
+                (12, 630, Some(700))
+            ])
+        );
+
+        // 開いた時間帯
+        let open = sample().slice_time(TimePoint::Seconds(1000), None).unwrap();
+        assert_eq!(
+            windows(&open),
+            HashSet::from([
+                (1, 1000, Some(1260)),
+                (5, 1000, None),
+                (9, 3600, Some(7200)),
+                (12, 1000, None)
+            ])
+        );
+
+        assert!(
+            sample()
+                .slice_time(TimePoint::Seconds(10), Some(TimePoint::Seconds(10)))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_shift_time() {
+        let plan = set(&[
+            id(1, 60, Single(10)),
+            id(5, 0, Any),
+            id(12, 60, AfterUnLimitRange(5)),
+        ]);
+
+        let later = plan.shift_time(30).unwrap();
+        let x1 = later.iter().find(|id| id.x_bounds().0 == 1).unwrap();
+        assert_eq!((x1.i(), x1.t()), (30, LimitRange(21, 22)));
+        let x12 = later.iter().find(|id| id.x_bounds().0 == 12).unwrap();
+        assert_eq!(
+            x12.time_window(&TimeAxis::default()).unwrap().0.timestamp(),
+            330
+        );
+
+        // 空間 ID は変わらない
+        assert!(later.iter().any(|id| *id == self::id(5, 0, Any)));
+
+        let earlier = plan.shift_time(-300).unwrap();
+        let x1 = earlier.iter().find(|id| id.x_bounds().0 == 1).unwrap();
+        assert_eq!(x1.t(), Single(5));
+
+        // 元に戻せる
+        let back = later.shift_time(-30).unwrap();
+        assert_eq!(windows(&back), windows(&plan));
+
+        // UNIX 時刻より前には動かせない
+        assert!(plan.shift_time(-301).is_err());
+    }
+
+    #[test]
+    fn test_shift_time_open_ranges() {
+        let plan = set(&[id(1, 60, Any), id(2, 60, BeforeUnLimitRange(10))]);
+        let x = |set: &SpaceTimeIdSet, x: u32| *set.iter().find(|id| id.x_bounds().0 == x).unwrap();
+
+        // 全時間の ID はどちらにずらしても全時間のまま
+        for seconds in [120, -120, 30] {
+            let shifted = plan.shift_time(seconds).unwrap();
+            assert_eq!(x(&shifted, 1), id(1, 60, Any));
+        }
+
+        // 終わりだけが動く
+        let later = plan.shift_time(120).unwrap();
+        assert_eq!(x(&later, 2), id(2, 60, BeforeUnLimitRange(12)));
+        let earlier = plan.shift_time(-600).unwrap();
+        assert_eq!(x(&earlier, 2), id(2, 60, BeforeUnLimitRange(0)));
+        let half = plan.shift_time(-30).unwrap();
+        assert_eq!(
+            (x(&half, 2).i(), x(&half, 2).t()),
+            (30, BeforeUnLimitRange(20))
+        );
+
+        // 終わりが UNIX 時刻より前になるならエラー
+        assert!(plan.shift_time(-660).is_err());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::id::DimensionRange::{AfterUnLimitRange, Any, LimitRange, Single};
+use crate::id::SpaceTimeId;
+use crate::set::SpaceTimeIdSet;
+use std::collections::HashSet;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (f, x, y, t) の単一ボクセルの集合
+    fn voxels<'a, I: IntoIterator<Item = &'a SpaceTimeId>>(ids: I) -> HashSet<SpaceTimeId> {
+        ids.into_iter().flat_map(|id| id.pure()).collect()
+    }
+
+    #[test]
+    fn test_translate_id() {
+        let id = SpaceTimeId::new(
+            5,
+            LimitRange(1, 3),
+            Single(4),
+            LimitRange(10, 12),
+            60,
+            Single(7),
+        )
+        .unwrap();
+        let moved = id.translate(2, -3, 4).unwrap();
+        assert_eq!(
+            moved,
+            vec![
+                SpaceTimeId::new(
+                    5,
+                    LimitRange(5, 7),
+                    Single(6),
+                    LimitRange(7, 9),
+                    60,
+                    Single(7)
+                )
+                .unwrap()
+            ]
+        );
+        assert_eq!(id.translate(0, 0, 0).unwrap(), vec![id]);
+    }
+
+    #[test]
+    fn test_translate_wraps_across_antimeridian() {
+        // z = 4 では x は 0..=15
+        let id = SpaceTimeId::new(4, Single(0), LimitRange(12, 14), Single(3), 0, Any).unwrap();
+        let moved = id.translate(3, 0, 0).unwrap();
+        let expected = [
+            SpaceTimeId::new(4, Single(0), Single(15), Single(3), 0, Any).unwrap(),
+            SpaceTimeId::new(4, Single(0), LimitRange(0, 1), Single(3), 0, Any).unwrap(),
+        ];
+        assert_eq!(voxels(&moved), voxels(&expected));
+        assert_eq!(moved.len(), 2);
+
+        // 負の方向にも回り込む
+        let single = SpaceTimeId::new(4, Single(0), Single(1), Single(3), 0, Any).unwrap();
+        assert_eq!(single.translate(-3, 0, 0).unwrap()[0].x(), Single(14));
+
+        // x 全体は動かしても全体のまま
+        let band = SpaceTimeId::new(4, Single(0), Any, Single(3), 0, Any).unwrap();
+        assert_eq!(band.translate(5, 0, 0).unwrap(), vec![band]);
+    }
+
+    #[test]
+    fn test_translate_out_of_bounds() {
+        let id = SpaceTimeId::new(4, Single(14), Single(0), Single(15), 0, Any).unwrap();
+        assert!(id.translate(0, 1, 0).is_err());
+        assert!(id.translate(0, -16, 0).is_err());
+        assert!(id.translate(0, 0, 2).is_err());
+        assert!(id.translate(0, 0, -31).is_err());
+        assert!(id.translate(0, 0, -30).is_ok());
+        assert!(id.translate(0, 0, 1).is_ok());
+
+        let open = SpaceTimeId::new(4, AfterUnLimitRange(3), Single(0), Single(0), 0, Any).unwrap();
+        assert!(open.translate(0, 0, 1).is_err());
+    }
+
+    #[test]
+    fn test_translate_set() {
+        let a = SpaceTimeId::new(6, Single(0), LimitRange(60, 62), Single(20), 0, Any).unwrap();
+        // 粗いズームの ID は最も細かいズームで動かす
+        let b = SpaceTimeId::new(5, Single(1), Single(10), Single(12), 0, Any).unwrap();
+        let set = unsafe { SpaceTimeIdSet::from_hash([a, b].into_iter().collect()) };
+
+        let moved = set.translate(3, 1, -1).unwrap();
+        let mut expected = a.translate(3, 1, -1).unwrap();
+        expected.extend(b.scale(Some(6), None).unwrap().translate(3, 1, -1).unwrap());
+        assert_eq!(voxels(moved.iter()), voxels(&expected));
+        assert!(moved.iter().any(|id| id.x_bounds().0 == 0));
+
+        assert!(set.translate(0, 100, 0).is_err());
+        assert!(SpaceTimeIdSet::new().translate(1, 1, 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_translate_meters() {
+        // 赤道付近の z = 20 のボクセルは約 38 m 四方、高さ 32 m
+        let id =
+            SpaceTimeId::new(20, Single(10), Single(524_288), Single(524_287), 0, Any).unwrap();
+        let moved = id.translate_meters(380.0, 190.0, 64.0).unwrap();
+        assert_eq!(moved.len(), 1);
+        assert_eq!(moved[0].x(), Single(524_298));
+        assert_eq!(moved[0].y(), Single(524_282));
+        assert_eq!(moved[0].f(), Single(12));
+
+        // 高緯度ではボクセルが小さいので、同じ距離でも多く動く
+        let north =
+            SpaceTimeId::new(20, Single(10), Single(524_288), Single(200_000), 0, Any).unwrap();
+        let dx = match north.translate_meters(380.0, 0.0, 0.0).unwrap()[0].x() {
+            Single(x) => x - 524_288,
+            other => panic!("{:?}", other),
+        };
+        assert!(dx > 10);
+
+        let set = SpaceTimeIdSet::from(id);
+        let moved_set = set.translate_meters(380.0, 190.0, 64.0).unwrap();
+        assert_eq!(voxels(moved_set.iter()), voxels(&moved));
+
+        assert!(id.translate_meters(f64::NAN, 0.0, 0.0).is_err());
+    }
+}